@@ -1,17 +1,27 @@
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use futures_util::stream::{self, StreamExt};
+use regex::{Regex, RegexBuilder};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use sqlx::{Column, Row};
 use std::cmp::Ordering;
-use std::collections::{BTreeSet, HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Stdio;
-use tauri::{command, AppHandle, Emitter, Manager};
+use std::sync::atomic::{AtomicI64, Ordering as AtomicOrdering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Instant;
+use tauri::{command, AppHandle, Emitter};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWriteExt, BufReader};
 use tokio::process::Command;
 use tokio::time::{timeout, Duration};
 
-use crate::database::get_db_pool;
+use crate::database::{
+    get_data_dir_warning, get_db_init_error, get_db_path, get_db_pool, resolve_data_dir,
+};
 
 // ============= Types =============
 
@@ -23,6 +33,14 @@ pub struct Todo {
     pub priority: String,
     #[serde(rename = "createdAt")]
     pub created_at: Option<String>,
+    #[serde(rename = "completedAt")]
+    pub completed_at: Option<String>,
+    #[serde(rename = "projectId")]
+    pub project_id: Option<String>,
+    /// UTC `YYYY-MM-DD HH:MM:SS`; hidden from `get_todos` until then.
+    #[serde(rename = "snoozedUntil")]
+    pub snoozed_until: Option<String>,
+    pub pinned: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -32,6 +50,7 @@ pub struct Project {
     pub deadline: Option<String>,
     pub progress: i32,
     pub status: String,
+    pub pinned: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -41,6 +60,10 @@ pub struct CalendarEvent {
     pub date: String,
     pub color: String,
     pub note: Option<String>,
+    pub pinned: bool,
+    /// Set by create/update when the requested color was replaced with blue.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -51,6 +74,50 @@ pub struct PersonalTask {
     pub date: Option<String>,
     pub location: Option<String>,
     pub note: Option<String>,
+    pub completed: bool,
+    pub completed_at: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MonthDigest {
+    pub year_month: String,
+    pub start_date: String,
+    pub end_date: String,
+    pub events: Vec<CalendarEvent>,
+    pub projects: Vec<Project>,
+    pub personal_tasks: Vec<PersonalTask>,
+    pub total_budget: f64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkbenchStats {
+    pub todos_total: i64,
+    pub todos_completed: i64,
+    pub projects_active: i64,
+    pub projects_completed: i64,
+    /// Monday through Sunday of the current local week.
+    pub week_start: String,
+    pub week_end: String,
+    pub events_this_week: i64,
+    pub personal_tasks_total: i64,
+    pub personal_tasks_completed: i64,
+    pub personal_budget_total: f64,
+    pub personal_budget_pending: f64,
+    pub inspirations_total: i64,
+    pub info_items_today: i64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpcomingItem {
+    /// `event` for now; the list is typed so todos can be merged in later.
+    pub kind: String,
+    pub id: String,
+    pub title: String,
+    pub date: String,
+    pub color: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -58,6 +125,9 @@ pub struct PersonalTask {
 pub struct Inspiration {
     pub id: String,
     pub content: String,
+    /// `draft`, `published` or `archived`.
+    pub status: String,
+    /// Same as `status == "archived"`, kept for callers that predate `status`.
     pub is_archived: bool,
     pub created_at: String,
     pub updated_at: String,
@@ -74,6 +144,19 @@ pub struct InfoSource {
     pub is_preset: bool,
     pub created_at: Option<String>,
     pub updated_at: Option<String>,
+    /// `ok`, `empty` (parsed but no entries) or `error`; `None` until first fetched.
+    #[serde(default)]
+    pub last_fetch_status: Option<String>,
+    #[serde(default)]
+    pub last_fetch_error: Option<String>,
+    #[serde(default)]
+    pub last_fetched_at: Option<String>,
+    /// `none`, `basic` or `bearer`.
+    #[serde(default = "default_info_source_auth_type")]
+    pub auth_type: String,
+    /// `user:password` for basic, the token for bearer. Never sent to the frontend.
+    #[serde(default, skip_serializing)]
+    pub auth_value: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -83,6 +166,19 @@ pub struct InfoSettings {
     pub include_keywords: Vec<String>,
     pub exclude_keywords: Vec<String>,
     pub max_items_per_day: i32,
+    /// Feeds fetched in parallel during a refresh. Higher finishes sooner; lower is
+    /// gentler on metered links and per-IP rate limits.
+    pub max_concurrent_fetches: i32,
+    /// Days of fetched items kept around; 1 keeps only today.
+    pub retention_days: i32,
+    /// `HH:MM` local window (may cross midnight) in which scheduled refreshes are
+    /// deferred. Both are set or neither.
+    pub quiet_hours_start: Option<String>,
+    pub quiet_hours_end: Option<String>,
+    /// RFC3339 time of a one-shot refresh set by `schedule_info_refresh_in`; cleared
+    /// once the scheduled refresh it triggers runs.
+    #[serde(default)]
+    pub next_refresh_at: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -101,12 +197,51 @@ pub struct InfoItem {
 
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
+pub struct ExportInfoDigestResponse {
+    pub path: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportRefreshLogsResponse {
+    pub path: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InfoItemContent {
+    pub link: String,
+    pub title: Option<String>,
+    pub content: String,
+    pub fetched_at: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
 pub struct InfoRefreshResponse {
     pub success: bool,
+    /// Set when another refresh was already running, so this one did nothing.
+    pub skipped: bool,
     pub fetched_count: i32,
     pub kept_count: i32,
     pub message: String,
     pub refreshed_at: String,
+    pub trigger_type: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InfoSourceValidation {
+    pub source_id: String,
+    pub name: String,
+    /// `ok`, `empty` or `error`, same as `InfoSource::last_fetch_status`.
+    pub status: String,
+    pub title: Option<String>,
+    /// Entries in the feed before keyword filtering.
+    pub item_count: usize,
+    pub error: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -183,9 +318,30 @@ struct OpenMeteoCurrent {
     weather_code: i32,
 }
 
+/// Max characters of a provider error body kept in `agent_provider_errors`.
+const PROVIDER_ERROR_BODY_LIMIT: usize = 2000;
+
+/// Default cap on a feed response body; override with `ZHAOXI_MAX_FEED_BYTES`.
+const DEFAULT_MAX_FEED_BYTES: usize = 10 * 1024 * 1024;
+
+/// Trigger type of the info refresh currently running, `None` when idle.
+static INFO_REFRESH_RUNNING: Mutex<Option<String>> = Mutex::new(None);
+
+/// Per-provider token buckets backing `requests_per_minute`.
+static PROVIDER_RATE_LIMITS: OnceLock<Mutex<HashMap<String, ProviderRateBucket>>> = OnceLock::new();
+
+/// Allowed todo priorities, ordered from lowest to highest.
+const TODO_PRIORITIES: [&str; 4] = ["low", "normal", "high", "urgent"];
+
+const DEFAULT_MAX_CONCURRENT_FETCHES: i32 = 6;
+
+const MAX_INFO_RETENTION_DAYS: i32 = 90;
+
 const BACKUP_SCHEMA_VERSION: &str = "zhaoxi-backup/v1";
-const SQLITE_BACKUP_TABLES: [&str; 12] = [
+const AGENT_BUNDLE_SCHEMA_VERSION: &str = "zhaoxi-agent-bundle/v1";
+const SQLITE_BACKUP_TABLES: [&str; 13] = [
     "todos",
+    "recurring_todo_templates",
     "projects",
     "events",
     "personal_tasks",
@@ -232,6 +388,9 @@ pub struct BackupAgentFiles {
 #[serde(rename_all = "camelCase")]
 pub struct BackupSqliteData {
     pub todos: Vec<Value>,
+    /// Missing from backups taken before recurring todos existed.
+    #[serde(default)]
+    pub recurring_todo_templates: Vec<Value>,
     pub projects: Vec<Value>,
     pub events: Vec<Value>,
     pub personal_tasks: Vec<Value>,
@@ -260,6 +419,12 @@ pub struct BackupMeta {
     pub exported_at: String,
     pub platform: String,
     pub include_secrets: bool,
+    /// Set on delta exports: only rows changed after this UTC timestamp are included.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delta_since: Option<String>,
+    /// Tables left out of the export; a replace import keeps their current rows.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub excluded_tables: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -270,12 +435,29 @@ pub struct BackupEnvelope {
     pub payload: BackupPayload,
 }
 
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupProgressEvent {
+    pub phase: String,
+    pub table: String,
+    pub table_index: usize,
+    pub table_count: usize,
+    pub rows_done: usize,
+    pub rows_total: usize,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ExportBackupRequest {
     pub path: String,
     pub include_secrets: Option<bool>,
     pub local_state: Option<BackupLocalState>,
+    /// Export only rows created/updated after this time (RFC3339 or `YYYY-MM-DD[ HH:MM:SS]`).
+    #[serde(default)]
+    pub since: Option<String>,
+    /// Tables from `SQLITE_BACKUP_TABLES` to leave out, e.g. the agent event logs.
+    #[serde(default)]
+    pub exclude_tables: Option<Vec<String>>,
 }
 
 #[derive(Debug, Serialize)]
@@ -319,6 +501,151 @@ pub struct ImportBackupResponse {
     pub local_state: BackupLocalState,
 }
 
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportTablePreview {
+    pub table: String,
+    /// Rows in the database now; all of them are deleted by a full (non-delta) import
+    /// unless the table was excluded from the backup.
+    pub current_rows: usize,
+    pub incoming_rows: usize,
+    pub excluded: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviewImportResponse {
+    pub mode: String,
+    /// Delta backups are merged, so no current rows are deleted.
+    pub delta: bool,
+    /// The backup was exported without secrets; API keys and tokens must be re-entered.
+    pub sanitized: bool,
+    pub tables: Vec<ImportTablePreview>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InspectBackupResponse {
+    pub schema_version: String,
+    pub meta: BackupMeta,
+    pub table_counts: HashMap<String, usize>,
+    pub mcp_servers: usize,
+    pub commands: usize,
+    pub skills: usize,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupTableDiff {
+    pub table: String,
+    /// Rows only in the second backup.
+    pub added: usize,
+    /// Rows only in the first backup.
+    pub removed: usize,
+    pub changed: usize,
+}
+
+#[derive(Debug, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupNameDiff {
+    pub only_in_a: Vec<String>,
+    pub only_in_b: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffBackupsResponse {
+    pub tables: Vec<BackupTableDiff>,
+    pub mcp_servers: BackupNameDiff,
+    pub commands: BackupNameDiff,
+    pub skills: BackupNameDiff,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupFileEntry {
+    pub path: String,
+    pub file_name: String,
+    /// `rollback`, `auto` or `manual`, derived from the file name prefix.
+    pub kind: String,
+    pub created_at: Option<String>,
+    pub size_bytes: u64,
+    pub meta: Option<BackupMeta>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListBackupsResponse {
+    pub backups: Vec<BackupFileEntry>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct BackupMetaOnly {
+    meta: BackupMeta,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbHealthReport {
+    pub initialized: bool,
+    pub writable: bool,
+    pub path: Option<String>,
+    pub size_bytes: Option<u64>,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VacuumResult {
+    pub before_bytes: u64,
+    pub after_bytes: u64,
+    pub reclaimed_bytes: u64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageReport {
+    pub data_dir: String,
+    pub database_bytes: u64,
+    pub wal_bytes: u64,
+    pub backups_bytes: u64,
+    pub agent_bytes: u64,
+    /// Everything under the data directory, including the parts listed above.
+    pub total_bytes: u64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartupStatus {
+    pub database_ready: bool,
+    pub data_dir: Option<String>,
+    pub error: Option<String>,
+    pub warning: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AppSettings {
+    /// Fixed UTC offset such as `+08:00` that decides "today"; `None` follows the
+    /// system timezone.
+    pub utc_offset: Option<String>,
+    /// Proxy URL for all outbound HTTP, or `off` to bypass proxies; `None` falls back
+    /// to `ZHAOXI_PROXY` and the usual proxy env vars.
+    pub proxy: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateAppSettingsRequest {
+    /// Omitted keeps the stored offset; an empty string goes back to the system timezone.
+    #[serde(default)]
+    pub utc_offset: Option<String>,
+    /// Omitted keeps the stored proxy; an empty string clears it.
+    #[serde(default)]
+    pub proxy: Option<String>,
+}
+
 // ============= Backup Commands =============
 
 #[command]
@@ -337,7 +664,8 @@ pub async fn validate_backup(request: ValidateBackupRequest) -> Result<ValidateB
         ));
     }
 
-    if parsed.payload.sqlite.todos.is_empty()
+    if parsed.meta.delta_since.is_none()
+        && parsed.payload.sqlite.todos.is_empty()
         && parsed.payload.sqlite.projects.is_empty()
         && parsed.payload.sqlite.events.is_empty()
         && parsed.payload.sqlite.personal_tasks.is_empty()
@@ -352,14 +680,169 @@ pub async fn validate_backup(request: ValidateBackupRequest) -> Result<ValidateB
     })
 }
 
+/// Summarizes a backup file for the restore dialog without touching any data.
+#[command]
+pub async fn inspect_backup(path: String) -> Result<InspectBackupResponse, String> {
+    let parsed = read_backup_envelope(&path)?;
+    let agent_files = &parsed.payload.agent_files;
+    Ok(InspectBackupResponse {
+        table_counts: sqlite_table_counts_from_backup(&parsed.payload.sqlite),
+        mcp_servers: agent_files.mcp_servers.len(),
+        commands: agent_files.user_commands.len(),
+        skills: agent_files.user_skills.len(),
+        schema_version: parsed.schema_version,
+        meta: parsed.meta,
+    })
+}
+
+/// Compares two backup files row by row (keyed by `id`) and by agent file names,
+/// reporting what restoring `path_b` over `path_a` would change.
+#[command]
+pub async fn diff_backups(path_a: String, path_b: String) -> Result<DiffBackupsResponse, String> {
+    let a = read_backup_envelope(&path_a)?.payload;
+    let b = read_backup_envelope(&path_b)?.payload;
+
+    let tables = backup_table_rows(&a.sqlite)
+        .into_iter()
+        .zip(backup_table_rows(&b.sqlite))
+        .map(|((table, rows_a), (_, rows_b))| {
+            let keyed_a: HashMap<String, &Value> = rows_a
+                .iter()
+                .map(|row| (backup_row_key(row), row))
+                .collect();
+            let keyed_b: HashMap<String, &Value> = rows_b
+                .iter()
+                .map(|row| (backup_row_key(row), row))
+                .collect();
+            let mut diff = BackupTableDiff {
+                table: table.to_string(),
+                added: 0,
+                removed: keyed_a
+                    .keys()
+                    .filter(|key| !keyed_b.contains_key(*key))
+                    .count(),
+                changed: 0,
+            };
+            for (key, row_b) in &keyed_b {
+                match keyed_a.get(key) {
+                    Some(row_a) if row_a != row_b => diff.changed += 1,
+                    Some(_) => {}
+                    None => diff.added += 1,
+                }
+            }
+            diff
+        })
+        .collect();
+
+    let agent_a = &a.agent_files;
+    let agent_b = &b.agent_files;
+    Ok(DiffBackupsResponse {
+        tables,
+        mcp_servers: diff_names(
+            agent_a
+                .mcp_servers
+                .iter()
+                .map(|server| server.name.as_str()),
+            agent_b
+                .mcp_servers
+                .iter()
+                .map(|server| server.name.as_str()),
+        ),
+        commands: diff_names(
+            agent_a.user_commands.iter().map(|file| file.path.as_str()),
+            agent_b.user_commands.iter().map(|file| file.path.as_str()),
+        ),
+        skills: diff_names(
+            agent_a.user_skills.iter().map(|skill| skill.id.as_str()),
+            agent_b.user_skills.iter().map(|skill| skill.id.as_str()),
+        ),
+    })
+}
+
+/// Validates export exclusions against `SQLITE_BACKUP_TABLES`, dropping duplicates.
+fn normalize_excluded_tables(tables: Vec<String>) -> Result<Vec<String>, String> {
+    let mut excluded = Vec::new();
+    for table in tables {
+        let table = table.trim().to_string();
+        if !SQLITE_BACKUP_TABLES.contains(&table.as_str()) {
+            return Err(format!("不支持排除的表: {}", table));
+        }
+        if !excluded.contains(&table) {
+            excluded.push(table);
+        }
+    }
+    Ok(excluded)
+}
+
+fn read_backup_envelope(path: &str) -> Result<BackupEnvelope, String> {
+    let path = PathBuf::from(path.trim());
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("读取备份文件失败 ({}): {}", path.display(), e))?;
+    serde_json::from_str(&content).map_err(|e| format!("备份文件 JSON 解析失败: {}", e))
+}
+
+fn backup_table_rows(sqlite: &BackupSqliteData) -> [(&'static str, &[Value]); 13] {
+    [
+        ("todos", &sqlite.todos),
+        ("recurring_todo_templates", &sqlite.recurring_todo_templates),
+        ("projects", &sqlite.projects),
+        ("events", &sqlite.events),
+        ("personal_tasks", &sqlite.personal_tasks),
+        ("inspirations", &sqlite.inspirations),
+        ("info_sources", &sqlite.info_sources),
+        ("info_settings", &sqlite.info_settings),
+        ("info_items_daily", &sqlite.info_items_daily),
+        ("info_refresh_logs", &sqlite.info_refresh_logs),
+        ("agent_sessions", &sqlite.agent_sessions),
+        ("agent_events", &sqlite.agent_events),
+        ("agent_action_audits", &sqlite.agent_action_audits),
+    ]
+}
+
+/// Every backed-up table has an `id` primary key; a row without one is keyed by
+/// its whole content so it still diffs sensibly.
+fn backup_row_key(row: &Value) -> String {
+    row.get("id")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .unwrap_or_else(|| row.to_string())
+}
+
+fn diff_names<'a>(
+    a: impl Iterator<Item = &'a str>,
+    b: impl Iterator<Item = &'a str>,
+) -> BackupNameDiff {
+    let a: BTreeSet<&str> = a.collect();
+    let b: BTreeSet<&str> = b.collect();
+    BackupNameDiff {
+        only_in_a: a.difference(&b).map(|name| name.to_string()).collect(),
+        only_in_b: b.difference(&a).map(|name| name.to_string()).collect(),
+    }
+}
+
 #[command]
 pub async fn export_backup(
     app: AppHandle,
     request: ExportBackupRequest,
 ) -> Result<ExportBackupResponse, String> {
     let include_secrets = request.include_secrets.unwrap_or(false);
-    let (mut envelope, mut warnings, table_counts) =
-        build_backup_envelope(&app, request.local_state, include_secrets).await?;
+    let since = request
+        .since
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(normalize_since_timestamp)
+        .transpose()?;
+    let excluded_tables = normalize_excluded_tables(request.exclude_tables.unwrap_or_default())?;
+    let (mut envelope, mut warnings, table_counts) = build_backup_envelope(
+        &app,
+        request.local_state,
+        include_secrets,
+        since,
+        excluded_tables,
+        "export",
+    )
+    .await?;
     if !include_secrets {
         sanitize_backup_envelope(&mut envelope);
     }
@@ -393,6 +876,50 @@ pub async fn export_backup(
     })
 }
 
+/// Dry run of `import_backup`: what each table holds now versus what the backup
+/// would put there. Reads only.
+#[command]
+pub async fn preview_import(path: String, mode: String) -> Result<PreviewImportResponse, String> {
+    let mode = mode.trim().to_lowercase();
+    if mode != "replace" {
+        return Err("当前仅支持 replace 导入模式".to_string());
+    }
+    let envelope = read_backup_envelope(&path)?;
+    if envelope.schema_version != BACKUP_SCHEMA_VERSION {
+        return Err(format!(
+            "不支持的备份版本: {} (期望 {})",
+            envelope.schema_version, BACKUP_SCHEMA_VERSION
+        ));
+    }
+
+    let pool = get_db_pool()?;
+    let mut tables = Vec::with_capacity(SQLITE_BACKUP_TABLES.len());
+    for (table, rows) in backup_table_rows(&envelope.payload.sqlite) {
+        let current_rows: i64 =
+            sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {}", quote_ident(table)))
+                .fetch_one(pool)
+                .await
+                .map_err(|e| format!("Failed to count rows in {}: {}", table, e))?;
+        tables.push(ImportTablePreview {
+            table: table.to_string(),
+            current_rows: current_rows as usize,
+            incoming_rows: rows.len(),
+            excluded: envelope
+                .meta
+                .excluded_tables
+                .iter()
+                .any(|name| name == table),
+        });
+    }
+
+    Ok(PreviewImportResponse {
+        mode,
+        delta: envelope.meta.delta_since.is_some(),
+        sanitized: !envelope.meta.include_secrets,
+        tables,
+    })
+}
+
 #[command]
 pub async fn import_backup(
     app: AppHandle,
@@ -413,8 +940,19 @@ pub async fn import_backup(
         ));
     }
 
+    let is_delta = envelope.meta.delta_since.is_some();
     let (rollback_path, rollback_warnings) = create_rollback_backup(&app).await?;
-    restore_sqlite_data(&envelope.payload.sqlite).await?;
+    let progress = BackupProgress {
+        app: &app,
+        phase: "import",
+    };
+    restore_sqlite_data(
+        &envelope.payload.sqlite,
+        is_delta,
+        &envelope.meta.excluded_tables,
+        &progress,
+    )
+    .await?;
     restore_agent_files(&app, &envelope.payload.agent_files)?;
 
     let table_counts = sqlite_table_counts_from_backup(&envelope.payload.sqlite);
@@ -422,6 +960,15 @@ pub async fn import_backup(
     if !envelope.meta.include_secrets {
         warnings.push("导入文件为脱敏备份，敏感配置需手动补全".to_string());
     }
+    if let Some(since) = &envelope.meta.delta_since {
+        warnings.push(format!("增量备份（{} 之后的变更）已合并到现有数据", since));
+    }
+    if !envelope.meta.excluded_tables.is_empty() {
+        warnings.push(format!(
+            "备份未包含以下表，已保留现有数据: {}",
+            envelope.meta.excluded_tables.join(", ")
+        ));
+    }
 
     Ok(ImportBackupResponse {
         restored_at: chrono::Utc::now().to_rfc3339(),
@@ -432,39 +979,392 @@ pub async fn import_backup(
     })
 }
 
-// ============= Weather Commands =============
+/// Lists backup files in the backups dir, newest first. Files that can't be read
+/// or parsed are still listed without meta, with a warning.
+#[command]
+pub async fn list_backups(app: AppHandle) -> Result<ListBackupsResponse, String> {
+    let dir = backup_work_dir(&app)?;
+    let entries = fs::read_dir(&dir)
+        .map_err(|e| format!("Failed to read backup dir ({}): {}", dir.display(), e))?;
+    let mut backups = Vec::new();
+    let mut warnings = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_file() || path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+            continue;
+        }
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        let metadata = match entry.metadata() {
+            Ok(metadata) => metadata,
+            Err(error) => {
+                warnings.push(format!("跳过备份文件 {}: {}", file_name, error));
+                continue;
+            }
+        };
+        let kind = if file_name.starts_with("rollback-") {
+            "rollback"
+        } else if file_name.starts_with("auto-") {
+            "auto"
+        } else {
+            "manual"
+        };
+        let meta = match fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| {
+                serde_json::from_str::<BackupMetaOnly>(&content).map_err(|e| e.to_string())
+            }) {
+            Ok(parsed) => Some(parsed.meta),
+            Err(error) => {
+                warnings.push(format!("无法解析备份文件 {}: {}", file_name, error));
+                None
+            }
+        };
+        let created_at = meta
+            .as_ref()
+            .map(|meta| meta.exported_at.clone())
+            .or_else(|| {
+                metadata
+                    .modified()
+                    .ok()
+                    .map(|time| chrono::DateTime::<chrono::Utc>::from(time).to_rfc3339())
+            });
+        backups.push(BackupFileEntry {
+            path: path.to_string_lossy().to_string(),
+            file_name,
+            kind: kind.to_string(),
+            created_at,
+            size_bytes: metadata.len(),
+            meta,
+        });
+    }
+    backups.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    Ok(ListBackupsResponse { backups, warnings })
+}
 
+/// Exports audits created before `before` (YYYY-MM-DD) to a JSON file, then
+/// deletes them in the same transaction. Returns the number archived.
 #[command]
-pub async fn geocode_city(request: GeocodeCityRequest) -> Result<GeocodeCityResponse, String> {
-    let city = request.city.trim();
-    if city.is_empty() {
-        return Err("城市名称不能为空".to_string());
+pub async fn archive_agent_audits(before: String, path: String) -> Result<usize, String> {
+    let before = before.trim().to_string();
+    chrono::NaiveDate::parse_from_str(&before, "%Y-%m-%d")
+        .map_err(|_| format!("无效的日期: {}（应为 YYYY-MM-DD）", before))?;
+    let output_path = PathBuf::from(path.trim());
+    if output_path.as_os_str().is_empty() {
+        return Err("归档路径不能为空".to_string());
     }
 
-    let endpoint = "https://geocoding-api.open-meteo.com/v1/search";
-    let client = reqwest::Client::new();
-    let response = client
-        .get(endpoint)
-        .query(&[
-            ("name", city),
-            ("count", "1"),
-            ("language", "zh"),
-            ("format", "json"),
-        ])
-        .send()
+    let pool = get_db_pool()?;
+    let mut tx = pool
+        .begin()
         .await
-        .map_err(|e| format!("地理编码请求失败: {}", e))?;
+        .map_err(|e| format!("Failed to start archive transaction: {}", e))?;
 
-    if !response.status().is_success() {
-        return Err(format!("地理编码服务异常: HTTP {}", response.status()));
+    let rows = sqlx::query(
+        "SELECT * FROM agent_action_audits WHERE created_at < ?1 ORDER BY created_at ASC",
+    )
+    .bind(&before)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to query agent audits: {}", e))?;
+    if rows.is_empty() {
+        return Ok(0);
     }
+    let audits: Vec<Value> = rows.into_iter().map(sqlite_row_to_json).collect();
 
-    let payload = response
-        .json::<OpenMeteoGeocodingResponse>()
-        .await
-        .map_err(|e| format!("地理编码响应解析失败: {}", e))?;
+    if let Some(parent) = output_path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("创建归档目录失败 ({}): {}", parent.display(), e))?;
+    }
+    let archive = json!({
+        "archivedAt": chrono::Utc::now().to_rfc3339(),
+        "before": before,
+        "agentActionAudits": audits,
+    });
+    fs::write(
+        &output_path,
+        serde_json::to_string_pretty(&archive).map_err(|e| format!("序列化归档内容失败: {}", e))?,
+    )
+    .map_err(|e| format!("写入归档文件失败 ({}): {}", output_path.display(), e))?;
 
-    let result = payload
+    sqlx::query("DELETE FROM agent_action_audits WHERE created_at < ?1")
+        .bind(&before)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to delete archived agent audits: {}", e))?;
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit archive transaction: {}", e))?;
+
+    Ok(audits.len())
+}
+
+// ============= System Commands =============
+
+/// Verifies the database accepts writes by creating a table inside a transaction
+/// and rolling it back. Also reports the file path/size and any startup error.
+#[command]
+pub async fn db_health_check() -> Result<DbHealthReport, String> {
+    let path = get_db_path().map(|path| path.to_string_lossy().to_string());
+    let size_bytes = get_db_path()
+        .and_then(|path| fs::metadata(path).ok())
+        .map(|meta| meta.len());
+
+    let pool = match get_db_pool() {
+        Ok(pool) => pool,
+        Err(error) => {
+            return Ok(DbHealthReport {
+                initialized: false,
+                writable: false,
+                path,
+                size_bytes,
+                message: get_db_init_error().cloned().unwrap_or(error),
+            });
+        }
+    };
+
+    let write_result = async {
+        let mut tx = pool.begin().await?;
+        sqlx::query("CREATE TABLE __health_check (id INTEGER)")
+            .execute(&mut *tx)
+            .await?;
+        tx.rollback().await
+    }
+    .await;
+
+    let (writable, message) = match write_result {
+        Ok(()) => (true, "数据库可正常读写".to_string()),
+        Err(e) => (false, format!("数据库写入失败: {}", e)),
+    };
+    Ok(DbHealthReport {
+        initialized: true,
+        writable,
+        path,
+        size_bytes,
+        message,
+    })
+}
+
+/// Held by backup reads/restores and VACUUM so they never overlap.
+static DB_MAINTENANCE_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// Size of the database file plus its WAL, if any.
+fn database_size_on_disk() -> u64 {
+    let Some(path) = get_db_path() else {
+        return 0;
+    };
+    let mut wal_path = path.clone().into_os_string();
+    wal_path.push("-wal");
+    [path.clone(), PathBuf::from(wal_path)]
+        .iter()
+        .filter_map(|file| fs::metadata(file).ok())
+        .map(|meta| meta.len())
+        .sum()
+}
+
+/// Reclaims free pages with VACUUM (and truncates the WAL when enabled).
+#[command]
+pub async fn vacuum_database() -> Result<VacuumResult, String> {
+    let pool = get_db_pool()?;
+    let _maintenance = DB_MAINTENANCE_LOCK
+        .try_lock()
+        .map_err(|_| "备份或恢复正在进行，请稍后再试".to_string())?;
+    let before_bytes = database_size_on_disk();
+
+    sqlx::query("VACUUM")
+        .execute(pool)
+        .await
+        .map_err(|e| format!("Failed to vacuum database: {}", e))?;
+    let journal_mode: String = sqlx::query_scalar("PRAGMA journal_mode")
+        .fetch_one(pool)
+        .await
+        .map_err(|e| format!("Failed to read journal mode: {}", e))?;
+    if journal_mode.eq_ignore_ascii_case("wal") {
+        sqlx::query("PRAGMA wal_checkpoint(TRUNCATE)")
+            .execute(pool)
+            .await
+            .map_err(|e| format!("Failed to checkpoint WAL: {}", e))?;
+    }
+
+    let after_bytes = database_size_on_disk();
+    Ok(VacuumResult {
+        before_bytes,
+        after_bytes,
+        reclaimed_bytes: before_bytes.saturating_sub(after_bytes),
+    })
+}
+
+/// Breaks down what the data directory holds on disk, so users can tell whether
+/// to vacuum, prune backups or clean up skills.
+#[command]
+pub async fn get_storage_report(app: AppHandle) -> Result<StorageReport, String> {
+    let root = app_data_root(&app)?;
+    let file_size = |path: &Path| fs::metadata(path).map(|meta| meta.len()).unwrap_or(0);
+    let (database_bytes, wal_bytes) = match get_db_path() {
+        Some(path) => {
+            let mut wal_path = path.clone().into_os_string();
+            wal_path.push("-wal");
+            (file_size(path), file_size(Path::new(&wal_path)))
+        }
+        None => (0, 0),
+    };
+    Ok(StorageReport {
+        data_dir: root.to_string_lossy().to_string(),
+        database_bytes,
+        wal_bytes,
+        backups_bytes: dir_size_on_disk(&root.join("backups")),
+        agent_bytes: dir_size_on_disk(&root.join("agent")),
+        total_bytes: dir_size_on_disk(&root),
+    })
+}
+
+/// Total size of the regular files below `dir`; symlinks are not followed and
+/// unreadable entries count as zero.
+fn dir_size_on_disk(dir: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(kind) if kind.is_dir() => dir_size_on_disk(&entry.path()),
+            Ok(kind) if kind.is_file() => entry.metadata().map(|meta| meta.len()).unwrap_or(0),
+            _ => 0,
+        })
+        .sum()
+}
+
+/// Lets the frontend block the UI with a readable message when init_database failed.
+#[command]
+pub async fn get_startup_status() -> Result<StartupStatus, String> {
+    let data_dir = get_db_path()
+        .and_then(|path| path.parent())
+        .map(|dir| dir.to_string_lossy().to_string());
+    if get_db_pool().is_ok() {
+        return Ok(StartupStatus {
+            database_ready: true,
+            data_dir,
+            error: None,
+            warning: get_data_dir_warning().cloned(),
+        });
+    }
+
+    let detail = get_db_init_error()
+        .cloned()
+        .unwrap_or_else(|| "Database not initialized".to_string());
+    Ok(StartupStatus {
+        database_ready: false,
+        data_dir,
+        error: Some(format!(
+            "数据库初始化失败，请确认数据目录存在且可写: {}",
+            detail
+        )),
+        warning: get_data_dir_warning().cloned(),
+    })
+}
+
+/// Last loaded app settings, so `app_now` does not need a database round trip.
+static APP_SETTINGS: Mutex<Option<AppSettings>> = Mutex::new(None);
+
+fn cached_app_settings() -> AppSettings {
+    APP_SETTINGS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+        .unwrap_or_default()
+}
+
+#[command]
+pub async fn get_app_settings() -> Result<AppSettings, String> {
+    load_app_settings().await
+}
+
+/// Reads the `'default'` settings row (defaults when it is missing) and refreshes
+/// the cached copy. Called once at startup.
+pub async fn load_app_settings() -> Result<AppSettings, String> {
+    let pool = get_db_pool()?;
+    let settings = sqlx::query("SELECT utc_offset, proxy FROM app_settings WHERE id = 'default'")
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| format!("Failed to fetch app settings: {}", e))?
+        .map(|row| AppSettings {
+            utc_offset: row.get("utc_offset"),
+            proxy: row.get("proxy"),
+        })
+        .unwrap_or_default();
+    *APP_SETTINGS.lock().unwrap_or_else(|e| e.into_inner()) = Some(settings.clone());
+    Ok(settings)
+}
+
+#[command]
+pub async fn update_app_settings(request: UpdateAppSettingsRequest) -> Result<AppSettings, String> {
+    let utc_offset = match request.utc_offset.as_deref().map(str::trim) {
+        None => None,
+        Some("") => Some(String::new()),
+        Some(value) => Some(
+            parse_utc_offset(value)
+                .ok_or_else(|| format!("无效的时区偏移: {}（应为 +08:00 这样的格式）", value))?
+                .to_string(),
+        ),
+    };
+    let proxy = request.proxy.as_deref().map(str::trim);
+    if let Some(url) = proxy.filter(|url| !url.is_empty() && !url.eq_ignore_ascii_case("off")) {
+        reqwest::Proxy::all(url).map_err(|e| format!("代理地址无效: {}", e))?;
+    }
+
+    let pool = get_db_pool()?;
+    sqlx::query(
+        "INSERT INTO app_settings (id, utc_offset, proxy, updated_at)
+         VALUES ('default', NULLIF(?1, ''), NULLIF(?2, ''), CURRENT_TIMESTAMP)
+         ON CONFLICT(id) DO UPDATE SET
+            utc_offset = CASE WHEN ?1 IS NULL THEN app_settings.utc_offset ELSE NULLIF(?1, '') END,
+            proxy = CASE WHEN ?2 IS NULL THEN app_settings.proxy ELSE NULLIF(?2, '') END,
+            updated_at = CURRENT_TIMESTAMP",
+    )
+    .bind(utc_offset)
+    .bind(proxy)
+    .execute(pool)
+    .await
+    .map_err(|e| format!("Failed to update app settings: {}", e))?;
+    let settings = load_app_settings().await?;
+    if proxy.is_some() {
+        reset_http_clients();
+    }
+    Ok(settings)
+}
+
+// ============= Weather Commands =============
+
+#[command]
+pub async fn geocode_city(request: GeocodeCityRequest) -> Result<GeocodeCityResponse, String> {
+    let city = request.city.trim();
+    if city.is_empty() {
+        return Err("城市名称不能为空".to_string());
+    }
+
+    let endpoint = "https://geocoding-api.open-meteo.com/v1/search";
+    let client = http_client()?;
+    let response = client
+        .get(endpoint)
+        .query(&[
+            ("name", city),
+            ("count", "1"),
+            ("language", "zh"),
+            ("format", "json"),
+        ])
+        .send()
+        .await
+        .map_err(|e| format!("地理编码请求失败: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(format!("地理编码服务异常: HTTP {}", response.status()));
+    }
+
+    let payload = response
+        .json::<OpenMeteoGeocodingResponse>()
+        .await
+        .map_err(|e| format!("地理编码响应解析失败: {}", e))?;
+
+    let result = payload
         .results
         .and_then(|items| items.into_iter().next())
         .ok_or_else(|| "未找到匹配城市".to_string())?;
@@ -486,7 +1386,7 @@ pub async fn get_current_weather(request: GetCurrentWeatherRequest) -> Result<We
     }
 
     let endpoint = "https://api.open-meteo.com/v1/forecast";
-    let client = reqwest::Client::new();
+    let client = http_client()?;
     let response = client
         .get(endpoint)
         .query(&[
@@ -518,7 +1418,7 @@ pub async fn get_current_weather(request: GetCurrentWeatherRequest) -> Result<We
         wind_level: wind_speed_to_level(payload.current.wind_speed_10m),
         condition: weather_code_to_condition(payload.current.weather_code).to_string(),
         city: city.to_string(),
-        updated_at: chrono::Local::now().to_rfc3339(),
+        updated_at: local_now_rfc3339(),
         source: "open-meteo".to_string(),
         location_name: request
             .location_name
@@ -528,28 +1428,62 @@ pub async fn get_current_weather(request: GetCurrentWeatherRequest) -> Result<We
 
 // ============= Todo Commands =============
 
+static LAST_ENTITY_ID: AtomicI64 = AtomicI64::new(0);
+
+/// Millisecond-based id that stays unique when several rows are created in the
+/// same millisecond (e.g. an agent batch): it never repeats or goes backwards.
+fn new_entity_id() -> String {
+    let now = chrono::Utc::now().timestamp_millis();
+    let previous = LAST_ENTITY_ID
+        .fetch_update(AtomicOrdering::SeqCst, AtomicOrdering::SeqCst, |last| {
+            Some(now.max(last + 1))
+        })
+        .unwrap_or(now);
+    now.max(previous + 1).to_string()
+}
+
 #[command]
-pub async fn get_todos() -> Result<Vec<Todo>, String> {
+pub async fn get_todos(
+    sort_by: Option<String>,
+    include_snoozed: Option<bool>,
+) -> Result<Vec<Todo>, String> {
     let pool = get_db_pool()?;
-    let rows = sqlx::query(
-        "SELECT id, title, completed, priority, created_at FROM todos ORDER BY created_at DESC",
-    )
+    let order_by = match sort_by.as_deref().map(str::trim) {
+        None | Some("") | Some("created") => "pinned DESC, created_at DESC".to_string(),
+        Some("priority") => format!(
+            "pinned DESC, {} DESC, created_at DESC",
+            todo_priority_rank_sql()
+        ),
+        Some(other) => return Err(format!("Unsupported todo sort: {}", other)),
+    };
+    // Snoozed todos reappear on their own once `snoozed_until` has passed
+    let filter = if include_snoozed.unwrap_or(false) {
+        ""
+    } else {
+        "WHERE snoozed_until IS NULL OR datetime(snoozed_until) <= CURRENT_TIMESTAMP"
+    };
+    let rows = sqlx::query(&format!(
+        "SELECT id, title, completed, priority, created_at, completed_at, project_id, snoozed_until, pinned FROM todos {} ORDER BY {}",
+        filter, order_by
+    ))
     .fetch_all(pool)
     .await
     .map_err(|e| format!("Failed to fetch todos: {}", e))?;
 
-    let todos: Vec<Todo> = rows
-        .into_iter()
-        .map(|row| Todo {
-            id: row.get("id"),
-            title: row.get("title"),
-            completed: row.get::<i32, _>("completed") != 0,
-            priority: row.get("priority"),
-            created_at: row.get("created_at"),
-        })
-        .collect();
+    Ok(rows.into_iter().map(row_to_todo).collect())
+}
 
-    Ok(todos)
+#[command]
+pub async fn get_todo(id: String) -> Result<Option<Todo>, String> {
+    let pool = get_db_pool()?;
+    let row = sqlx::query(
+        "SELECT id, title, completed, priority, created_at, completed_at, project_id, snoozed_until, pinned FROM todos WHERE id = ?1",
+    )
+    .bind(&id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch todo: {}", e))?;
+    Ok(row.map(row_to_todo))
 }
 
 #[derive(Deserialize)]
@@ -562,8 +1496,8 @@ pub struct CreateTodoRequest {
 #[command]
 pub async fn create_todo(request: CreateTodoRequest) -> Result<Todo, String> {
     let pool = get_db_pool()?;
-    let id = chrono::Utc::now().timestamp_millis().to_string();
-    let priority = request.priority.unwrap_or_else(|| "normal".to_string());
+    let id = new_entity_id();
+    let priority = normalize_todo_priority(request.priority.as_deref().unwrap_or("normal"))?;
 
     sqlx::query("INSERT INTO todos (id, title, priority) VALUES (?1, ?2, ?3)")
         .bind(&id)
@@ -573,22 +1507,154 @@ pub async fn create_todo(request: CreateTodoRequest) -> Result<Todo, String> {
         .await
         .map_err(|e| format!("Failed to create todo: {}", e))?;
 
-    let row =
-        sqlx::query("SELECT id, title, completed, priority, created_at FROM todos WHERE id = ?1")
+    let row = sqlx::query(
+        "SELECT id, title, completed, priority, created_at, completed_at, project_id, snoozed_until, pinned FROM todos WHERE id = ?1",
+    )
+    .bind(&id)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch created todo: {}", e))?;
+
+    Ok(row_to_todo(row))
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportTodosTextResponse {
+    pub created: usize,
+    pub ids: Vec<String>,
+}
+
+/// Creates one todo per non-blank line of a pasted checklist, in a single
+/// transaction. A trailing `!` or `!!` raises that line's priority by one or two
+/// levels above the default.
+#[command]
+pub async fn import_todos_text(
+    text: String,
+    default_priority: Option<String>,
+) -> Result<ImportTodosTextResponse, String> {
+    let pool = get_db_pool()?;
+    let default_priority =
+        normalize_todo_priority(default_priority.as_deref().unwrap_or("normal"))?;
+    let entries: Vec<(String, String)> = text
+        .lines()
+        .filter_map(|line| parse_todo_line(line, &default_priority))
+        .collect();
+    if entries.is_empty() {
+        return Err("没有可导入的待办".to_string());
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+    let mut ids = Vec::with_capacity(entries.len());
+    for (title, priority) in &entries {
+        let id = new_entity_id();
+        sqlx::query("INSERT INTO todos (id, title, priority) VALUES (?1, ?2, ?3)")
             .bind(&id)
-            .fetch_one(pool)
+            .bind(title)
+            .bind(priority)
+            .execute(&mut *tx)
             .await
-            .map_err(|e| format!("Failed to fetch created todo: {}", e))?;
+            .map_err(|e| format!("Failed to create todo: {}", e))?;
+        ids.push(id);
+    }
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit transaction: {}", e))?;
 
-    Ok(Todo {
-        id: row.get("id"),
-        title: row.get("title"),
-        completed: row.get::<i32, _>("completed") != 0,
-        priority: row.get("priority"),
-        created_at: row.get("created_at"),
+    Ok(ImportTodosTextResponse {
+        created: ids.len(),
+        ids,
     })
 }
 
+/// Upper bound for `todo.bulk_create`; keeps the multi-row insert well under
+/// SQLite's bound-parameter limit.
+const MAX_BULK_TODOS: usize = 100;
+
+/// Inserts every non-blank title of a `todo.bulk_create` payload with a single
+/// multi-row statement on the caller's transaction. Returns the created count.
+async fn bulk_create_todos(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    payload: &Value,
+) -> Result<usize, String> {
+    let titles = payload
+        .get("titles")
+        .and_then(Value::as_array)
+        .ok_or_else(|| "Missing field: titles".to_string())?
+        .iter()
+        .map(|item| {
+            item.as_str()
+                .map(str::trim)
+                .ok_or_else(|| "titles 只能包含字符串".to_string())
+        })
+        .filter(|title| !matches!(title, Ok("")))
+        .collect::<Result<Vec<&str>, String>>()?;
+    if titles.is_empty() {
+        return Err("titles 不能为空".to_string());
+    }
+    if titles.len() > MAX_BULK_TODOS {
+        return Err(format!("一次最多创建 {} 条待办", MAX_BULK_TODOS));
+    }
+    let priority =
+        normalize_todo_priority(get_optional_str(payload, "priority").unwrap_or("normal"))?;
+
+    let sql = format!(
+        "INSERT INTO todos (id, title, priority) VALUES {}",
+        vec!["(?, ?, ?)"; titles.len()].join(", ")
+    );
+    let mut query = sqlx::query(&sql);
+    for title in &titles {
+        query = query.bind(new_entity_id()).bind(*title).bind(&priority);
+    }
+    query
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| format!("Failed to create todos: {}", e))?;
+    Ok(titles.len())
+}
+
+/// Strips list markers (`- `, `* `, `1. `, `- [ ] `) and trailing `!` marks from a
+/// checklist line, returning the title and its priority.
+fn parse_todo_line(line: &str, default_priority: &str) -> Option<(String, String)> {
+    let mut title = line.trim();
+    if let Some(rest) = title
+        .strip_prefix("- ")
+        .or_else(|| title.strip_prefix("* "))
+    {
+        title = rest.trim_start();
+    } else {
+        let digits = title.len() - title.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        if digits > 0 {
+            if let Some(rest) = title[digits..].strip_prefix(". ") {
+                title = rest.trim_start();
+            }
+        }
+    }
+    for checkbox in ["[ ] ", "[x] ", "[X] "] {
+        if let Some(rest) = title.strip_prefix(checkbox) {
+            title = rest.trim_start();
+            break;
+        }
+    }
+
+    let without_marks = title.trim_end_matches('!');
+    let bump = (title.len() - without_marks.len()).min(2);
+    let title = without_marks.trim_end();
+    if title.is_empty() {
+        return None;
+    }
+
+    let base = TODO_PRIORITIES
+        .iter()
+        .position(|item| *item == default_priority)
+        .unwrap_or(1);
+    let priority = TODO_PRIORITIES[(base + bump).min(TODO_PRIORITIES.len() - 1)];
+    Some((title.to_string(), priority.to_string()))
+}
+
 #[derive(Deserialize)]
 pub struct UpdateTodoRequest {
     pub id: String,
@@ -600,679 +1666,1777 @@ pub struct UpdateTodoRequest {
 #[command]
 pub async fn update_todo(request: UpdateTodoRequest) -> Result<Todo, String> {
     let pool = get_db_pool()?;
+    let priority = request
+        .priority
+        .as_deref()
+        .map(normalize_todo_priority)
+        .transpose()?;
 
     // Build dynamic update query
-    let mut updates: Vec<String> = Vec::new();
-
-    if request.title.is_some() {
-        updates.push("title = ?".to_string());
-    }
-    if request.completed.is_some() {
-        updates.push("completed = ?".to_string());
-    }
-    if request.priority.is_some() {
-        updates.push("priority = ?".to_string());
-    }
+    let mut updates = UpdateBuilder::new();
+    updates.set_opt("title", request.title.as_deref());
+    updates.set_completed(request.completed);
+    updates.set_opt("priority", priority);
 
     if updates.is_empty() {
         return Err("No fields to update".to_string());
     }
 
-    let query = format!("UPDATE todos SET {} WHERE id = ?", updates.join(", "));
-    let mut query_builder = sqlx::query(&query);
-
-    if let Some(title) = &request.title {
-        query_builder = query_builder.bind(title);
-    }
-    if let Some(completed) = request.completed {
-        query_builder = query_builder.bind(if completed { 1 } else { 0 });
-    }
-    if let Some(priority) = &request.priority {
-        query_builder = query_builder.bind(priority);
-    }
-    query_builder = query_builder.bind(&request.id);
-
-    query_builder
-        .execute(pool)
+    updates
+        .execute("todos", &request.id, pool)
         .await
         .map_err(|e| format!("Failed to update todo: {}", e))?;
 
-    let row =
-        sqlx::query("SELECT id, title, completed, priority, created_at FROM todos WHERE id = ?1")
-            .bind(&request.id)
-            .fetch_one(pool)
-            .await
-            .map_err(|e| format!("Failed to fetch updated todo: {}", e))?;
+    let row = sqlx::query(
+        "SELECT id, title, completed, priority, created_at, completed_at, project_id, snoozed_until, pinned FROM todos WHERE id = ?1",
+    )
+    .bind(&request.id)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch updated todo: {}", e))?;
 
-    Ok(Todo {
-        id: row.get("id"),
-        title: row.get("title"),
-        completed: row.get::<i32, _>("completed") != 0,
-        priority: row.get("priority"),
-        created_at: row.get("created_at"),
-    })
+    Ok(row_to_todo(row))
 }
 
+/// Hides a todo from `get_todos` until `until` (RFC 3339, `YYYY-MM-DD HH:MM:SS` or
+/// `YYYY-MM-DD`, the latter meaning local midnight). `None` wakes it immediately.
 #[command]
-pub async fn delete_todo(id: String) -> Result<(), String> {
+pub async fn snooze_todo(id: String, until: Option<String>) -> Result<Todo, String> {
     let pool = get_db_pool()?;
-    sqlx::query("DELETE FROM todos WHERE id = ?1")
+    let snoozed_until = match until
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+    {
+        Some(value) => {
+            let until = parse_snooze_until(value)?;
+            if until <= chrono::Utc::now() {
+                return Err("稍后提醒的时间必须晚于现在".to_string());
+            }
+            Some(until.format("%Y-%m-%d %H:%M:%S").to_string())
+        }
+        None => None,
+    };
+
+    let result = sqlx::query("UPDATE todos SET snoozed_until = ?1 WHERE id = ?2")
+        .bind(&snoozed_until)
         .bind(&id)
         .execute(pool)
         .await
-        .map_err(|e| format!("Failed to delete todo: {}", e))?;
-    Ok(())
-}
+        .map_err(|e| format!("Failed to snooze todo: {}", e))?;
+    if result.rows_affected() == 0 {
+        return Err(format!("Todo not found: {}", id));
+    }
 
-// ============= Project Commands =============
+    let row = sqlx::query(
+        "SELECT id, title, completed, priority, created_at, completed_at, project_id, snoozed_until, pinned FROM todos WHERE id = ?1",
+    )
+    .bind(&id)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch snoozed todo: {}", e))?;
+
+    Ok(row_to_todo(row))
+}
 
+/// Advances the priority one step through `TODO_PRIORITIES`, wrapping from the last
+/// back to the first. An unrecognized stored value resets to `normal`.
 #[command]
-pub async fn get_projects() -> Result<Vec<Project>, String> {
+pub async fn cycle_todo_priority(id: String) -> Result<Todo, String> {
     let pool = get_db_pool()?;
-    let rows =
-        sqlx::query("SELECT id, title, deadline, progress, status FROM projects ORDER BY deadline")
-            .fetch_all(pool)
-            .await
-            .map_err(|e| format!("Failed to fetch projects: {}", e))?;
+    let current: Option<String> = sqlx::query_scalar("SELECT priority FROM todos WHERE id = ?1")
+        .bind(&id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| format!("Failed to fetch todo: {}", e))?
+        .ok_or_else(|| format!("Todo not found: {}", id))?;
+    let next = next_todo_priority(current.as_deref().unwrap_or_default());
 
-    let projects: Vec<Project> = rows
-        .into_iter()
-        .map(|row| Project {
-            id: row.get("id"),
-            title: row.get("title"),
-            deadline: row.get("deadline"),
-            progress: row.get("progress"),
-            status: row.get("status"),
-        })
-        .collect();
-
-    Ok(projects)
-}
-
-#[derive(Deserialize)]
-pub struct CreateProjectRequest {
-    pub title: String,
-    pub deadline: String,
-}
-
-#[command]
-pub async fn create_project(request: CreateProjectRequest) -> Result<Project, String> {
-    let pool = get_db_pool()?;
-    let id = chrono::Utc::now().timestamp_millis().to_string();
+    sqlx::query("UPDATE todos SET priority = ?1 WHERE id = ?2")
+        .bind(next)
+        .bind(&id)
+        .execute(pool)
+        .await
+        .map_err(|e| format!("Failed to update todo priority: {}", e))?;
 
-    sqlx::query(
-        "INSERT INTO projects (id, title, deadline, progress, status) VALUES (?1, ?2, ?3, 0, 'active')"
+    let row = sqlx::query(
+        "SELECT id, title, completed, priority, created_at, completed_at, project_id, snoozed_until, pinned FROM todos WHERE id = ?1",
     )
     .bind(&id)
-    .bind(&request.title)
-    .bind(&request.deadline)
-    .execute(pool)
+    .fetch_one(pool)
     .await
-    .map_err(|e| format!("Failed to create project: {}", e))?;
+    .map_err(|e| format!("Failed to fetch updated todo: {}", e))?;
 
-    let row =
-        sqlx::query("SELECT id, title, deadline, progress, status FROM projects WHERE id = ?1")
-            .bind(&id)
-            .fetch_one(pool)
-            .await
-            .map_err(|e| format!("Failed to fetch created project: {}", e))?;
+    Ok(row_to_todo(row))
+}
+
+fn next_todo_priority(current: &str) -> &'static str {
+    match TODO_PRIORITIES
+        .iter()
+        .position(|priority| priority.eq_ignore_ascii_case(current.trim()))
+    {
+        Some(index) => TODO_PRIORITIES[(index + 1) % TODO_PRIORITIES.len()],
+        None => "normal",
+    }
+}
+
+fn parse_snooze_until(input: &str) -> Result<chrono::DateTime<chrono::Utc>, String> {
+    if let Ok(value) = chrono::DateTime::parse_from_rfc3339(input) {
+        return Ok(value.with_timezone(&chrono::Utc));
+    }
+    if let Ok(value) = chrono::NaiveDateTime::parse_from_str(input, "%Y-%m-%d %H:%M:%S") {
+        return Ok(value.and_utc());
+    }
+    if let Ok(date) = chrono::NaiveDate::parse_from_str(input, "%Y-%m-%d") {
+        if let Some(value) = local_midnight_utc(date, *app_now().offset()) {
+            return Ok(value);
+        }
+    }
+    Err(format!("无法解析稍后提醒时间: {}", input))
+}
 
-    Ok(Project {
+fn row_to_todo(row: sqlx::sqlite::SqliteRow) -> Todo {
+    Todo {
         id: row.get("id"),
         title: row.get("title"),
-        deadline: row.get("deadline"),
-        progress: row.get("progress"),
-        status: row.get("status"),
-    })
+        completed: row.get::<i32, _>("completed") != 0,
+        priority: row.get("priority"),
+        created_at: row.get("created_at"),
+        completed_at: row.get("completed_at"),
+        project_id: row.get("project_id"),
+        snoozed_until: row.get("snoozed_until"),
+        pinned: row.get::<i32, _>("pinned") != 0,
+    }
 }
 
-#[derive(Deserialize)]
-pub struct UpdateProjectRequest {
-    pub id: String,
-    pub title: Option<String>,
-    pub deadline: Option<String>,
-    pub progress: Option<i32>,
-    pub status: Option<String>,
+/// Trims and lowercases a priority, rejecting anything outside `TODO_PRIORITIES`.
+fn normalize_todo_priority(input: &str) -> Result<String, String> {
+    let normalized = input.trim().to_lowercase();
+    if TODO_PRIORITIES.contains(&normalized.as_str()) {
+        Ok(normalized)
+    } else {
+        Err(format!(
+            "Invalid todo priority: {} (expected one of {})",
+            input,
+            TODO_PRIORITIES.join(", ")
+        ))
+    }
 }
 
-#[command]
-pub async fn update_project(request: UpdateProjectRequest) -> Result<Project, String> {
-    let pool = get_db_pool()?;
-
-    let mut updates: Vec<String> = Vec::new();
+/// SQL expression ranking `priority` by its position in `TODO_PRIORITIES`.
+fn todo_priority_rank_sql() -> String {
+    let cases = TODO_PRIORITIES
+        .iter()
+        .enumerate()
+        .map(|(rank, priority)| format!("WHEN '{}' THEN {}", priority, rank))
+        .collect::<Vec<String>>()
+        .join(" ");
+    format!("(CASE priority {} ELSE -1 END)", cases)
+}
 
-    if request.title.is_some() {
-        updates.push("title = ?".to_string());
+/// SET fragment keeping `completed_at` in sync with a `completed` change.
+/// SQLite evaluates `completed` here against the pre-update row, so the
+/// timestamp is only stamped on a 0→1 transition.
+fn completed_at_assignment(completed: bool) -> &'static str {
+    if completed {
+        "completed_at = CASE WHEN completed = 0 THEN CURRENT_TIMESTAMP ELSE completed_at END"
+    } else {
+        "completed_at = NULL"
     }
-    if request.deadline.is_some() {
-        updates.push("deadline = ?".to_string());
+}
+
+/// Bound value for one column assignment collected by `UpdateBuilder`.
+#[derive(Debug, Clone, PartialEq)]
+enum UpdateValue {
+    Text(String),
+    Integer(i64),
+    Real(f64),
+}
+
+impl From<&str> for UpdateValue {
+    fn from(value: &str) -> Self {
+        UpdateValue::Text(value.to_string())
     }
-    if request.progress.is_some() {
-        updates.push("progress = ?".to_string());
+}
+
+impl From<String> for UpdateValue {
+    fn from(value: String) -> Self {
+        UpdateValue::Text(value)
     }
-    if request.status.is_some() {
-        updates.push("status = ?".to_string());
+}
+
+impl From<i32> for UpdateValue {
+    fn from(value: i32) -> Self {
+        UpdateValue::Integer(value as i64)
     }
+}
 
-    if updates.is_empty() {
-        return Err("No fields to update".to_string());
+impl From<f64> for UpdateValue {
+    fn from(value: f64) -> Self {
+        UpdateValue::Real(value)
     }
+}
 
-    let query = format!("UPDATE projects SET {} WHERE id = ?", updates.join(", "));
-    let mut query_builder = sqlx::query(&query);
+/// Collects `column = ?` assignments together with their binds so the SQL and
+/// the bind order can't drift apart in the dynamic `UPDATE ... WHERE id = ?` paths.
+#[derive(Debug, Default)]
+struct UpdateBuilder {
+    assignments: Vec<String>,
+    binds: Vec<UpdateValue>,
+}
 
-    if let Some(title) = &request.title {
-        query_builder = query_builder.bind(title);
-    }
-    if let Some(deadline) = &request.deadline {
-        query_builder = query_builder.bind(deadline);
+impl UpdateBuilder {
+    fn new() -> Self {
+        Self::default()
     }
-    if let Some(progress) = request.progress {
-        query_builder = query_builder.bind(progress);
+
+    fn set_opt<T: Into<UpdateValue>>(&mut self, column: &str, value: Option<T>) {
+        if let Some(value) = value {
+            self.assignments.push(format!("{} = ?", column));
+            self.binds.push(value.into());
+        }
     }
-    if let Some(status) = &request.status {
-        query_builder = query_builder.bind(status);
+
+    /// Sets `completed` and keeps `completed_at` in step with it.
+    fn set_completed(&mut self, completed: Option<bool>) {
+        if let Some(completed) = completed {
+            self.assignments.push("completed = ?".to_string());
+            self.binds
+                .push(UpdateValue::Integer(if completed { 1 } else { 0 }));
+            self.assignments
+                .push(completed_at_assignment(completed).to_string());
+        }
     }
-    query_builder = query_builder.bind(&request.id);
 
-    query_builder
-        .execute(pool)
-        .await
-        .map_err(|e| format!("Failed to update project: {}", e))?;
+    fn is_empty(&self) -> bool {
+        self.assignments.is_empty()
+    }
 
-    let row =
-        sqlx::query("SELECT id, title, deadline, progress, status FROM projects WHERE id = ?1")
-            .bind(&request.id)
-            .fetch_one(pool)
-            .await
-            .map_err(|e| format!("Failed to fetch updated project: {}", e))?;
+    fn sql(&self, table: &str) -> String {
+        format!(
+            "UPDATE {} SET {} WHERE id = ?",
+            table,
+            self.assignments.join(", ")
+        )
+    }
 
-    Ok(Project {
-        id: row.get("id"),
-        title: row.get("title"),
-        deadline: row.get("deadline"),
-        progress: row.get("progress"),
-        status: row.get("status"),
-    })
+    async fn execute<'c, E>(
+        &self,
+        table: &str,
+        id: &str,
+        executor: E,
+    ) -> Result<sqlx::sqlite::SqliteQueryResult, sqlx::Error>
+    where
+        E: sqlx::Executor<'c, Database = sqlx::Sqlite>,
+    {
+        let sql = self.sql(table);
+        let mut query = sqlx::query(&sql);
+        for bind in &self.binds {
+            query = match bind {
+                UpdateValue::Text(value) => query.bind(value.as_str()),
+                UpdateValue::Integer(value) => query.bind(*value),
+                UpdateValue::Real(value) => query.bind(*value),
+            };
+        }
+        query.bind(id).execute(executor).await
+    }
 }
 
 #[command]
-pub async fn delete_project(id: String) -> Result<(), String> {
+pub async fn delete_todo(id: String) -> Result<(), String> {
     let pool = get_db_pool()?;
-    sqlx::query("DELETE FROM projects WHERE id = ?1")
+    sqlx::query("DELETE FROM todos WHERE id = ?1")
         .bind(&id)
         .execute(pool)
         .await
-        .map_err(|e| format!("Failed to delete project: {}", e))?;
+        .map_err(|e| format!("Failed to delete todo: {}", e))?;
     Ok(())
 }
 
-// ============= Event Commands =============
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecurringTodoTemplate {
+    pub id: String,
+    pub title: String,
+    pub priority: String,
+    pub rrule: String,
+    pub start_date: String,
+    /// Date of the last occurrence turned into a todo.
+    pub last_generated_date: Option<String>,
+    pub enabled: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateRecurringTodoTemplateRequest {
+    pub title: String,
+    pub priority: Option<String>,
+    pub rrule: String,
+    /// First possible occurrence; defaults to today.
+    pub start_date: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerateRecurringTodosResponse {
+    pub created: usize,
+    pub ids: Vec<String>,
+}
 
 #[command]
-pub async fn get_events() -> Result<Vec<CalendarEvent>, String> {
+pub async fn get_recurring_todo_templates() -> Result<Vec<RecurringTodoTemplate>, String> {
     let pool = get_db_pool()?;
-    let rows = sqlx::query("SELECT id, title, date, color, note FROM events ORDER BY date")
-        .fetch_all(pool)
-        .await
-        .map_err(|e| format!("Failed to fetch events: {}", e))?;
-
-    let events: Vec<CalendarEvent> = rows
+    let rows = sqlx::query(
+        "SELECT id, title, priority, rrule, start_date, last_generated_date, enabled
+         FROM recurring_todo_templates ORDER BY created_at, id",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch recurring todo templates: {}", e))?;
+    Ok(rows
         .into_iter()
-        .map(|row| CalendarEvent {
-            id: row.get("id"),
-            title: row.get("title"),
-            date: row.get("date"),
-            color: row.get("color"),
-            note: row.get("note"),
-        })
-        .collect();
-
-    Ok(events)
+        .map(row_to_recurring_todo_template)
+        .collect())
 }
 
 #[command]
-pub async fn get_events_by_date(date: String) -> Result<Vec<CalendarEvent>, String> {
+pub async fn create_recurring_todo_template(
+    request: CreateRecurringTodoTemplateRequest,
+) -> Result<RecurringTodoTemplate, String> {
     let pool = get_db_pool()?;
-    let rows = sqlx::query("SELECT id, title, date, color, note FROM events WHERE date = ?1")
-        .bind(&date)
-        .fetch_all(pool)
-        .await
-        .map_err(|e| format!("Failed to fetch events: {}", e))?;
+    let title = request.title.trim();
+    if title.is_empty() {
+        return Err("标题不能为空".to_string());
+    }
+    let priority = normalize_todo_priority(request.priority.as_deref().unwrap_or("normal"))?;
+    let rrule = request.rrule.trim().to_uppercase();
+    parse_todo_rrule(&rrule)?;
+    let start_date = match request.start_date.as_deref().map(str::trim) {
+        Some(date) if !date.is_empty() => normalize_event_date(date)?,
+        _ => local_today_string(),
+    };
 
-    let events: Vec<CalendarEvent> = rows
-        .into_iter()
-        .map(|row| CalendarEvent {
-            id: row.get("id"),
-            title: row.get("title"),
-            date: row.get("date"),
-            color: row.get("color"),
-            note: row.get("note"),
-        })
-        .collect();
+    let id = new_entity_id();
+    sqlx::query(
+        "INSERT INTO recurring_todo_templates (id, title, priority, rrule, start_date)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+    )
+    .bind(&id)
+    .bind(title)
+    .bind(&priority)
+    .bind(&rrule)
+    .bind(&start_date)
+    .execute(pool)
+    .await
+    .map_err(|e| format!("Failed to create recurring todo template: {}", e))?;
 
-    Ok(events)
+    Ok(RecurringTodoTemplate {
+        id,
+        title: title.to_string(),
+        priority,
+        rrule,
+        start_date,
+        last_generated_date: None,
+        enabled: true,
+    })
 }
 
-#[derive(Deserialize)]
-pub struct CreateEventRequest {
-    pub title: String,
-    pub date: String,
-    #[serde(default)]
-    pub color: Option<String>,
-    #[serde(default)]
-    pub note: Option<String>,
+#[command]
+pub async fn delete_recurring_todo_template(id: String) -> Result<(), String> {
+    let pool = get_db_pool()?;
+    sqlx::query("DELETE FROM recurring_todo_templates WHERE id = ?1")
+        .bind(&id)
+        .execute(pool)
+        .await
+        .map_err(|e| format!("Failed to delete recurring todo template: {}", e))?;
+    Ok(())
 }
 
+/// Creates a todo for every enabled template with an occurrence between its last
+/// generated date and today. Occurrences missed while the app was closed collapse
+/// into one todo per template, so a long absence does not flood the list.
 #[command]
-pub async fn create_event(request: CreateEventRequest) -> Result<CalendarEvent, String> {
+pub async fn generate_due_recurring_todos() -> Result<GenerateRecurringTodosResponse, String> {
     let pool = get_db_pool()?;
-    let id = chrono::Utc::now().timestamp_millis().to_string();
-    let color = request.color.unwrap_or_else(|| "blue".to_string());
+    let today = app_now().date_naive();
 
-    sqlx::query("INSERT INTO events (id, title, date, color, note) VALUES (?1, ?2, ?3, ?4, ?5)")
-        .bind(&id)
-        .bind(&request.title)
-        .bind(&request.date)
-        .bind(&color)
-        .bind(&request.note)
-        .execute(pool)
+    let mut tx = pool
+        .begin()
         .await
-        .map_err(|e| format!("Failed to create event: {}", e))?;
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+    let templates = sqlx::query(
+        "SELECT id, title, priority, rrule, start_date, last_generated_date, enabled
+         FROM recurring_todo_templates WHERE enabled = 1 ORDER BY created_at, id",
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to fetch recurring todo templates: {}", e))?
+    .into_iter()
+    .map(row_to_recurring_todo_template);
+    let mut ids = Vec::new();
+    for template in templates {
+        // Stored rules were validated on create; skip any that no longer parse
+        let Ok(rule) = parse_todo_rrule(&template.rrule) else {
+            continue;
+        };
+        let Ok(start) = chrono::NaiveDate::parse_from_str(&template.start_date, "%Y-%m-%d") else {
+            continue;
+        };
+        let after = template
+            .last_generated_date
+            .as_deref()
+            .and_then(|date| chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").ok());
+        let Some(occurrence) = latest_todo_occurrence(&rule, start, after, today) else {
+            continue;
+        };
 
-    let row = sqlx::query("SELECT id, title, date, color, note FROM events WHERE id = ?1")
-        .bind(&id)
-        .fetch_one(pool)
+        // Only the caller that advances the marker creates the todo, so concurrent
+        // calls (e.g. two views loading todos at once) cannot both generate it
+        let claimed = sqlx::query(
+            "UPDATE recurring_todo_templates SET last_generated_date = ?1
+             WHERE id = ?2 AND (last_generated_date IS NULL OR last_generated_date < ?1)",
+        )
+        .bind(occurrence.format("%Y-%m-%d").to_string())
+        .bind(&template.id)
+        .execute(&mut *tx)
         .await
-        .map_err(|e| format!("Failed to fetch created event: {}", e))?;
+        .map_err(|e| format!("Failed to update recurring todo template: {}", e))?;
+        if claimed.rows_affected() != 1 {
+            continue;
+        }
 
-    Ok(CalendarEvent {
-        id: row.get("id"),
-        title: row.get("title"),
-        date: row.get("date"),
-        color: row.get("color"),
-        note: row.get("note"),
+        let id = new_entity_id();
+        sqlx::query("INSERT INTO todos (id, title, priority) VALUES (?1, ?2, ?3)")
+            .bind(&id)
+            .bind(&template.title)
+            .bind(&template.priority)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Failed to create todo: {}", e))?;
+        ids.push(id);
+    }
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit transaction: {}", e))?;
+
+    Ok(GenerateRecurringTodosResponse {
+        created: ids.len(),
+        ids,
     })
 }
 
-#[derive(Deserialize)]
-pub struct UpdateEventRequest {
-    pub id: String,
-    pub title: Option<String>,
-    pub date: Option<String>,
-    pub color: Option<String>,
-    pub note: Option<String>,
+fn row_to_recurring_todo_template(row: sqlx::sqlite::SqliteRow) -> RecurringTodoTemplate {
+    RecurringTodoTemplate {
+        id: row.get("id"),
+        title: row.get("title"),
+        priority: row.get("priority"),
+        rrule: row.get("rrule"),
+        start_date: row.get("start_date"),
+        last_generated_date: row.get("last_generated_date"),
+        enabled: row.get::<i32, _>("enabled") != 0,
+    }
 }
 
-#[command]
-pub async fn update_event(request: UpdateEventRequest) -> Result<CalendarEvent, String> {
-    let pool = get_db_pool()?;
-
-    let mut updates: Vec<String> = Vec::new();
-
-    if request.title.is_some() {
-        updates.push("title = ?".to_string());
-    }
-    if request.date.is_some() {
-        updates.push("date = ?".to_string());
+/// The subset of RFC 5545 RRULE that recurring todos support.
+struct TodoRecurrence {
+    freq: TodoRecurrenceFreq,
+    interval: u32,
+    by_day: Vec<chrono::Weekday>,
+    by_month_day: Option<u32>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum TodoRecurrenceFreq {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+/// Parses `FREQ=DAILY|WEEKLY|MONTHLY` with optional `INTERVAL`, `BYDAY` (weekly,
+/// e.g. `MO,WE`) and `BYMONTHDAY` (monthly). A leading `RRULE:` is accepted.
+fn parse_todo_rrule(input: &str) -> Result<TodoRecurrence, String> {
+    let rule = input.trim();
+    let rule = rule.strip_prefix("RRULE:").unwrap_or(rule);
+    let mut freq = None;
+    let mut interval = 1;
+    let mut by_day = Vec::new();
+    let mut by_month_day = None;
+    for part in rule.split(';').filter(|part| !part.trim().is_empty()) {
+        let (key, value) = part
+            .split_once('=')
+            .ok_or_else(|| format!("无效的重复规则片段: {}", part))?;
+        match key.trim() {
+            "FREQ" => {
+                freq = Some(match value.trim() {
+                    "DAILY" => TodoRecurrenceFreq::Daily,
+                    "WEEKLY" => TodoRecurrenceFreq::Weekly,
+                    "MONTHLY" => TodoRecurrenceFreq::Monthly,
+                    other => return Err(format!("不支持的重复频率: {}", other)),
+                })
+            }
+            "INTERVAL" => {
+                interval = value
+                    .trim()
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|value| *value > 0)
+                    .ok_or_else(|| format!("无效的 INTERVAL: {}", value))?;
+            }
+            "BYDAY" => {
+                for day in value.split(',') {
+                    let weekday = match day.trim() {
+                        "MO" => chrono::Weekday::Mon,
+                        "TU" => chrono::Weekday::Tue,
+                        "WE" => chrono::Weekday::Wed,
+                        "TH" => chrono::Weekday::Thu,
+                        "FR" => chrono::Weekday::Fri,
+                        "SA" => chrono::Weekday::Sat,
+                        "SU" => chrono::Weekday::Sun,
+                        other => return Err(format!("无效的 BYDAY: {}", other)),
+                    };
+                    by_day.push(weekday);
+                }
+            }
+            "BYMONTHDAY" => {
+                by_month_day = Some(
+                    value
+                        .trim()
+                        .parse::<u32>()
+                        .ok()
+                        .filter(|day| (1..=31).contains(day))
+                        .ok_or_else(|| format!("无效的 BYMONTHDAY: {}", value))?,
+                );
+            }
+            other => return Err(format!("不支持的重复规则字段: {}", other)),
+        }
     }
-    if request.color.is_some() {
-        updates.push("color = ?".to_string());
+    let freq = freq.ok_or_else(|| "重复规则缺少 FREQ".to_string())?;
+    if !by_day.is_empty() && freq != TodoRecurrenceFreq::Weekly {
+        return Err("BYDAY 仅支持 FREQ=WEEKLY".to_string());
     }
-    if request.note.is_some() {
-        updates.push("note = ?".to_string());
+    if by_month_day.is_some() && freq != TodoRecurrenceFreq::Monthly {
+        return Err("BYMONTHDAY 仅支持 FREQ=MONTHLY".to_string());
     }
+    Ok(TodoRecurrence {
+        freq,
+        interval,
+        by_day,
+        by_month_day,
+    })
+}
 
-    if updates.is_empty() {
-        return Err("No fields to update".to_string());
+/// Whether the rule, anchored at `start`, has an occurrence on `date`. Without
+/// `BYDAY`/`BYMONTHDAY` the start date's weekday or day of month is used; months
+/// too short for the day are skipped, as in RFC 5545.
+fn todo_recurrence_occurs_on(
+    rule: &TodoRecurrence,
+    start: chrono::NaiveDate,
+    date: chrono::NaiveDate,
+) -> bool {
+    use chrono::Datelike;
+    if date < start {
+        return false;
+    }
+    let interval = i64::from(rule.interval);
+    match rule.freq {
+        TodoRecurrenceFreq::Daily => (date - start).num_days() % interval == 0,
+        TodoRecurrenceFreq::Weekly => {
+            let weekday_matches = if rule.by_day.is_empty() {
+                date.weekday() == start.weekday()
+            } else {
+                rule.by_day.contains(&date.weekday())
+            };
+            let week_of = |day: chrono::NaiveDate| {
+                day - chrono::Duration::days(day.weekday().num_days_from_monday().into())
+            };
+            weekday_matches && ((week_of(date) - week_of(start)).num_days() / 7) % interval == 0
+        }
+        TodoRecurrenceFreq::Monthly => {
+            let months =
+                (date.year() - start.year()) * 12 + date.month() as i32 - start.month() as i32;
+            date.day() == rule.by_month_day.unwrap_or(start.day())
+                && i64::from(months) % interval == 0
+        }
     }
+}
 
-    let query = format!("UPDATE events SET {} WHERE id = ?", updates.join(", "));
-    let mut query_builder = sqlx::query(&query);
+/// Longest gap searched for a missed occurrence; anything older is not caught up.
+const MAX_RECURRENCE_LOOKBACK_DAYS: i64 = 366;
 
-    if let Some(title) = &request.title {
-        query_builder = query_builder.bind(title);
+/// The most recent occurrence in `(after, today]`, not earlier than `start`.
+fn latest_todo_occurrence(
+    rule: &TodoRecurrence,
+    start: chrono::NaiveDate,
+    after: Option<chrono::NaiveDate>,
+    today: chrono::NaiveDate,
+) -> Option<chrono::NaiveDate> {
+    let mut earliest = start.max(today - chrono::Duration::days(MAX_RECURRENCE_LOOKBACK_DAYS));
+    if let Some(after) = after {
+        earliest = earliest.max(after + chrono::Duration::days(1));
     }
-    if let Some(date) = &request.date {
-        query_builder = query_builder.bind(date);
-    }
-    if let Some(color) = &request.color {
-        query_builder = query_builder.bind(color);
-    }
-    if let Some(note) = &request.note {
-        query_builder = query_builder.bind(note);
+    let mut date = today;
+    while date >= earliest {
+        if todo_recurrence_occurs_on(rule, start, date) {
+            return Some(date);
+        }
+        date = date.pred_opt()?;
     }
-    query_builder = query_builder.bind(&request.id);
-
-    query_builder
-        .execute(pool)
-        .await
-        .map_err(|e| format!("Failed to update event: {}", e))?;
-
-    let row = sqlx::query("SELECT id, title, date, color, note FROM events WHERE id = ?1")
-        .bind(&request.id)
-        .fetch_one(pool)
-        .await
-        .map_err(|e| format!("Failed to fetch updated event: {}", e))?;
-
-    Ok(CalendarEvent {
-        id: row.get("id"),
-        title: row.get("title"),
-        date: row.get("date"),
-        color: row.get("color"),
-        note: row.get("note"),
-    })
+    None
 }
 
+// ============= Project Commands =============
+
 #[command]
-pub async fn delete_event(id: String) -> Result<(), String> {
+pub async fn get_projects() -> Result<Vec<Project>, String> {
     let pool = get_db_pool()?;
-    sqlx::query("DELETE FROM events WHERE id = ?1")
-        .bind(&id)
-        .execute(pool)
-        .await
-        .map_err(|e| format!("Failed to delete event: {}", e))?;
-    Ok(())
-}
+    let rows =
+        sqlx::query("SELECT id, title, deadline, progress, status, pinned FROM projects ORDER BY pinned DESC, deadline")
+            .fetch_all(pool)
+            .await
+            .map_err(|e| format!("Failed to fetch projects: {}", e))?;
 
-// ============= Personal Task Commands =============
+    let projects: Vec<Project> = rows.into_iter().map(row_to_project).collect();
+
+    Ok(projects)
+}
 
 #[command]
-pub async fn get_personal_tasks() -> Result<Vec<PersonalTask>, String> {
+pub async fn get_project(id: String) -> Result<Option<Project>, String> {
     let pool = get_db_pool()?;
-    let rows = sqlx::query(
-        "SELECT id, title, budget, date, location, note FROM personal_tasks ORDER BY date",
+    let row = sqlx::query(
+        "SELECT id, title, deadline, progress, status, pinned FROM projects WHERE id = ?1",
     )
-    .fetch_all(pool)
+    .bind(&id)
+    .fetch_optional(pool)
     .await
-    .map_err(|e| format!("Failed to fetch personal tasks: {}", e))?;
-
-    let tasks: Vec<PersonalTask> = rows
-        .into_iter()
-        .map(|row| PersonalTask {
-            id: row.get("id"),
-            title: row.get("title"),
-            budget: row.get("budget"),
-            date: row.get("date"),
-            location: row.get("location"),
-            note: row.get("note"),
-        })
-        .collect();
+    .map_err(|e| format!("Failed to fetch project: {}", e))?;
+    Ok(row.map(row_to_project))
+}
 
-    Ok(tasks)
+fn row_to_project(row: sqlx::sqlite::SqliteRow) -> Project {
+    Project {
+        id: row.get("id"),
+        title: row.get("title"),
+        deadline: row.get("deadline"),
+        progress: row.get("progress"),
+        status: row.get("status"),
+        pinned: row.get::<i32, _>("pinned") != 0,
+    }
 }
 
 #[derive(Deserialize)]
-pub struct CreatePersonalTaskRequest {
+pub struct CreateProjectRequest {
     pub title: String,
-    #[serde(default)]
-    pub budget: Option<f64>,
-    #[serde(default)]
-    pub date: Option<String>,
-    #[serde(default)]
-    pub location: Option<String>,
-    #[serde(default)]
-    pub note: Option<String>,
+    pub deadline: String,
 }
 
 #[command]
-pub async fn create_personal_task(
-    request: CreatePersonalTaskRequest,
-) -> Result<PersonalTask, String> {
+pub async fn create_project(request: CreateProjectRequest) -> Result<Project, String> {
     let pool = get_db_pool()?;
-    let id = chrono::Utc::now().timestamp_millis().to_string();
+    let id = new_entity_id();
 
     sqlx::query(
-        "INSERT INTO personal_tasks (id, title, budget, date, location, note) VALUES (?1, ?2, ?3, ?4, ?5, ?6)"
+        "INSERT INTO projects (id, title, deadline, progress, status) VALUES (?1, ?2, ?3, 0, 'active')"
     )
     .bind(&id)
     .bind(&request.title)
-    .bind(request.budget)
-    .bind(&request.date)
-    .bind(&request.location)
-    .bind(&request.note)
+    .bind(&request.deadline)
     .execute(pool)
     .await
-    .map_err(|e| format!("Failed to create personal task: {}", e))?;
+    .map_err(|e| format!("Failed to create project: {}", e))?;
 
     let row = sqlx::query(
-        "SELECT id, title, budget, date, location, note FROM personal_tasks WHERE id = ?1",
+        "SELECT id, title, deadline, progress, status, pinned FROM projects WHERE id = ?1",
     )
     .bind(&id)
     .fetch_one(pool)
     .await
-    .map_err(|e| format!("Failed to fetch created personal task: {}", e))?;
+    .map_err(|e| format!("Failed to fetch created project: {}", e))?;
 
-    Ok(PersonalTask {
-        id: row.get("id"),
-        title: row.get("title"),
-        budget: row.get("budget"),
-        date: row.get("date"),
-        location: row.get("location"),
-        note: row.get("note"),
+    Ok(row_to_project(row))
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateProjectWithTodosResponse {
+    pub project: Project,
+    pub todo_ids: Vec<String>,
+}
+
+/// Creates a project and its first todos in one transaction, so a failure never
+/// leaves an orphan project behind.
+#[command]
+pub async fn create_project_with_todos(
+    title: String,
+    deadline: String,
+    todos: Vec<String>,
+) -> Result<CreateProjectWithTodosResponse, String> {
+    let pool = get_db_pool()?;
+    let todo_titles: Vec<&str> = todos
+        .iter()
+        .map(|todo| todo.trim())
+        .filter(|todo| !todo.is_empty())
+        .collect();
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+    let project_id = new_entity_id();
+    sqlx::query(
+        "INSERT INTO projects (id, title, deadline, progress, status) VALUES (?1, ?2, ?3, 0, 'active')",
+    )
+    .bind(&project_id)
+    .bind(&title)
+    .bind(&deadline)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to create project: {}", e))?;
+
+    let mut todo_ids = Vec::with_capacity(todo_titles.len());
+    for todo_title in todo_titles {
+        let todo_id = new_entity_id();
+        sqlx::query(
+            "INSERT INTO todos (id, title, priority, project_id) VALUES (?1, ?2, 'normal', ?3)",
+        )
+        .bind(&todo_id)
+        .bind(todo_title)
+        .bind(&project_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to create todo: {}", e))?;
+        todo_ids.push(todo_id);
+    }
+
+    let row = sqlx::query(
+        "SELECT id, title, deadline, progress, status, pinned FROM projects WHERE id = ?1",
+    )
+    .bind(&project_id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to fetch created project: {}", e))?;
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit transaction: {}", e))?;
+
+    Ok(CreateProjectWithTodosResponse {
+        project: row_to_project(row),
+        todo_ids,
     })
 }
 
 #[derive(Deserialize)]
-pub struct UpdatePersonalTaskRequest {
+pub struct UpdateProjectRequest {
     pub id: String,
     pub title: Option<String>,
-    pub budget: Option<f64>,
-    pub date: Option<String>,
-    pub location: Option<String>,
-    pub note: Option<String>,
+    pub deadline: Option<String>,
+    pub progress: Option<i32>,
+    pub status: Option<String>,
 }
 
 #[command]
-pub async fn update_personal_task(
-    request: UpdatePersonalTaskRequest,
-) -> Result<PersonalTask, String> {
+pub async fn update_project(request: UpdateProjectRequest) -> Result<Project, String> {
     let pool = get_db_pool()?;
 
-    let mut updates: Vec<String> = Vec::new();
-
-    if request.title.is_some() {
-        updates.push("title = ?".to_string());
-    }
-    if request.budget.is_some() {
-        updates.push("budget = ?".to_string());
-    }
-    if request.date.is_some() {
-        updates.push("date = ?".to_string());
-    }
-    if request.location.is_some() {
-        updates.push("location = ?".to_string());
-    }
-    if request.note.is_some() {
-        updates.push("note = ?".to_string());
-    }
+    let mut updates = UpdateBuilder::new();
+    updates.set_opt("title", request.title.as_deref());
+    updates.set_opt("deadline", request.deadline.as_deref());
+    updates.set_opt("progress", request.progress);
+    updates.set_opt("status", request.status.as_deref());
 
     if updates.is_empty() {
         return Err("No fields to update".to_string());
     }
 
-    let query = format!(
-        "UPDATE personal_tasks SET {} WHERE id = ?",
-        updates.join(", ")
-    );
-    let mut query_builder = sqlx::query(&query);
-
-    if let Some(title) = &request.title {
-        query_builder = query_builder.bind(title);
-    }
-    if let Some(budget) = request.budget {
-        query_builder = query_builder.bind(budget);
-    }
-    if let Some(date) = &request.date {
-        query_builder = query_builder.bind(date);
-    }
-    if let Some(location) = &request.location {
-        query_builder = query_builder.bind(location);
-    }
-    if let Some(note) = &request.note {
-        query_builder = query_builder.bind(note);
-    }
-    query_builder = query_builder.bind(&request.id);
-
-    query_builder
-        .execute(pool)
+    updates
+        .execute("projects", &request.id, pool)
         .await
-        .map_err(|e| format!("Failed to update personal task: {}", e))?;
+        .map_err(|e| format!("Failed to update project: {}", e))?;
 
     let row = sqlx::query(
-        "SELECT id, title, budget, date, location, note FROM personal_tasks WHERE id = ?1",
+        "SELECT id, title, deadline, progress, status, pinned FROM projects WHERE id = ?1",
     )
     .bind(&request.id)
     .fetch_one(pool)
     .await
-    .map_err(|e| format!("Failed to fetch updated personal task: {}", e))?;
+    .map_err(|e| format!("Failed to fetch updated project: {}", e))?;
 
-    Ok(PersonalTask {
-        id: row.get("id"),
-        title: row.get("title"),
-        budget: row.get("budget"),
-        date: row.get("date"),
-        location: row.get("location"),
-        note: row.get("note"),
-    })
+    Ok(row_to_project(row))
 }
 
 #[command]
-pub async fn delete_personal_task(id: String) -> Result<(), String> {
+pub async fn delete_project(id: String) -> Result<(), String> {
     let pool = get_db_pool()?;
-    sqlx::query("DELETE FROM personal_tasks WHERE id = ?1")
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+    sqlx::query("UPDATE todos SET project_id = NULL WHERE project_id = ?1")
         .bind(&id)
-        .execute(pool)
+        .execute(&mut *tx)
         .await
-        .map_err(|e| format!("Failed to delete personal task: {}", e))?;
+        .map_err(|e| format!("Failed to unlink project todos: {}", e))?;
+    sqlx::query("DELETE FROM projects WHERE id = ?1")
+        .bind(&id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to delete project: {}", e))?;
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit transaction: {}", e))?;
     Ok(())
 }
 
-// ============= Inspiration Commands =============
+// ============= Event Commands =============
 
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct CreateInspirationRequest {
-    pub content: String,
-}
+#[command]
+pub async fn get_events() -> Result<Vec<CalendarEvent>, String> {
+    let pool = get_db_pool()?;
+    let rows = sqlx::query(
+        "SELECT id, title, date, color, note, pinned FROM events ORDER BY pinned DESC, date",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch events: {}", e))?;
 
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct ToggleInspirationArchivedRequest {
-    pub id: String,
-    pub is_archived: bool,
+    let events: Vec<CalendarEvent> = rows.into_iter().map(row_to_event).collect();
+
+    Ok(events)
 }
 
 #[command]
-pub async fn get_inspirations(include_archived: Option<bool>) -> Result<Vec<Inspiration>, String> {
+pub async fn get_event(id: String) -> Result<Option<CalendarEvent>, String> {
     let pool = get_db_pool()?;
-    let show_archived = include_archived.unwrap_or(true);
-
-    let rows = if show_archived {
-        sqlx::query(
-            "SELECT id, content, is_archived, created_at, updated_at
-             FROM inspirations
-             ORDER BY created_at DESC",
-        )
-        .fetch_all(pool)
-        .await
-        .map_err(|e| format!("Failed to fetch inspirations: {}", e))?
-    } else {
-        sqlx::query(
-            "SELECT id, content, is_archived, created_at, updated_at
-             FROM inspirations
-             WHERE is_archived = 0
-             ORDER BY created_at DESC",
-        )
-        .fetch_all(pool)
+    let row = sqlx::query("SELECT id, title, date, color, note, pinned FROM events WHERE id = ?1")
+        .bind(&id)
+        .fetch_optional(pool)
         .await
-        .map_err(|e| format!("Failed to fetch inspirations: {}", e))?
-    };
+        .map_err(|e| format!("Failed to fetch event: {}", e))?;
+    Ok(row.map(row_to_event))
+}
 
-    Ok(rows
-        .into_iter()
-        .map(|row| Inspiration {
-            id: row.get("id"),
-            content: row.get("content"),
-            is_archived: row.get::<i32, _>("is_archived") != 0,
-            created_at: row.get("created_at"),
-            updated_at: row.get("updated_at"),
-        })
-        .collect())
+fn row_to_event(row: sqlx::sqlite::SqliteRow) -> CalendarEvent {
+    CalendarEvent {
+        id: row.get("id"),
+        title: row.get("title"),
+        date: row.get("date"),
+        color: row.get("color"),
+        note: row.get("note"),
+        pinned: row.get::<i32, _>("pinned") != 0,
+        warning: None,
+    }
 }
 
 #[command]
-pub async fn create_inspiration(request: CreateInspirationRequest) -> Result<Inspiration, String> {
+pub async fn get_events_by_date(date: String) -> Result<Vec<CalendarEvent>, String> {
     let pool = get_db_pool()?;
-    let content = request.content.trim();
-    if content.is_empty() {
-        return Err("Inspiration content cannot be empty".to_string());
-    }
-    let id = chrono::Utc::now().timestamp_millis().to_string();
-
-    sqlx::query(
-        "INSERT INTO inspirations (id, content, is_archived, created_at, updated_at)
-         VALUES (?1, ?2, 0, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)",
+    let date = normalize_event_date(&date)?;
+    let rows = sqlx::query(
+        "SELECT id, title, date, color, note, pinned FROM events WHERE date = ?1 ORDER BY pinned DESC",
     )
-    .bind(&id)
-    .bind(content)
-    .execute(pool)
+    .bind(&date)
+    .fetch_all(pool)
     .await
-    .map_err(|e| format!("Failed to create inspiration: {}", e))?;
+    .map_err(|e| format!("Failed to fetch events: {}", e))?;
 
-    let row = sqlx::query(
-        "SELECT id, content, is_archived, created_at, updated_at
-         FROM inspirations
-         WHERE id = ?1",
-    )
-    .bind(&id)
-    .fetch_one(pool)
-    .await
-    .map_err(|e| format!("Failed to fetch created inspiration: {}", e))?;
+    let events: Vec<CalendarEvent> = rows.into_iter().map(row_to_event).collect();
 
-    Ok(Inspiration {
-        id: row.get("id"),
-        content: row.get("content"),
-        is_archived: row.get::<i32, _>("is_archived") != 0,
-        created_at: row.get("created_at"),
-        updated_at: row.get("updated_at"),
-    })
+    Ok(events)
+}
+
+/// Event colors the UI can render, paired with their swatch hex value.
+const EVENT_COLOR_PALETTE: &[(&str, &str)] = &[
+    ("blue", "#3b82f6"),
+    ("orange", "#f97316"),
+    ("green", "#22c55e"),
+    ("teal", "#14b8a6"),
+    ("red", "#ef4444"),
+];
+
+#[derive(Debug, Serialize)]
+pub struct EventColor {
+    pub name: String,
+    pub hex: String,
+}
+
+#[command]
+pub async fn get_event_color_palette() -> Result<Vec<EventColor>, String> {
+    Ok(EVENT_COLOR_PALETTE
+        .iter()
+        .map(|(name, hex)| EventColor {
+            name: name.to_string(),
+            hex: hex.to_string(),
+        })
+        .collect())
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventColorUsage {
+    pub color: String,
+    pub count: i64,
+    /// False for colors stored before palette validation, which need cleaning up.
+    pub in_palette: bool,
+}
+
+/// Event counts per stored color, most used first, for the calendar legend.
+#[command]
+pub async fn get_event_color_usage() -> Result<Vec<EventColorUsage>, String> {
+    let pool = get_db_pool()?;
+    let rows = sqlx::query(
+        "SELECT color, COUNT(*) AS count FROM events GROUP BY color ORDER BY count DESC, color",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to count event colors: {}", e))?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            let color: String = row.get("color");
+            let in_palette = EVENT_COLOR_PALETTE.iter().any(|(name, _)| *name == color);
+            EventColorUsage {
+                color,
+                count: row.get("count"),
+                in_palette,
+            }
+        })
+        .collect())
+}
+
+/// Maps a color name onto the palette, falling back to blue for anything else
+/// (e.g. `navy` or `#123` from the agent) along with a warning for the caller.
+fn normalize_event_color(color: &str) -> (&'static str, Option<String>) {
+    let trimmed = color.trim().to_lowercase();
+    match EVENT_COLOR_PALETTE
+        .iter()
+        .find(|(name, _)| *name == trimmed.as_str())
+    {
+        Some((name, _)) => (name, None),
+        None => (
+            "blue",
+            Some(format!("未知颜色 {}，已改用 blue", color.trim())),
+        ),
+    }
+}
+
+/// Appends a color fallback warning to an agent action's result message.
+fn with_color_warning(message: &str, warning: Option<String>) -> String {
+    match warning {
+        Some(warning) => format!("{}（{}）", message, warning),
+        None => message.to_string(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct CreateEventRequest {
+    pub title: String,
+    pub date: String,
+    #[serde(default)]
+    pub color: Option<String>,
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+#[command]
+pub async fn create_event(request: CreateEventRequest) -> Result<CalendarEvent, String> {
+    let pool = get_db_pool()?;
+    let id = new_entity_id();
+    let (color, color_warning) = request
+        .color
+        .as_deref()
+        .map(normalize_event_color)
+        .unwrap_or(("blue", None));
+    let date = normalize_event_date(&request.date)?;
+
+    sqlx::query("INSERT INTO events (id, title, date, color, note) VALUES (?1, ?2, ?3, ?4, ?5)")
+        .bind(&id)
+        .bind(&request.title)
+        .bind(&date)
+        .bind(color)
+        .bind(&request.note)
+        .execute(pool)
+        .await
+        .map_err(|e| format!("Failed to create event: {}", e))?;
+
+    let row = sqlx::query("SELECT id, title, date, color, note, pinned FROM events WHERE id = ?1")
+        .bind(&id)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| format!("Failed to fetch created event: {}", e))?;
+
+    Ok(CalendarEvent {
+        warning: color_warning,
+        ..row_to_event(row)
+    })
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportEventsTextResponse {
+    pub created: usize,
+    pub ids: Vec<String>,
+    /// Non-blank lines that did not start with a valid date, as pasted.
+    pub skipped: Vec<String>,
+    /// Set when `default_color` was not in the palette and blue was used.
+    pub warning: Option<String>,
+}
+
+/// Creates one event per `YYYY-MM-DD [HH:MM] title` line of a pasted agenda, in a
+/// single transaction. Events have no time field, so a time stays at the front of
+/// the title.
+#[command]
+pub async fn import_events_text(
+    text: String,
+    default_color: Option<String>,
+) -> Result<ImportEventsTextResponse, String> {
+    let pool = get_db_pool()?;
+    let (color, color_warning) = default_color
+        .as_deref()
+        .filter(|value| !value.trim().is_empty())
+        .map(normalize_event_color)
+        .unwrap_or(("blue", None));
+    let mut entries = Vec::new();
+    let mut skipped = Vec::new();
+    for line in text.lines().filter(|line| !line.trim().is_empty()) {
+        match parse_event_line(line) {
+            Some(entry) => entries.push(entry),
+            None => skipped.push(line.trim().to_string()),
+        }
+    }
+    if entries.is_empty() {
+        return Err("没有可导入的日程".to_string());
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+    let mut ids = Vec::with_capacity(entries.len());
+    for (date, title) in &entries {
+        let id = new_entity_id();
+        sqlx::query("INSERT INTO events (id, title, date, color) VALUES (?1, ?2, ?3, ?4)")
+            .bind(&id)
+            .bind(title)
+            .bind(date)
+            .bind(color)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Failed to create event: {}", e))?;
+        ids.push(id);
+    }
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit transaction: {}", e))?;
+
+    Ok(ImportEventsTextResponse {
+        created: ids.len(),
+        ids,
+        skipped,
+        warning: color_warning,
+    })
+}
+
+/// Splits `2025-03-14 14:00 Standup` into its date and title (`14:00 Standup`).
+/// Returns `None` without a valid leading date or a title.
+fn parse_event_line(line: &str) -> Option<(String, String)> {
+    let line = line.trim().trim_start_matches(['-', '*']).trim_start();
+    let (date, rest) = line.split_once(char::is_whitespace)?;
+    let date = normalize_event_date(date).ok()?;
+    let rest = rest.trim();
+    let title = match rest.split_once(char::is_whitespace) {
+        Some((time, title)) => match chrono::NaiveTime::parse_from_str(time, "%H:%M") {
+            Ok(time) => format!("{} {}", time.format("%H:%M"), title.trim()),
+            Err(_) => rest.to_string(),
+        },
+        None => rest.to_string(),
+    };
+    if title.is_empty() || chrono::NaiveTime::parse_from_str(&title, "%H:%M").is_ok() {
+        return None;
+    }
+    Some((date, title))
+}
+
+#[derive(Deserialize)]
+pub struct UpdateEventRequest {
+    pub id: String,
+    pub title: Option<String>,
+    pub date: Option<String>,
+    pub color: Option<String>,
+    pub note: Option<String>,
+}
+
+#[command]
+pub async fn update_event(request: UpdateEventRequest) -> Result<CalendarEvent, String> {
+    let pool = get_db_pool()?;
+    let date = request
+        .date
+        .as_deref()
+        .map(normalize_event_date)
+        .transpose()?;
+
+    let mut updates = UpdateBuilder::new();
+    updates.set_opt("title", request.title.as_deref());
+    updates.set_opt("date", date);
+    let color = request.color.as_deref().map(normalize_event_color);
+    updates.set_opt("color", color.as_ref().map(|(color, _)| *color));
+    updates.set_opt("note", request.note.as_deref());
+
+    if updates.is_empty() {
+        return Err("No fields to update".to_string());
+    }
+
+    updates
+        .execute("events", &request.id, pool)
+        .await
+        .map_err(|e| format!("Failed to update event: {}", e))?;
+
+    let row = sqlx::query("SELECT id, title, date, color, note, pinned FROM events WHERE id = ?1")
+        .bind(&request.id)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| format!("Failed to fetch updated event: {}", e))?;
+
+    Ok(CalendarEvent {
+        warning: color.and_then(|(_, warning)| warning),
+        ..row_to_event(row)
+    })
+}
+
+#[command]
+pub async fn delete_event(id: String) -> Result<(), String> {
+    let pool = get_db_pool()?;
+    sqlx::query("DELETE FROM events WHERE id = ?1")
+        .bind(&id)
+        .execute(pool)
+        .await
+        .map_err(|e| format!("Failed to delete event: {}", e))?;
+    Ok(())
+}
+
+/// Canonicalizes `YYYY-M-D` style input (also `/` separated) to zero-padded `YYYY-MM-DD`.
+pub(crate) fn normalize_event_date(input: &str) -> Result<String, String> {
+    let invalid = || format!("无效的日期: {}（应为 YYYY-MM-DD）", input);
+    let parts: Vec<&str> = input.trim().split(['-', '/']).collect();
+    let [year, month, day] = parts.as_slice() else {
+        return Err(invalid());
+    };
+    let year = year.parse::<i32>().map_err(|_| invalid())?;
+    let month = month.parse::<u32>().map_err(|_| invalid())?;
+    let day = day.parse::<u32>().map_err(|_| invalid())?;
+    chrono::NaiveDate::from_ymd_opt(year, month, day)
+        .map(|date| date.format("%Y-%m-%d").to_string())
+        .ok_or_else(invalid)
+}
+
+/// Pins or unpins a todo, project or event; pinned items sort first in their list.
+#[command]
+pub async fn set_pinned(entity_type: String, id: String, pinned: bool) -> Result<(), String> {
+    let table = match entity_type.trim() {
+        "todo" => "todos",
+        "project" => "projects",
+        "event" => "events",
+        other => {
+            return Err(format!(
+                "Unsupported entity type: {} (expected todo, project or event)",
+                other
+            ))
+        }
+    };
+    let pool = get_db_pool()?;
+    let result = sqlx::query(&format!("UPDATE {} SET pinned = ?1 WHERE id = ?2", table))
+        .bind(if pinned { 1 } else { 0 })
+        .bind(&id)
+        .execute(pool)
+        .await
+        .map_err(|e| format!("Failed to update pinned flag: {}", e))?;
+    if result.rows_affected() == 0 {
+        return Err(format!("{} not found: {}", entity_type.trim(), id));
+    }
+    Ok(())
+}
+
+// ============= Personal Task Commands =============
+
+#[command]
+pub async fn get_personal_tasks(
+    include_completed: Option<bool>,
+) -> Result<Vec<PersonalTask>, String> {
+    let pool = get_db_pool()?;
+    let filter = if include_completed.unwrap_or(true) {
+        ""
+    } else {
+        "WHERE completed = 0 "
+    };
+    let query = format!(
+        "SELECT id, title, budget, date, location, note, completed, completed_at FROM personal_tasks {}ORDER BY date",
+        filter
+    );
+    let rows = sqlx::query(&query)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Failed to fetch personal tasks: {}", e))?;
+
+    let tasks: Vec<PersonalTask> = rows.into_iter().map(row_to_personal_task).collect();
+
+    Ok(tasks)
+}
+
+#[command]
+pub async fn get_personal_task(id: String) -> Result<Option<PersonalTask>, String> {
+    let pool = get_db_pool()?;
+    let row = sqlx::query(
+        "SELECT id, title, budget, date, location, note, completed, completed_at FROM personal_tasks WHERE id = ?1",
+    )
+    .bind(&id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch personal task: {}", e))?;
+    Ok(row.map(row_to_personal_task))
+}
+
+#[derive(Deserialize)]
+pub struct CreatePersonalTaskRequest {
+    pub title: String,
+    #[serde(default)]
+    pub budget: Option<f64>,
+    #[serde(default)]
+    pub date: Option<String>,
+    #[serde(default)]
+    pub location: Option<String>,
+    #[serde(default)]
+    pub note: Option<String>,
+    #[serde(default)]
+    pub completed: bool,
+}
+
+#[command]
+pub async fn create_personal_task(
+    request: CreatePersonalTaskRequest,
+) -> Result<PersonalTask, String> {
+    let pool = get_db_pool()?;
+    let id = new_entity_id();
+
+    sqlx::query(
+        "INSERT INTO personal_tasks (id, title, budget, date, location, note, completed, completed_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, CASE WHEN ?7 = 1 THEN CURRENT_TIMESTAMP END)"
+    )
+    .bind(&id)
+    .bind(&request.title)
+    .bind(request.budget)
+    .bind(&request.date)
+    .bind(&request.location)
+    .bind(&request.note)
+    .bind(if request.completed { 1 } else { 0 })
+    .execute(pool)
+    .await
+    .map_err(|e| format!("Failed to create personal task: {}", e))?;
+
+    let row = sqlx::query(
+        "SELECT id, title, budget, date, location, note, completed, completed_at FROM personal_tasks WHERE id = ?1",
+    )
+    .bind(&id)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch created personal task: {}", e))?;
+
+    Ok(row_to_personal_task(row))
+}
+
+#[derive(Deserialize)]
+pub struct UpdatePersonalTaskRequest {
+    pub id: String,
+    pub title: Option<String>,
+    pub budget: Option<f64>,
+    pub date: Option<String>,
+    pub location: Option<String>,
+    pub note: Option<String>,
+    pub completed: Option<bool>,
+}
+
+#[command]
+pub async fn update_personal_task(
+    request: UpdatePersonalTaskRequest,
+) -> Result<PersonalTask, String> {
+    let pool = get_db_pool()?;
+
+    let mut updates = UpdateBuilder::new();
+    updates.set_opt("title", request.title.as_deref());
+    updates.set_opt("budget", request.budget);
+    updates.set_opt("date", request.date.as_deref());
+    updates.set_opt("location", request.location.as_deref());
+    updates.set_opt("note", request.note.as_deref());
+    updates.set_completed(request.completed);
+
+    if updates.is_empty() {
+        return Err("No fields to update".to_string());
+    }
+
+    updates
+        .execute("personal_tasks", &request.id, pool)
+        .await
+        .map_err(|e| format!("Failed to update personal task: {}", e))?;
+
+    let row = sqlx::query(
+        "SELECT id, title, budget, date, location, note, completed, completed_at FROM personal_tasks WHERE id = ?1",
+    )
+    .bind(&request.id)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch updated personal task: {}", e))?;
+
+    Ok(row_to_personal_task(row))
+}
+
+fn row_to_personal_task(row: sqlx::sqlite::SqliteRow) -> PersonalTask {
+    PersonalTask {
+        id: row.get("id"),
+        title: row.get("title"),
+        budget: row.get("budget"),
+        date: row.get("date"),
+        location: row.get("location"),
+        note: row.get("note"),
+        completed: row.get::<i32, _>("completed") != 0,
+        completed_at: row.get("completed_at"),
+    }
+}
+
+#[command]
+pub async fn delete_personal_task(id: String) -> Result<(), String> {
+    let pool = get_db_pool()?;
+    sqlx::query("DELETE FROM personal_tasks WHERE id = ?1")
+        .bind(&id)
+        .execute(pool)
+        .await
+        .map_err(|e| format!("Failed to delete personal task: {}", e))?;
+    Ok(())
+}
+
+/// Copies a dated personal task into a new calendar event, optionally marking the
+/// task completed. Runs on the caller's connection so it can join a transaction.
+async fn convert_personal_task_to_event(
+    conn: &mut sqlx::SqliteConnection,
+    id: &str,
+    mark_completed: bool,
+) -> Result<CalendarEvent, String> {
+    let task = sqlx::query("SELECT title, date, note FROM personal_tasks WHERE id = ?1")
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| format!("Failed to fetch personal task: {}", e))?
+        .ok_or_else(|| format!("个人事务不存在: {}", id))?;
+    let title: String = task.get("title");
+    let note: Option<String> = task.get("note");
+    let date = task
+        .get::<Option<String>, _>("date")
+        .filter(|value| !value.trim().is_empty())
+        .ok_or_else(|| format!("个人事务「{}」没有日期，无法转换为日程", title))?;
+    let date = normalize_event_date(&date)?;
+
+    let event_id = new_entity_id();
+    sqlx::query("INSERT INTO events (id, title, date, color, note) VALUES (?1, ?2, ?3, ?4, ?5)")
+        .bind(&event_id)
+        .bind(&title)
+        .bind(&date)
+        .bind("blue")
+        .bind(&note)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| format!("Failed to create event: {}", e))?;
+
+    if mark_completed {
+        let mut updates = UpdateBuilder::new();
+        updates.set_completed(Some(true));
+        updates
+            .execute("personal_tasks", id, &mut *conn)
+            .await
+            .map_err(|e| format!("Failed to update personal task: {}", e))?;
+    }
+
+    let row = sqlx::query("SELECT id, title, date, color, note, pinned FROM events WHERE id = ?1")
+        .bind(&event_id)
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|e| format!("Failed to fetch created event: {}", e))?;
+    Ok(row_to_event(row))
+}
+
+#[command]
+pub async fn personal_task_to_event(
+    id: String,
+    mark_completed: Option<bool>,
+) -> Result<CalendarEvent, String> {
+    let pool = get_db_pool()?;
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+    let event =
+        convert_personal_task_to_event(&mut tx, &id, mark_completed.unwrap_or(false)).await?;
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit transaction: {}", e))?;
+    Ok(event)
+}
+
+// ============= Digest Commands =============
+
+/// Everything scheduled in one `YYYY-MM` month: events, project deadlines,
+/// personal tasks and their summed budget.
+#[command]
+pub async fn get_month_digest(year_month: String) -> Result<MonthDigest, String> {
+    let (start_date, end_date) = month_date_range(&year_month)?;
+    let events = fetch_events_in_range(&start_date, &end_date).await?;
+    let projects = fetch_projects_due_in_range(&start_date, &end_date).await?;
+    let personal_tasks = fetch_personal_tasks_in_range(&start_date, &end_date).await?;
+    let total_budget = personal_tasks
+        .iter()
+        .filter_map(|task| task.budget)
+        .sum::<f64>();
+
+    Ok(MonthDigest {
+        year_month: year_month.trim().to_string(),
+        start_date,
+        end_date,
+        events,
+        projects,
+        personal_tasks,
+        total_budget,
+    })
+}
+
+/// First and last day (inclusive, `YYYY-MM-DD`) of a strict `YYYY-MM` month.
+fn month_date_range(year_month: &str) -> Result<(String, String), String> {
+    let trimmed = year_month.trim();
+    let start = chrono::NaiveDate::parse_from_str(&format!("{}-01", trimmed), "%Y-%m-%d")
+        .ok()
+        .filter(|date| date.format("%Y-%m").to_string() == trimmed)
+        .ok_or_else(|| format!("无效的月份: {}（应为 YYYY-MM）", year_month))?;
+    let end = start
+        .checked_add_months(chrono::Months::new(1))
+        .and_then(|next| next.pred_opt())
+        .ok_or_else(|| format!("无效的月份: {}", year_month))?;
+    Ok((
+        start.format("%Y-%m-%d").to_string(),
+        end.format("%Y-%m-%d").to_string(),
+    ))
+}
+
+/// Events whose `date` falls within `start..=end` (both `YYYY-MM-DD`).
+async fn fetch_events_in_range(start: &str, end: &str) -> Result<Vec<CalendarEvent>, String> {
+    let pool = get_db_pool()?;
+    let rows = sqlx::query(
+        "SELECT id, title, date, color, note, pinned FROM events WHERE date BETWEEN ?1 AND ?2 ORDER BY date",
+    )
+    .bind(start)
+    .bind(end)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch events: {}", e))?;
+
+    Ok(rows.into_iter().map(row_to_event).collect())
+}
+
+/// Projects whose `deadline` falls within `start..=end`.
+async fn fetch_projects_due_in_range(start: &str, end: &str) -> Result<Vec<Project>, String> {
+    let pool = get_db_pool()?;
+    let rows = sqlx::query(
+        "SELECT id, title, deadline, progress, status, pinned FROM projects WHERE deadline BETWEEN ?1 AND ?2 ORDER BY deadline",
+    )
+    .bind(start)
+    .bind(end)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch projects: {}", e))?;
+
+    Ok(rows.into_iter().map(row_to_project).collect())
+}
+
+/// Personal tasks whose `date` falls within `start..=end`.
+async fn fetch_personal_tasks_in_range(
+    start: &str,
+    end: &str,
+) -> Result<Vec<PersonalTask>, String> {
+    let pool = get_db_pool()?;
+    let rows = sqlx::query(
+        "SELECT id, title, budget, date, location, note, completed, completed_at FROM personal_tasks WHERE date BETWEEN ?1 AND ?2 ORDER BY date",
+    )
+    .bind(start)
+    .bind(end)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch personal tasks: {}", e))?;
+
+    Ok(rows.into_iter().map(row_to_personal_task).collect())
+}
+
+/// Headline counts for the stats dashboard, gathered in a single query.
+#[command]
+pub async fn get_workbench_stats() -> Result<WorkbenchStats, String> {
+    use chrono::Datelike;
+
+    let pool = get_db_pool()?;
+    let today_string = local_today_string();
+    let today = chrono::NaiveDate::parse_from_str(&today_string, "%Y-%m-%d")
+        .map_err(|e| format!("Failed to parse today: {}", e))?;
+    let week_start = today - chrono::Duration::days(today.weekday().num_days_from_monday().into());
+    let week_end = week_start + chrono::Duration::days(6);
+    let week_start = week_start.format("%Y-%m-%d").to_string();
+    let week_end = week_end.format("%Y-%m-%d").to_string();
+
+    let row = sqlx::query(
+        "SELECT
+            (SELECT COUNT(*) FROM todos) AS todos_total,
+            (SELECT COUNT(*) FROM todos WHERE completed = 1) AS todos_completed,
+            (SELECT COUNT(*) FROM projects WHERE status = 'active') AS projects_active,
+            (SELECT COUNT(*) FROM projects WHERE status = 'completed') AS projects_completed,
+            (SELECT COUNT(*) FROM events WHERE date BETWEEN ?1 AND ?2) AS events_this_week,
+            (SELECT COUNT(*) FROM personal_tasks) AS personal_tasks_total,
+            (SELECT COUNT(*) FROM personal_tasks WHERE completed = 1) AS personal_tasks_completed,
+            (SELECT TOTAL(budget) FROM personal_tasks) AS personal_budget_total,
+            (SELECT TOTAL(budget) FROM personal_tasks WHERE completed = 0) AS personal_budget_pending,
+            (SELECT COUNT(*) FROM inspirations) AS inspirations_total,
+            (SELECT COUNT(*) FROM info_items_daily WHERE date = ?3) AS info_items_today",
+    )
+    .bind(&week_start)
+    .bind(&week_end)
+    .bind(&today_string)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| format!("Failed to query workbench stats: {}", e))?;
+
+    Ok(WorkbenchStats {
+        todos_total: row.get("todos_total"),
+        todos_completed: row.get("todos_completed"),
+        projects_active: row.get("projects_active"),
+        projects_completed: row.get("projects_completed"),
+        week_start,
+        week_end,
+        events_this_week: row.get("events_this_week"),
+        personal_tasks_total: row.get("personal_tasks_total"),
+        personal_tasks_completed: row.get("personal_tasks_completed"),
+        personal_budget_total: row.get("personal_budget_total"),
+        personal_budget_pending: row.get("personal_budget_pending"),
+        inspirations_total: row.get("inspirations_total"),
+        info_items_today: row.get("info_items_today"),
+    })
+}
+
+const MAX_UPCOMING_DAYS: i32 = 90;
+
+/// Everything dated in `[today, today + days]`, oldest first. Only events carry a
+/// date today; todos will join the list once they have a due date.
+#[command]
+pub async fn get_upcoming(days: i32) -> Result<Vec<UpcomingItem>, String> {
+    if !(1..=MAX_UPCOMING_DAYS).contains(&days) {
+        return Err(format!("天数应在 1 到 {} 之间", MAX_UPCOMING_DAYS));
+    }
+    let today = app_now().date_naive();
+    let end = today + chrono::Duration::days(days.into());
+    let events = fetch_events_in_range(
+        &today.format("%Y-%m-%d").to_string(),
+        &end.format("%Y-%m-%d").to_string(),
+    )
+    .await?;
+
+    let mut items: Vec<UpcomingItem> = events
+        .into_iter()
+        .map(|event| UpcomingItem {
+            kind: "event".to_string(),
+            id: event.id,
+            title: event.title,
+            date: event.date,
+            color: Some(event.color),
+        })
+        .collect();
+    items.sort_by(|a, b| a.date.cmp(&b.date).then_with(|| a.title.cmp(&b.title)));
+    Ok(items)
+}
+
+// ============= Inspiration Commands =============
+
+const INSPIRATION_STATUSES: [&str; 3] = ["draft", "published", "archived"];
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateInspirationRequest {
+    pub content: String,
+    /// `draft` or `published` (the default).
+    #[serde(default)]
+    pub status: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToggleInspirationArchivedRequest {
+    pub id: String,
+    pub is_archived: bool,
+}
+
+/// With `status`, only inspirations in that state; otherwise everything, minus
+/// archived ones when `include_archived` is false.
+#[command]
+pub async fn get_inspirations(
+    include_archived: Option<bool>,
+    status: Option<String>,
+) -> Result<Vec<Inspiration>, String> {
+    let pool = get_db_pool()?;
+    let status = status
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(normalize_inspiration_status)
+        .transpose()?;
+    let show_archived = include_archived.unwrap_or(true);
+
+    let rows = match status {
+        Some(status) => sqlx::query(
+            "SELECT id, content, status, created_at, updated_at
+             FROM inspirations
+             WHERE status = ?1
+             ORDER BY created_at DESC",
+        )
+        .bind(status)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Failed to fetch inspirations: {}", e))?,
+        None if show_archived => sqlx::query(
+            "SELECT id, content, status, created_at, updated_at
+             FROM inspirations
+             ORDER BY created_at DESC",
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Failed to fetch inspirations: {}", e))?,
+        None => sqlx::query(
+            "SELECT id, content, status, created_at, updated_at
+             FROM inspirations
+             WHERE status <> 'archived'
+             ORDER BY created_at DESC",
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Failed to fetch inspirations: {}", e))?,
+    };
+
+    Ok(rows.into_iter().map(row_to_inspiration).collect())
 }
 
 #[command]
-pub async fn toggle_inspiration_archived(
-    request: ToggleInspirationArchivedRequest,
-) -> Result<Inspiration, String> {
+pub async fn create_inspiration(request: CreateInspirationRequest) -> Result<Inspiration, String> {
     let pool = get_db_pool()?;
+    let content = request.content.trim();
+    if content.is_empty() {
+        return Err("Inspiration content cannot be empty".to_string());
+    }
+    let status = match request.status.as_deref().map(str::trim) {
+        None | Some("") => "published",
+        Some(value) => match normalize_inspiration_status(value)? {
+            "archived" => return Err("新建灵感不能直接归档".to_string()),
+            status => status,
+        },
+    };
+    let id = new_entity_id();
 
     sqlx::query(
+        "INSERT INTO inspirations (id, content, is_archived, status, created_at, updated_at)
+         VALUES (?1, ?2, 0, ?3, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)",
+    )
+    .bind(&id)
+    .bind(content)
+    .bind(status)
+    .execute(pool)
+    .await
+    .map_err(|e| format!("Failed to create inspiration: {}", e))?;
+
+    let row = sqlx::query(
+        "SELECT id, content, status, created_at, updated_at
+         FROM inspirations
+         WHERE id = ?1",
+    )
+    .bind(&id)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch created inspiration: {}", e))?;
+
+    Ok(row_to_inspiration(row))
+}
+
+/// Moves an inspiration between `draft`, `published` and `archived`.
+#[command]
+pub async fn set_inspiration_status(id: String, status: String) -> Result<Inspiration, String> {
+    let pool = get_db_pool()?;
+    let status = normalize_inspiration_status(&status)?;
+
+    // is_archived is still written so older readers and backups stay consistent
+    let result = sqlx::query(
         "UPDATE inspirations
-         SET is_archived = ?1, updated_at = CURRENT_TIMESTAMP
-         WHERE id = ?2",
+         SET status = ?1, is_archived = ?2, updated_at = CURRENT_TIMESTAMP
+         WHERE id = ?3",
     )
-    .bind(if request.is_archived { 1 } else { 0 })
-    .bind(&request.id)
+    .bind(status)
+    .bind(if status == "archived" { 1 } else { 0 })
+    .bind(&id)
     .execute(pool)
     .await
     .map_err(|e| format!("Failed to update inspiration status: {}", e))?;
+    if result.rows_affected() == 0 {
+        return Err(format!("Inspiration not found: {}", id));
+    }
 
     let row = sqlx::query(
-        "SELECT id, content, is_archived, created_at, updated_at
+        "SELECT id, content, status, created_at, updated_at
          FROM inspirations
          WHERE id = ?1",
     )
-    .bind(&request.id)
+    .bind(&id)
     .fetch_one(pool)
     .await
     .map_err(|e| format!("Failed to fetch updated inspiration: {}", e))?;
 
-    Ok(Inspiration {
+    Ok(row_to_inspiration(row))
+}
+
+/// Compatibility wrapper over `set_inspiration_status`: archiving sets `archived`,
+/// unarchiving sets `published`.
+#[command]
+pub async fn toggle_inspiration_archived(
+    request: ToggleInspirationArchivedRequest,
+) -> Result<Inspiration, String> {
+    let status = if request.is_archived {
+        "archived"
+    } else {
+        "published"
+    };
+    set_inspiration_status(request.id, status.to_string()).await
+}
+
+fn normalize_inspiration_status(status: &str) -> Result<&'static str, String> {
+    let normalized = status.trim().to_lowercase();
+    INSPIRATION_STATUSES
+        .into_iter()
+        .find(|candidate| *candidate == normalized)
+        .ok_or_else(|| {
+            format!(
+                "无效的灵感状态: {}（可选: {}）",
+                status,
+                INSPIRATION_STATUSES.join(", ")
+            )
+        })
+}
+
+fn row_to_inspiration(row: sqlx::sqlite::SqliteRow) -> Inspiration {
+    let status: String = row.get("status");
+    Inspiration {
         id: row.get("id"),
         content: row.get("content"),
-        is_archived: row.get::<i32, _>("is_archived") != 0,
+        is_archived: status == "archived",
+        status,
         created_at: row.get("created_at"),
         updated_at: row.get("updated_at"),
-    })
+    }
 }
 
 #[command]
@@ -1282,131 +3446,450 @@ pub async fn delete_inspiration(id: String) -> Result<(), String> {
         .bind(&id)
         .execute(pool)
         .await
-        .map_err(|e| format!("Failed to delete inspiration: {}", e))?;
+        .map_err(|e| format!("Failed to delete inspiration: {}", e))?;
+    Ok(())
+}
+
+// ============= Daily Info Center Commands =============
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpsertInfoSourceRequest {
+    pub id: Option<String>,
+    pub name: String,
+    pub url: String,
+    #[serde(default = "default_info_source_type")]
+    pub r#type: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub is_preset: bool,
+    /// Omitted keeps the stored auth settings.
+    #[serde(default)]
+    pub auth_type: Option<String>,
+    /// Omitted keeps the stored credential; ignored when the auth type is `none`.
+    #[serde(default)]
+    pub auth_value: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateInfoSettingsRequest {
+    pub push_time: String,
+    #[serde(default)]
+    pub include_keywords: Vec<String>,
+    #[serde(default)]
+    pub exclude_keywords: Vec<String>,
+    pub max_items_per_day: i32,
+    /// Omitted keeps the stored value.
+    #[serde(default)]
+    pub max_concurrent_fetches: Option<i32>,
+    /// Omitted keeps the stored value.
+    #[serde(default)]
+    pub retention_days: Option<i32>,
+    /// Omitted keeps the stored window; empty strings clear it.
+    #[serde(default)]
+    pub quiet_hours_start: Option<String>,
+    #[serde(default)]
+    pub quiet_hours_end: Option<String>,
+}
+
+#[command]
+pub async fn get_info_sources() -> Result<Vec<InfoSource>, String> {
+    let pool = get_db_pool()?;
+    let rows = sqlx::query(
+        "SELECT id, name, type, url, enabled, is_preset, created_at, updated_at,
+         last_fetch_status, last_fetch_error, last_fetched_at, auth_type, auth_value
+         FROM info_sources
+         ORDER BY is_preset DESC, created_at DESC",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch info sources: {}", e))?;
+
+    Ok(rows.into_iter().map(row_to_info_source).collect())
+}
+
+/// Fetches `url` and sniffs whether it serves RSS, Atom or JSON Feed, so a source
+/// saved with the wrong `type` can be corrected.
+#[command]
+pub async fn detect_info_source_type(url: String) -> Result<String, String> {
+    let response = guarded_get(&url).await?;
+    if !response.status().is_success() {
+        return Err(format!("HTTP {}", response.status()));
+    }
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_ascii_lowercase)
+        .unwrap_or_default();
+    let bytes = read_body_with_limit(response, max_feed_bytes()).await?;
+    // Plenty of feeds are served as text/html, so only a body that fails to parse
+    // and sniffs as a page counts as HTML
+    if let Ok(feed) = feed_rs::parser::parse(bytes.as_slice()) {
+        let kind = match feed.feed_type {
+            feed_rs::model::FeedType::Atom => "atom",
+            feed_rs::model::FeedType::JSON => "json",
+            _ => "rss",
+        };
+        return Ok(kind.to_string());
+    }
+    if looks_like_html(&bytes) {
+        return Err("URL returned HTML, not a feed".to_string());
+    }
+
+    let head = String::from_utf8_lossy(&bytes[..bytes.len().min(2048)]).to_ascii_lowercase();
+    let head = head.trim_start_matches('\u{feff}').trim_start();
+    if head.starts_with('{') || content_type.contains("json") {
+        if head.contains("jsonfeed.org/version") {
+            return Ok("json".to_string());
+        }
+        return Err("返回的是 JSON，但不是 JSON Feed".to_string());
+    }
+    if head.starts_with('<') {
+        // The root element may follow an `<?xml ?>` prolog, comments or stylesheet hints
+        if head.contains("<rss") || head.contains("<rdf:rdf") {
+            return Ok("rss".to_string());
+        }
+        if head.contains("<feed") {
+            return Ok("atom".to_string());
+        }
+    }
+    Err("无法识别的订阅格式".to_string())
+}
+
+fn row_to_info_source(row: sqlx::sqlite::SqliteRow) -> InfoSource {
+    InfoSource {
+        id: row.get("id"),
+        name: row.get("name"),
+        r#type: row.get("type"),
+        url: row.get("url"),
+        enabled: row.get::<i32, _>("enabled") != 0,
+        is_preset: row.get::<i32, _>("is_preset") != 0,
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+        last_fetch_status: row.get("last_fetch_status"),
+        last_fetch_error: row.get("last_fetch_error"),
+        last_fetched_at: row.get("last_fetched_at"),
+        auth_type: row.get("auth_type"),
+        auth_value: row.get("auth_value"),
+    }
+}
+
+#[command]
+pub async fn upsert_info_source(request: UpsertInfoSourceRequest) -> Result<InfoSource, String> {
+    let pool = get_db_pool()?;
+    let source_id = request
+        .id
+        .clone()
+        .unwrap_or_else(|| format!("source-{}", new_entity_id()));
+    let source_type = if request.r#type.trim().is_empty() {
+        "rss".to_string()
+    } else {
+        request.r#type.trim().to_lowercase()
+    };
+    let auth_type = match request
+        .auth_type
+        .as_deref()
+        .map(|value| value.trim().to_lowercase())
+    {
+        None => None,
+        Some(value) => match value.as_str() {
+            "" | "none" => Some("none"),
+            "basic" => Some("basic"),
+            "bearer" => Some("bearer"),
+            other => return Err(format!("不支持的认证方式: {}", other)),
+        },
+    };
+    let auth_value = request
+        .auth_value
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty());
+    if auth_type == Some("basic") && auth_value.is_some_and(|value| !value.contains(':')) {
+        return Err("Basic 认证格式应为 用户名:密码".to_string());
+    }
+
+    sqlx::query(
+        "INSERT INTO info_sources (id, name, type, url, enabled, is_preset, auth_type, auth_value, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, COALESCE(?7, 'none'),
+                 CASE WHEN COALESCE(?7, 'none') = 'none' THEN NULL ELSE ?8 END, CURRENT_TIMESTAMP)
+         ON CONFLICT(id) DO UPDATE SET
+            name = excluded.name,
+            type = excluded.type,
+            url = excluded.url,
+            enabled = excluded.enabled,
+            icon_url = CASE WHEN excluded.url = info_sources.url THEN info_sources.icon_url ELSE NULL END,
+            etag = CASE WHEN excluded.url = info_sources.url THEN info_sources.etag ELSE NULL END,
+            last_modified = CASE WHEN excluded.url = info_sources.url THEN info_sources.last_modified ELSE NULL END,
+            auth_type = COALESCE(?7, info_sources.auth_type),
+            auth_value = CASE WHEN COALESCE(?7, info_sources.auth_type) = 'none' THEN NULL
+                ELSE COALESCE(?8, info_sources.auth_value) END,
+            updated_at = CURRENT_TIMESTAMP",
+    )
+    .bind(&source_id)
+    .bind(request.name.trim())
+    .bind(&source_type)
+    .bind(request.url.trim())
+    .bind(if request.enabled { 1 } else { 0 })
+    .bind(if request.is_preset { 1 } else { 0 })
+    .bind(auth_type)
+    .bind(auth_value)
+    .execute(pool)
+    .await
+    .map_err(|e| format!("Failed to upsert info source: {}", e))?;
+
+    let row = sqlx::query(
+        "SELECT id, name, type, url, enabled, is_preset, created_at, updated_at,
+         last_fetch_status, last_fetch_error, last_fetched_at, auth_type, auth_value
+         FROM info_sources WHERE id = ?1",
+    )
+    .bind(&source_id)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch updated source: {}", e))?;
+
+    Ok(row_to_info_source(row))
+}
+
+#[command]
+pub async fn delete_info_source(id: String) -> Result<(), String> {
+    let pool = get_db_pool()?;
+    sqlx::query("DELETE FROM info_sources WHERE id = ?1")
+        .bind(&id)
+        .execute(pool)
+        .await
+        .map_err(|e| format!("Failed to delete info source: {}", e))?;
+    sqlx::query("DELETE FROM info_link_sightings WHERE source_id = ?1")
+        .bind(&id)
+        .execute(pool)
+        .await
+        .map_err(|e| format!("Failed to delete link sightings: {}", e))?;
+    Ok(())
+}
+
+/// Folds a duplicate source into another: its collected items are repointed to
+/// `keep_id` and the kept source stays enabled if either one was.
+#[command]
+pub async fn merge_info_sources(keep_id: String, remove_id: String) -> Result<InfoSource, String> {
+    if keep_id == remove_id {
+        return Err("不能将信息源与自身合并".to_string());
+    }
+    let _guard = try_begin_info_refresh("merge")
+        .map_err(|running| format!("已有刷新正在进行（{}），请稍后再试", running))?;
+    let pool = get_db_pool()?;
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+    merge_info_source_into(&mut tx, &keep_id, &remove_id).await?;
+
+    let row = sqlx::query(
+        "SELECT id, name, type, url, enabled, is_preset, created_at, updated_at,
+         last_fetch_status, last_fetch_error, last_fetched_at, auth_type, auth_value
+         FROM info_sources WHERE id = ?1",
+    )
+    .bind(&keep_id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to fetch merged source: {}", e))?;
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit transaction: {}", e))?;
+
+    Ok(row_to_info_source(row))
+}
+
+/// Merges sources whose URLs differ only in scheme, `www.`, host case, default
+/// port or a trailing slash, keeping the enabled (then preset, then oldest) one of
+/// each group. Stored URLs are left as they are. Returns how many were merged away.
+#[command]
+pub async fn dedupe_info_sources() -> Result<usize, String> {
+    let _guard = try_begin_info_refresh("merge")
+        .map_err(|running| format!("已有刷新正在进行（{}），请稍后再试", running))?;
+    let pool = get_db_pool()?;
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+    let rows = sqlx::query(
+        "SELECT id, url FROM info_sources
+         ORDER BY enabled DESC, is_preset DESC, created_at ASC, id ASC",
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to query info sources: {}", e))?;
+
+    // Rows come in keeper order, so the first source seen for a URL wins
+    let mut keepers: HashMap<String, String> = HashMap::new();
+    let mut merged = 0;
+    for row in rows {
+        let id: String = row.get("id");
+        let url: String = row.get("url");
+        let key = canonical_source_url(&url);
+        match keepers.get(&key) {
+            Some(keep_id) => {
+                merge_info_source_into(&mut tx, keep_id, &id).await?;
+                merged += 1;
+            }
+            None => {
+                keepers.insert(key, id);
+            }
+        }
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit transaction: {}", e))?;
+    Ok(merged)
+}
+
+/// Repoints `remove_id`'s items and sightings to `keep_id`, keeps the survivor
+/// enabled if either was, and deletes `remove_id`.
+async fn merge_info_source_into(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    keep_id: &str,
+    remove_id: &str,
+) -> Result<(), String> {
+    let removed_enabled: Option<i32> =
+        sqlx::query_scalar("SELECT enabled FROM info_sources WHERE id = ?1")
+            .bind(remove_id)
+            .fetch_optional(&mut **tx)
+            .await
+            .map_err(|e| format!("Failed to query info source: {}", e))?;
+    let removed_enabled = removed_enabled.ok_or_else(|| format!("信息源不存在: {}", remove_id))?;
+
+    let kept = sqlx::query(
+        "UPDATE info_sources
+         SET enabled = MAX(enabled, ?2), updated_at = CURRENT_TIMESTAMP
+         WHERE id = ?1",
+    )
+    .bind(keep_id)
+    .bind(removed_enabled)
+    .execute(&mut **tx)
+    .await
+    .map_err(|e| format!("Failed to update info source: {}", e))?;
+    if kept.rows_affected() == 0 {
+        return Err(format!("信息源不存在: {}", keep_id));
+    }
+
+    sqlx::query("UPDATE info_items_daily SET source_id = ?1 WHERE source_id = ?2")
+        .bind(keep_id)
+        .bind(remove_id)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| format!("Failed to repoint info items: {}", e))?;
+    sqlx::query(
+        "INSERT OR IGNORE INTO info_link_sightings (link, source_id, date, title)
+         SELECT link, ?1, date, title FROM info_link_sightings WHERE source_id = ?2",
+    )
+    .bind(keep_id)
+    .bind(remove_id)
+    .execute(&mut **tx)
+    .await
+    .map_err(|e| format!("Failed to repoint link sightings: {}", e))?;
+    sqlx::query("DELETE FROM info_link_sightings WHERE source_id = ?1")
+        .bind(remove_id)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| format!("Failed to repoint link sightings: {}", e))?;
+    sqlx::query("DELETE FROM info_sources WHERE id = ?1")
+        .bind(remove_id)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| format!("Failed to delete info source: {}", e))?;
     Ok(())
 }
 
-// ============= Daily Info Center Commands =============
-
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct UpsertInfoSourceRequest {
-    pub id: Option<String>,
-    pub name: String,
-    pub url: String,
-    #[serde(default = "default_info_source_type")]
-    pub r#type: String,
-    #[serde(default = "default_true")]
-    pub enabled: bool,
-    #[serde(default)]
-    pub is_preset: bool,
+/// Comparison key for feed URLs: scheme, `www.` and the fragment are dropped, the
+/// host is lowercased (and default ports removed) by the URL parser, and a trailing
+/// slash is trimmed. Unparseable URLs are compared as trimmed text.
+fn canonical_source_url(url: &str) -> String {
+    let Ok(parsed) = reqwest::Url::parse(url.trim()) else {
+        return url.trim().to_string();
+    };
+    let host = parsed.host_str().unwrap_or_default();
+    let host = host.strip_prefix("www.").unwrap_or(host);
+    let mut key = host.to_string();
+    if let Some(port) = parsed.port() {
+        key.push_str(&format!(":{}", port));
+    }
+    key.push_str(parsed.path().trim_end_matches('/'));
+    if let Some(query) = parsed.query() {
+        key.push('?');
+        key.push_str(query);
+    }
+    key
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
-pub struct UpdateInfoSettingsRequest {
-    pub push_time: String,
-    #[serde(default)]
-    pub include_keywords: Vec<String>,
-    #[serde(default)]
-    pub exclude_keywords: Vec<String>,
-    pub max_items_per_day: i32,
+pub struct DuplicateInfoLink {
+    pub link: String,
+    pub title: String,
+    pub source_ids: Vec<String>,
 }
 
+/// Links surfaced by more than one source over the last `days` days (default 7),
+/// most widely echoed first. Points at sources that mostly republish each other.
 #[command]
-pub async fn get_info_sources() -> Result<Vec<InfoSource>, String> {
+pub async fn get_duplicate_info_links(days: Option<i32>) -> Result<Vec<DuplicateInfoLink>, String> {
     let pool = get_db_pool()?;
+    let days = days.unwrap_or(7).clamp(1, MAX_INFO_RETENTION_DAYS);
+    let since = (app_now().date_naive() - chrono::Duration::days((days - 1).into()))
+        .format("%Y-%m-%d")
+        .to_string();
     let rows = sqlx::query(
-        "SELECT id, name, type, url, enabled, is_preset, created_at, updated_at
-         FROM info_sources
-         ORDER BY is_preset DESC, created_at DESC",
+        "SELECT link, MAX(title) AS title, GROUP_CONCAT(DISTINCT source_id) AS source_ids
+         FROM info_link_sightings
+         WHERE date >= ?1
+         GROUP BY link
+         HAVING COUNT(DISTINCT source_id) > 1
+         ORDER BY COUNT(DISTINCT source_id) DESC, link",
     )
+    .bind(&since)
     .fetch_all(pool)
     .await
-    .map_err(|e| format!("Failed to fetch info sources: {}", e))?;
+    .map_err(|e| format!("Failed to query duplicate links: {}", e))?;
 
     Ok(rows
         .into_iter()
-        .map(|row| InfoSource {
-            id: row.get("id"),
-            name: row.get("name"),
-            r#type: row.get("type"),
-            url: row.get("url"),
-            enabled: row.get::<i32, _>("enabled") != 0,
-            is_preset: row.get::<i32, _>("is_preset") != 0,
-            created_at: row.get("created_at"),
-            updated_at: row.get("updated_at"),
+        .map(|row| DuplicateInfoLink {
+            link: row.get("link"),
+            title: row.get("title"),
+            source_ids: row
+                .get::<String, _>("source_ids")
+                .split(',')
+                .map(str::to_string)
+                .collect(),
         })
         .collect())
 }
 
 #[command]
-pub async fn upsert_info_source(request: UpsertInfoSourceRequest) -> Result<InfoSource, String> {
-    let pool = get_db_pool()?;
-    let source_id = request
-        .id
-        .clone()
-        .unwrap_or_else(|| format!("source-{}", chrono::Utc::now().timestamp_millis()));
-    let source_type = if request.r#type.trim().is_empty() {
-        "rss".to_string()
-    } else {
-        request.r#type.trim().to_lowercase()
-    };
+pub async fn get_info_settings() -> Result<InfoSettings, String> {
+    load_info_settings().await
+}
 
+/// Make sure the `'default'` settings row exists, re-inserting defaults if it was
+/// lost, and return the loaded settings.
+#[command]
+pub async fn ensure_info_settings() -> Result<InfoSettings, String> {
+    let pool = get_db_pool()?;
     sqlx::query(
-        "INSERT INTO info_sources (id, name, type, url, enabled, is_preset, updated_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, CURRENT_TIMESTAMP)
-         ON CONFLICT(id) DO UPDATE SET
-            name = excluded.name,
-            type = excluded.type,
-            url = excluded.url,
-            enabled = excluded.enabled,
-            updated_at = CURRENT_TIMESTAMP",
+        r#"
+        INSERT INTO info_settings (id, push_time, include_keywords_json, exclude_keywords_json, max_items_per_day)
+        VALUES ('default', '09:00', '[]', '[]', 20)
+        ON CONFLICT(id) DO NOTHING
+        "#,
     )
-    .bind(&source_id)
-    .bind(request.name.trim())
-    .bind(&source_type)
-    .bind(request.url.trim())
-    .bind(if request.enabled { 1 } else { 0 })
-    .bind(if request.is_preset { 1 } else { 0 })
     .execute(pool)
     .await
-    .map_err(|e| format!("Failed to upsert info source: {}", e))?;
-
-    let row = sqlx::query(
-        "SELECT id, name, type, url, enabled, is_preset, created_at, updated_at
-         FROM info_sources WHERE id = ?1",
-    )
-    .bind(&source_id)
-    .fetch_one(pool)
-    .await
-    .map_err(|e| format!("Failed to fetch updated source: {}", e))?;
-
-    Ok(InfoSource {
-        id: row.get("id"),
-        name: row.get("name"),
-        r#type: row.get("type"),
-        url: row.get("url"),
-        enabled: row.get::<i32, _>("enabled") != 0,
-        is_preset: row.get::<i32, _>("is_preset") != 0,
-        created_at: row.get("created_at"),
-        updated_at: row.get("updated_at"),
-    })
-}
-
-#[command]
-pub async fn delete_info_source(id: String) -> Result<(), String> {
-    let pool = get_db_pool()?;
-    sqlx::query("DELETE FROM info_sources WHERE id = ?1")
-        .bind(&id)
-        .execute(pool)
-        .await
-        .map_err(|e| format!("Failed to delete info source: {}", e))?;
-    Ok(())
-}
-
-#[command]
-pub async fn get_info_settings() -> Result<InfoSettings, String> {
+    .map_err(|e| format!("Failed to ensure info settings: {}", e))?;
     load_info_settings().await
 }
 
@@ -1415,6 +3898,24 @@ pub async fn update_info_settings(
     request: UpdateInfoSettingsRequest,
 ) -> Result<InfoSettings, String> {
     let pool = get_db_pool()?;
+    upsert_info_settings(pool, request).await?;
+    load_info_settings().await
+}
+
+async fn upsert_info_settings<'c, E>(
+    executor: E,
+    request: UpdateInfoSettingsRequest,
+) -> Result<(), String>
+where
+    E: sqlx::Executor<'c, Database = sqlx::Sqlite>,
+{
+    for keyword in request
+        .include_keywords
+        .iter()
+        .chain(&request.exclude_keywords)
+    {
+        validate_keyword_pattern(keyword)?;
+    }
     let include_keywords_json =
         serde_json::to_string(&normalize_keywords(request.include_keywords)).map_err(|e| {
             format!(
@@ -1430,53 +3931,616 @@ pub async fn update_info_settings(
             )
         })?;
     let max_items_per_day = request.max_items_per_day.clamp(1, 100);
+    let max_concurrent_fetches = request
+        .max_concurrent_fetches
+        .map(|value| value.clamp(1, 20));
+    let retention_days = request
+        .retention_days
+        .map(|value| value.clamp(1, MAX_INFO_RETENTION_DAYS));
     let push_time = normalize_push_time(&request.push_time);
+    let quiet_hours = normalize_quiet_hours(
+        request.quiet_hours_start.as_deref(),
+        request.quiet_hours_end.as_deref(),
+    )?;
+    let (quiet_start, quiet_end) = match &quiet_hours {
+        Some(Some((start, end))) => (Some(start.as_str()), Some(end.as_str())),
+        // Empty strings clear the stored window
+        Some(None) => (Some(""), Some("")),
+        None => (None, None),
+    };
 
     sqlx::query(
-        "INSERT INTO info_settings (id, push_time, include_keywords_json, exclude_keywords_json, max_items_per_day, updated_at)
-         VALUES ('default', ?1, ?2, ?3, ?4, CURRENT_TIMESTAMP)
+        "INSERT INTO info_settings (id, push_time, include_keywords_json, exclude_keywords_json, max_items_per_day, max_concurrent_fetches, retention_days, quiet_hours_start, quiet_hours_end, updated_at)
+         VALUES ('default', ?1, ?2, ?3, ?4, COALESCE(?5, ?6), COALESCE(?7, 1), NULLIF(?8, ''), NULLIF(?9, ''), CURRENT_TIMESTAMP)
          ON CONFLICT(id) DO UPDATE SET
             push_time = excluded.push_time,
             include_keywords_json = excluded.include_keywords_json,
             exclude_keywords_json = excluded.exclude_keywords_json,
             max_items_per_day = excluded.max_items_per_day,
+            max_concurrent_fetches = COALESCE(?5, info_settings.max_concurrent_fetches),
+            retention_days = COALESCE(?7, info_settings.retention_days),
+            quiet_hours_start = CASE WHEN ?8 IS NULL THEN info_settings.quiet_hours_start ELSE NULLIF(?8, '') END,
+            quiet_hours_end = CASE WHEN ?9 IS NULL THEN info_settings.quiet_hours_end ELSE NULLIF(?9, '') END,
             updated_at = CURRENT_TIMESTAMP",
     )
     .bind(&push_time)
     .bind(include_keywords_json)
     .bind(exclude_keywords_json)
     .bind(max_items_per_day)
-    .execute(pool)
+    .bind(max_concurrent_fetches)
+    .bind(DEFAULT_MAX_CONCURRENT_FETCHES)
+    .bind(retention_days)
+    .bind(quiet_start)
+    .bind(quiet_end)
+    .execute(executor)
     .await
     .map_err(|e| format!("Failed to update info settings: {}", e))?;
+    Ok(())
+}
+
+/// Overlays an `info.update_settings` payload on the current settings, so the agent
+/// only has to send the fields it changes.
+async fn info_settings_request_from_payload(
+    payload: &Value,
+) -> Result<UpdateInfoSettingsRequest, String> {
+    let Some(patch) = payload.as_object().filter(|map| !map.is_empty()) else {
+        return Err("info.update_settings 缺少可更新字段".to_string());
+    };
+    let mut merged = serde_json::to_value(load_info_settings().await?)
+        .map_err(|e| format!("Failed to serialize info settings: {}", e))?;
+    if let Some(map) = merged.as_object_mut() {
+        for (key, value) in patch {
+            map.insert(key.clone(), value.clone());
+        }
+    }
+    serde_json::from_value(merged).map_err(|e| format!("info.update_settings 参数无效: {}", e))
+}
+
+/// Items for `date` (defaults to today). Past days are only available within the
+/// configured retention window.
+#[command]
+pub async fn get_today_info_items(date: Option<String>) -> Result<Vec<InfoItem>, String> {
+    fetch_info_items_for_date(&info_date_or_today(date.as_deref())?).await
+}
+
+/// Same items as `get_today_info_items`, ordered by `"score"` or `"recency"`.
+/// Recency puts items without a publish time last instead of first.
+#[command]
+pub async fn get_today_info_items_sorted(
+    by: String,
+    date: Option<String>,
+) -> Result<Vec<InfoItem>, String> {
+    let order_by = match by.trim().to_lowercase().as_str() {
+        "" | "score" => INFO_ITEMS_ORDER_BY_SCORE,
+        "recency" => "published_at DESC NULLS LAST, score DESC, fetched_at DESC",
+        other => return Err(format!("不支持的排序方式: {}", other)),
+    };
+    fetch_info_items_ordered(&info_date_or_today(date.as_deref())?, order_by).await
+}
+
+/// Normalizes an optional `YYYY-MM-DD` date key, falling back to today.
+fn info_date_or_today(date: Option<&str>) -> Result<String, String> {
+    match date.map(str::trim).filter(|value| !value.is_empty()) {
+        Some(value) => Ok(chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .map_err(|_| format!("日期格式应为 YYYY-MM-DD: {}", value))?
+            .format("%Y-%m-%d")
+            .to_string()),
+        None => Ok(local_today_string()),
+    }
+}
+
+const INFO_ITEMS_ORDER_BY_SCORE: &str = "score DESC, fetched_at DESC";
+
+/// Stored items for one `YYYY-MM-DD` date key, best score first.
+async fn fetch_info_items_for_date(date: &str) -> Result<Vec<InfoItem>, String> {
+    fetch_info_items_ordered(date, INFO_ITEMS_ORDER_BY_SCORE).await
+}
+
+/// `order_by` is spliced into the SQL, so callers pass fixed clauses, never user input.
+async fn fetch_info_items_ordered(date: &str, order_by: &str) -> Result<Vec<InfoItem>, String> {
+    let pool = get_db_pool()?;
+    let rows = sqlx::query(&format!(
+        "SELECT id, source_id, title, link, summary, published_at, score, matched_keywords_json, fetched_at
+         FROM info_items_daily
+         WHERE date = ?1
+         ORDER BY {}",
+        order_by
+    ))
+    .bind(date)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch info items: {}", e))?;
+
+    Ok(rows
+        .into_iter()
+        .map(row_to_info_item)
+        .collect::<Result<Vec<_>, _>>()?)
+}
+
+/// Re-runs keyword filtering and scoring over today's stored items without fetching.
+/// Items now excluded are removed; items dropped at fetch time can only come back
+/// through a network refresh.
+#[command]
+pub async fn reprocess_today_info() -> Result<Vec<InfoItem>, String> {
+    let pool = get_db_pool()?;
+    let settings = load_info_settings().await?;
+    let filter = InfoKeywordFilter::from_settings(&settings);
+    let now = chrono::Utc::now();
+
+    let mut rescored: Vec<(String, f64, Vec<String>)> = Vec::new();
+    let mut removed: Vec<String> = Vec::new();
+    for item in get_today_info_items(None).await? {
+        let published = item
+            .published_at
+            .as_deref()
+            .and_then(|value| chrono::DateTime::parse_from_rfc3339(value).ok())
+            .map(|value| value.with_timezone(&chrono::Utc));
+        match score_info_entry(
+            &item.title,
+            item.summary.as_deref(),
+            published,
+            &filter,
+            now,
+        ) {
+            Some((score, matched_keywords)) => rescored.push((item.id, score, matched_keywords)),
+            None => removed.push(item.id),
+        }
+    }
+    rescored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal));
+    if rescored.len() > settings.max_items_per_day as usize {
+        removed.extend(
+            rescored
+                .drain(settings.max_items_per_day as usize..)
+                .map(|(id, _, _)| id),
+        );
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to start reprocess transaction: {}", e))?;
+    for (id, score, matched_keywords) in &rescored {
+        let matched_keywords_json = serde_json::to_string(matched_keywords)
+            .map_err(|e| format!("Failed to serialize matched keywords: {}", e))?;
+        sqlx::query(
+            "UPDATE info_items_daily SET score = ?1, matched_keywords_json = ?2 WHERE id = ?3",
+        )
+        .bind(score)
+        .bind(matched_keywords_json)
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to update info item: {}", e))?;
+    }
+    for id in &removed {
+        sqlx::query("DELETE FROM info_items_daily WHERE id = ?1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Failed to remove info item: {}", e))?;
+    }
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit reprocess transaction: {}", e))?;
+
+    get_today_info_items(None).await
+}
+
+/// Deletes today's stored items without refetching; sources and settings are kept.
+#[command]
+pub async fn clear_today_info() -> Result<u64, String> {
+    let _guard = try_begin_info_refresh("clear")
+        .map_err(|running| format!("已有刷新正在进行（{}），请稍后再试", running))?;
+    let pool = get_db_pool()?;
+    let result = sqlx::query("DELETE FROM info_items_daily WHERE date = ?1")
+        .bind(local_today_string())
+        .execute(pool)
+        .await
+        .map_err(|e| format!("Failed to clear today info items: {}", e))?;
+    Ok(result.rows_affected())
+}
+
+/// Writes the items of `date` (defaults to today) as a Markdown or HTML digest.
+#[command]
+pub async fn export_info_digest(
+    date: Option<String>,
+    format: String,
+    path: String,
+) -> Result<ExportInfoDigestResponse, String> {
+    let date = info_date_or_today(date.as_deref())?;
+    let items = fetch_info_items_for_date(&date).await?;
+    let content = match format.trim().to_lowercase().as_str() {
+        "markdown" | "md" => render_info_digest_markdown(&date, &items),
+        "html" => render_info_digest_html(&date, &items),
+        other => return Err(format!("Unsupported digest format: {}", other)),
+    };
+
+    let output_path = PathBuf::from(path.trim());
+    if output_path.as_os_str().is_empty() {
+        return Err("导出路径不能为空".to_string());
+    }
+    if let Some(parent) = output_path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("创建导出目录失败 ({}): {}", parent.display(), e))?;
+    }
+    fs::write(&output_path, content)
+        .map_err(|e| format!("写入导出文件失败 ({}): {}", output_path.display(), e))?;
+
+    Ok(ExportInfoDigestResponse {
+        path: output_path.to_string_lossy().to_string(),
+        count: items.len(),
+    })
+}
+
+fn render_info_digest_markdown(date: &str, items: &[InfoItem]) -> String {
+    let mut markdown = format!("# 每日信息 {}\n", date);
+    for item in items {
+        markdown.push_str(&format!("\n## [{}]({})\n", item.title, item.link));
+        if let Some(summary) = item.summary.as_deref().filter(|value| !value.is_empty()) {
+            markdown.push_str(&format!("\n{}\n", summary));
+        }
+    }
+    markdown
+}
+
+fn render_info_digest_html(date: &str, items: &[InfoItem]) -> String {
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"><title>每日信息 {0}</title></head>\n<body>\n<h1>每日信息 {0}</h1>\n",
+        escape_html(date)
+    );
+    for item in items {
+        html.push_str(&format!(
+            "<article>\n<h2><a href=\"{}\">{}</a></h2>\n",
+            escape_html(&item.link),
+            escape_html(&item.title)
+        ));
+        if let Some(summary) = item.summary.as_deref().filter(|value| !value.is_empty()) {
+            html.push_str(&format!("<p>{}</p>\n", escape_html(summary)));
+        }
+        html.push_str("</article>\n");
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+fn escape_html(input: &str) -> String {
+    input
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Item count per source for `date` (defaults to today). Enabled sources with no
+/// items are included with 0.
+#[command]
+pub async fn get_info_item_counts_by_source(
+    date: Option<String>,
+) -> Result<HashMap<String, i64>, String> {
+    let pool = get_db_pool()?;
+    let date = date
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .unwrap_or_else(local_today_string);
+    let rows = sqlx::query(
+        "SELECT s.id AS source_id, COUNT(i.id) AS item_count
+         FROM info_sources s
+         LEFT JOIN info_items_daily i ON i.source_id = s.id AND i.date = ?1
+         WHERE s.enabled = 1 OR i.id IS NOT NULL
+         GROUP BY s.id",
+    )
+    .bind(&date)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to count info items: {}", e))?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| (row.get("source_id"), row.get("item_count")))
+        .collect())
+}
+
+/// Ready-to-paste citation for a stored item: `标题 — 来源 (链接)`. The source part
+/// is left out when the source has since been deleted.
+#[command]
+pub async fn format_info_item_citation(id: String) -> Result<String, String> {
+    let pool = get_db_pool()?;
+    let row = sqlx::query(
+        "SELECT i.title, i.link, s.name AS source_name
+         FROM info_items_daily i
+         LEFT JOIN info_sources s ON s.id = i.source_id
+         WHERE i.id = ?1",
+    )
+    .bind(id.trim())
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch info item: {}", e))?
+    .ok_or_else(|| format!("未找到信息条目: {}", id.trim()))?;
+
+    Ok(format_citation(
+        row.get("title"),
+        row.get::<Option<String>, _>("source_name").as_deref(),
+        row.get("link"),
+    ))
+}
+
+fn format_citation(title: &str, source_name: Option<&str>, link: &str) -> String {
+    match source_name.map(str::trim).filter(|name| !name.is_empty()) {
+        Some(source_name) => format!("{} — {} ({})", title.trim(), source_name, link.trim()),
+        None => format!("{} ({})", title.trim(), link.trim()),
+    }
+}
+
+#[command]
+pub async fn refresh_info_now() -> Result<InfoRefreshResponse, String> {
+    refresh_info_with_trigger("manual").await
+}
+
+/// The push-time refresh. Returns `None` without touching the network while the
+/// quiet-hours window is active, and a `skipped` response while another refresh
+/// runs; the caller retries in both cases.
+#[command]
+pub async fn refresh_info_scheduled() -> Result<Option<InfoRefreshResponse>, String> {
+    let settings = load_info_settings().await?;
+    if in_quiet_hours(&settings, app_now().time()) {
+        return Ok(None);
+    }
+    refresh_info_with_trigger("scheduled").await.map(Some)
+}
+
+const MAX_SCHEDULED_REFRESH_MINUTES: i64 = 7 * 24 * 60;
+
+/// How long a due one-shot refresh waits before retrying after quiet hours or a
+/// refresh that was already running got in its way.
+const ONE_SHOT_REFRESH_RETRY_SECS: u64 = 60;
+
+/// Schedules a one-shot refresh `minutes` from now, on top of the daily push time,
+/// replacing any earlier one. Returns the absolute time it is due.
+#[command]
+pub async fn schedule_info_refresh_in(app: AppHandle, minutes: i64) -> Result<String, String> {
+    if !(1..=MAX_SCHEDULED_REFRESH_MINUTES).contains(&minutes) {
+        return Err(format!(
+            "刷新间隔应在 1 到 {} 分钟之间",
+            MAX_SCHEDULED_REFRESH_MINUTES
+        ));
+    }
+    let next_refresh_at = (app_now() + chrono::Duration::minutes(minutes)).to_rfc3339();
+    let pool = get_db_pool()?;
+    let result = sqlx::query(
+        "UPDATE info_settings SET next_refresh_at = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = 'default'",
+    )
+        .bind(&next_refresh_at)
+        .execute(pool)
+        .await
+        .map_err(|e| format!("Failed to schedule info refresh: {}", e))?;
+    if result.rows_affected() == 0 {
+        return Err("信息设置不存在，请先打开信息中心".to_string());
+    }
+    arm_one_shot_refresh(app, next_refresh_at.clone());
+    Ok(next_refresh_at)
+}
+
+/// Starts the timer for a stored one-shot refresh. Called when one is scheduled and
+/// at startup, so a pending refresh survives a restart. Emits `info_refreshed` once
+/// it has run.
+pub fn arm_one_shot_refresh(app: AppHandle, due: String) {
+    tauri::async_runtime::spawn(async move {
+        let retry = Duration::from_secs(ONE_SHOT_REFRESH_RETRY_SECS);
+        if let Ok(Some(response)) = run_one_shot_refresh(&due, retry).await {
+            let _ = app.emit("info_refreshed", response);
+        }
+    });
+}
 
-    load_info_settings().await
+/// Waits for `due`, then runs the refresh once. Quiet hours or a refresh already in
+/// progress push it back by `retry`. A timer whose schedule was replaced or
+/// cleared retires without running, and the schedule is only cleared after its
+/// refresh succeeded, so each one-shot runs exactly once.
+async fn run_one_shot_refresh(
+    due: &str,
+    retry: Duration,
+) -> Result<Option<InfoRefreshResponse>, String> {
+    // Unparseable times count as due so a bad value cannot stick around
+    let mut wait = chrono::DateTime::parse_from_rfc3339(due)
+        .ok()
+        .and_then(|due| {
+            (due.with_timezone(&chrono::Utc) - chrono::Utc::now())
+                .to_std()
+                .ok()
+        })
+        .unwrap_or_default();
+    loop {
+        tokio::time::sleep(wait).await;
+        wait = retry;
+
+        let settings = load_info_settings().await?;
+        if settings.next_refresh_at.as_deref() != Some(due) {
+            return Ok(None);
+        }
+        if in_quiet_hours(&settings, app_now().time()) {
+            continue;
+        }
+        let Ok(_guard) = try_begin_info_refresh("scheduled") else {
+            continue;
+        };
+        // Another timer for the same schedule may have run it while we waited
+        if load_info_settings().await?.next_refresh_at.as_deref() != Some(due) {
+            return Ok(None);
+        }
+
+        let response = run_info_refresh("scheduled").await?;
+        let pool = get_db_pool()?;
+        sqlx::query(
+            "UPDATE info_settings SET next_refresh_at = NULL, updated_at = CURRENT_TIMESTAMP WHERE id = 'default' AND next_refresh_at = ?1",
+        )
+        .bind(due)
+        .execute(pool)
+        .await
+        .map_err(|e| format!("Failed to clear scheduled refresh: {}", e))?;
+        return Ok(Some(response));
+    }
 }
 
+/// Cheap "check for updates": only sources whose feed changed since their last fetch
+/// (by ETag/Last-Modified) are re-fetched, and their new items are merged into
+/// today's list instead of rebuilding it. Until today has items, every source is
+/// fetched.
 #[command]
-pub async fn get_today_info_items() -> Result<Vec<InfoItem>, String> {
-    let pool = get_db_pool()?;
-    let date = local_today_string();
-    let rows = sqlx::query(
-        "SELECT id, source_id, title, link, summary, published_at, score, matched_keywords_json, fetched_at
-         FROM info_items_daily
-         WHERE date = ?1
-         ORDER BY score DESC, fetched_at DESC",
-    )
-    .bind(&date)
-    .fetch_all(pool)
-    .await
-    .map_err(|e| format!("Failed to fetch today info items: {}", e))?;
+pub async fn refresh_info_incremental() -> Result<InfoRefreshResponse, String> {
+    let trigger_type = "incremental";
+    let _guard = match try_begin_info_refresh(trigger_type) {
+        Ok(guard) => guard,
+        Err(running_trigger) => return Ok(info_refresh_busy_response(running_trigger)),
+    };
 
-    Ok(rows
+    let pool = get_db_pool()?;
+    let settings = load_info_settings().await?;
+    let today = local_today_string();
+    let refreshed_at = local_now_rfc3339();
+    let existing = fetch_info_items_for_date(&today).await?;
+    let stored_validators = if existing.is_empty() {
+        HashMap::new()
+    } else {
+        load_feed_validators().await?
+    };
+    let sources: Vec<InfoSource> = get_info_sources()
+        .await?
         .into_iter()
-        .map(row_to_info_item)
-        .collect::<Result<Vec<_>, _>>()?)
-}
+        .filter(|source| source.enabled)
+        .collect();
 
-#[command]
-pub async fn refresh_info_now() -> Result<InfoRefreshResponse, String> {
-    refresh_info_with_trigger("manual").await
+    let concurrency = settings.max_concurrent_fetches.max(1) as usize;
+    let filter = InfoKeywordFilter::from_settings(&settings);
+    let results: Vec<(&InfoSource, Result<Option<FetchedFeed>, String>)> =
+        stream::iter(sources.iter())
+            .map(|source| {
+                let filter = &filter;
+                let validators = stored_validators
+                    .get(&source.id)
+                    .cloned()
+                    .unwrap_or_default();
+                async move {
+                    let fetched = fetch_source_items_if_changed(source, filter, &validators).await;
+                    (source, fetched)
+                }
+            })
+            .buffer_unordered(concurrency)
+            .collect()
+            .await;
+
+    let existing_links: HashSet<&str> = existing.iter().map(|item| item.link.as_str()).collect();
+    let mut fetched_count = 0;
+    let mut changed_sources = 0;
+    let mut new_items: HashMap<String, InfoItem> = HashMap::new();
+    let mut errors = Vec::new();
+    let mut sightings = Vec::new();
+    let mut health = Vec::with_capacity(results.len());
+    let mut validators = Vec::new();
+    for (source, result) in results {
+        match result {
+            Ok(None) => health.push((source.id.clone(), "ok", None)),
+            Ok(Some(feed)) => {
+                changed_sources += 1;
+                let status = if feed.empty { "empty" } else { "ok" };
+                health.push((source.id.clone(), status, None));
+                validators.push((source.id.clone(), feed.validators));
+                fetched_count += feed.items.len() as i32;
+                for item in feed.items {
+                    sightings.push((
+                        item.link.clone(),
+                        item.source_id.clone(),
+                        item.title.clone(),
+                    ));
+                    if existing_links.contains(item.link.as_str()) {
+                        continue;
+                    }
+                    if new_items
+                        .get(&item.link)
+                        .is_none_or(|current| item.score > current.score)
+                    {
+                        new_items.insert(item.link.clone(), item);
+                    }
+                }
+            }
+            Err(error) => {
+                health.push((source.id.clone(), "error", Some(error.clone())));
+                errors.push(format!("{}: {}", source.name, error));
+            }
+        }
+    }
+
+    record_link_sightings(&today, &sightings).await?;
+    record_source_health(&health).await?;
+    record_feed_validators(&validators).await?;
+
+    let mut merged: Vec<(bool, &InfoItem)> = existing
+        .iter()
+        .map(|item| (false, item))
+        .chain(new_items.values().map(|item| (true, item)))
+        .collect();
+    merged.sort_by(|a, b| b.1.score.partial_cmp(&a.1.score).unwrap_or(Ordering::Equal));
+    merged.truncate(settings.max_items_per_day as usize);
+    let kept_ids: HashSet<&str> = merged
+        .iter()
+        .filter(|(is_new, _)| !is_new)
+        .map(|(_, item)| item.id.as_str())
+        .collect();
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+    for item in existing
+        .iter()
+        .filter(|item| !kept_ids.contains(item.id.as_str()))
+    {
+        sqlx::query("DELETE FROM info_items_daily WHERE id = ?1")
+            .bind(&item.id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Failed to drop info item: {}", e))?;
+    }
+    let mut added = 0;
+    for (_, item) in merged.iter().filter(|(is_new, _)| *is_new) {
+        store_info_item(&mut *tx, &today, item).await?;
+        added += 1;
+    }
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit transaction: {}", e))?;
+
+    let success = errors.is_empty();
+    let mut message = format!(
+        "检查 {} 个信息源，{} 个有更新，新增 {} 条信息",
+        sources.len(),
+        changed_sources,
+        added
+    );
+    if !success {
+        message.push_str(&format!("，{} 个信息源失败", errors.len()));
+    }
+    insert_info_refresh_log(
+        trigger_type,
+        success,
+        &if errors.is_empty() {
+            message.clone()
+        } else {
+            format!("{}（{}）", message, errors.join("; "))
+        },
+        fetched_count,
+        merged.len() as i32,
+    )
+    .await;
+
+    Ok(InfoRefreshResponse {
+        success,
+        skipped: false,
+        fetched_count,
+        kept_count: merged.len() as i32,
+        message,
+        refreshed_at,
+        trigger_type: trigger_type.to_string(),
+    })
 }
 
 #[command]
@@ -1517,14 +4581,292 @@ pub async fn get_info_refresh_status() -> Result<InfoRefreshStatus, String> {
     })
 }
 
+/// Writes the info refresh history to a CSV file, oldest first.
+#[command]
+pub async fn export_refresh_logs_csv(
+    path: String,
+    since: Option<String>,
+) -> Result<ExportRefreshLogsResponse, String> {
+    let since = since
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(normalize_since_timestamp)
+        .transpose()?;
+
+    let pool = get_db_pool()?;
+    let rows = sqlx::query(
+        "SELECT trigger_type, success, message, fetched_count, kept_count, created_at
+         FROM info_refresh_logs
+         WHERE ?1 IS NULL OR datetime(created_at) >= datetime(?1)
+         ORDER BY created_at ASC",
+    )
+    .bind(since)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch refresh logs: {}", e))?;
+
+    let records: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| {
+            vec![
+                row.get::<String, _>("trigger_type"),
+                (row.get::<i32, _>("success") != 0).to_string(),
+                row.get::<String, _>("message"),
+                row.get::<i64, _>("fetched_count").to_string(),
+                row.get::<i64, _>("kept_count").to_string(),
+                row.get::<String, _>("created_at"),
+            ]
+        })
+        .collect();
+
+    let output_path = PathBuf::from(path.trim());
+    write_csv(
+        &output_path,
+        &[
+            "trigger_type",
+            "success",
+            "message",
+            "fetched_count",
+            "kept_count",
+            "created_at",
+        ],
+        &records,
+    )?;
+
+    Ok(ExportRefreshLogsResponse {
+        path: output_path.to_string_lossy().to_string(),
+        count: records.len(),
+    })
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn write_csv(path: &Path, header: &[&str], rows: &[Vec<String>]) -> Result<(), String> {
+    if path.as_os_str().is_empty() {
+        return Err("导出路径不能为空".to_string());
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("创建导出目录失败 ({}): {}", parent.display(), e))?;
+    }
+
+    let mut content = header
+        .iter()
+        .map(|name| csv_field(name))
+        .collect::<Vec<_>>()
+        .join(",");
+    content.push_str("\r\n");
+    for row in rows {
+        let line = row
+            .iter()
+            .map(|value| csv_field(value))
+            .collect::<Vec<_>>()
+            .join(",");
+        content.push_str(&line);
+        content.push_str("\r\n");
+    }
+
+    fs::write(path, content).map_err(|e| format!("写入导出文件失败 ({}): {}", path.display(), e))
+}
+
 #[command]
 pub async fn open_external_link(url: String) -> Result<(), String> {
+    let trimmed = ensure_http_link(&url)?;
+    webbrowser::open(trimmed).map_err(|e| format!("Failed to open link: {}", e))?;
+    Ok(())
+}
+
+/// Downloads a linked article and extracts its main text. Results are cached by link.
+#[command]
+pub async fn fetch_info_item_content(link: String) -> Result<InfoItemContent, String> {
+    let link = ensure_http_link(&link)?.to_string();
+    let pool = get_db_pool()?;
+    let cached = sqlx::query(
+        "SELECT link, title, content, fetched_at FROM info_item_contents WHERE link = ?1",
+    )
+    .bind(&link)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Failed to query cached content: {}", e))?;
+    if let Some(row) = cached {
+        return Ok(InfoItemContent {
+            link: row.get("link"),
+            title: row.get("title"),
+            content: row.get("content"),
+            fetched_at: row.get("fetched_at"),
+        });
+    }
+
+    let response = guarded_get(&link).await?;
+    if !response.status().is_success() {
+        return Err(format!("HTTP {}", response.status()));
+    }
+    let bytes = read_body_with_limit(response, max_feed_bytes()).await?;
+    let html = String::from_utf8_lossy(&bytes);
+    let (title, content) = extract_article_text(&html);
+    if content.is_empty() {
+        return Err("未能从页面中提取正文".to_string());
+    }
+
+    let fetched_at = local_now_rfc3339();
+    sqlx::query(
+        "INSERT OR REPLACE INTO info_item_contents (link, title, content, fetched_at)
+         VALUES (?1, ?2, ?3, ?4)",
+    )
+    .bind(&link)
+    .bind(&title)
+    .bind(&content)
+    .bind(&fetched_at)
+    .execute(pool)
+    .await
+    .map_err(|e| format!("Failed to cache content: {}", e))?;
+
+    Ok(InfoItemContent {
+        link,
+        title,
+        content,
+        fetched_at,
+    })
+}
+
+fn ensure_http_link(url: &str) -> Result<&str, String> {
     let trimmed = url.trim();
     if !(trimmed.starts_with("http://") || trimmed.starts_with("https://")) {
         return Err("Only http/https links are allowed".to_string());
     }
-    webbrowser::open(trimmed).map_err(|e| format!("Failed to open link: {}", e))?;
-    Ok(())
+    Ok(trimmed)
+}
+
+/// Readability-style extraction: prefer `<article>`/`<main>`, fall back to `<body>`,
+/// and keep the text of paragraph-like blocks.
+fn extract_article_text(html: &str) -> (Option<String>, String) {
+    let document = scraper::Html::parse_document(html);
+    let select = |css: &str| scraper::Selector::parse(css).expect("valid selector");
+    let title = document
+        .select(&select("title"))
+        .next()
+        .map(|node| node.text().collect::<String>().trim().to_string())
+        .filter(|title| !title.is_empty());
+
+    let block_selector = select("h1, h2, h3, h4, p, li, pre");
+    let root = ["article", "main", "[role=main]", "body"]
+        .iter()
+        .find_map(|css| document.select(&select(css)).next());
+    let Some(root) = root else {
+        return (title, String::new());
+    };
+
+    let paragraphs: Vec<String> = root
+        .select(&block_selector)
+        .map(|node| {
+            node.text()
+                .collect::<String>()
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .filter(|text| !text.is_empty())
+        .collect();
+    (title, paragraphs.join("\n\n"))
+}
+
+/// Icon URL for a source's website, cached on the source row. Feeds often live on a
+/// different host (`feeds.example.com`, FeedBurner), so the site of a recently
+/// collected item is tried before the feed's own origin. `None` when neither
+/// declares an icon nor serves `/favicon.ico`.
+#[command]
+pub async fn get_source_icon(source_id: String) -> Result<Option<String>, String> {
+    let pool = get_db_pool()?;
+    let row = sqlx::query("SELECT url, icon_url FROM info_sources WHERE id = ?1")
+        .bind(&source_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| format!("Failed to query info source: {}", e))?
+        .ok_or_else(|| format!("信息源不存在: {}", source_id))?;
+    if let Some(icon_url) = row.get::<Option<String>, _>("icon_url") {
+        return Ok(Some(icon_url));
+    }
+    let feed_url: String = row.get("url");
+    let item_link: Option<String> = sqlx::query_scalar(
+        "SELECT link FROM info_items_daily WHERE source_id = ?1 ORDER BY fetched_at DESC LIMIT 1",
+    )
+    .bind(&source_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Failed to query info items: {}", e))?;
+
+    let mut origins: Vec<reqwest::Url> = Vec::new();
+    for url in item_link.iter().chain(std::iter::once(&feed_url)) {
+        let Ok(mut origin) = reqwest::Url::parse(url.trim()) else {
+            continue;
+        };
+        origin.set_path("/");
+        origin.set_query(None);
+        origin.set_fragment(None);
+        if !origins.contains(&origin) {
+            origins.push(origin);
+        }
+    }
+
+    for origin in origins {
+        if let Some(icon_url) = resolve_site_icon(&origin).await {
+            sqlx::query(
+                "UPDATE info_sources SET icon_url = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = ?2",
+            )
+            .bind(&icon_url)
+            .bind(&source_id)
+            .execute(pool)
+            .await
+            .map_err(|e| format!("Failed to cache source icon: {}", e))?;
+            return Ok(Some(icon_url));
+        }
+    }
+    Ok(None)
+}
+
+/// The icon a site's home page declares via `<link rel="icon">`, else its
+/// `/favicon.ico` if that answers with success.
+async fn resolve_site_icon(origin: &reqwest::Url) -> Option<String> {
+    if let Ok(response) = guarded_get(origin.as_str()).await {
+        if response.status().is_success() {
+            let page_url = response.url().clone();
+            if let Ok(bytes) = read_body_with_limit(response, max_feed_bytes()).await {
+                let html = String::from_utf8_lossy(&bytes);
+                if let Some(icon_url) = extract_icon_link(&html, &page_url) {
+                    return Some(icon_url);
+                }
+            }
+        }
+    }
+    let favicon = origin.join("/favicon.ico").ok()?;
+    let response = guarded_get(favicon.as_str()).await.ok()?;
+    response
+        .status()
+        .is_success()
+        .then(|| response.url().to_string())
+}
+
+fn extract_icon_link(html: &str, page_url: &reqwest::Url) -> Option<String> {
+    let document = scraper::Html::parse_document(html);
+    let selector = scraper::Selector::parse("link[rel][href]").expect("valid selector");
+    document
+        .select(&selector)
+        .filter(|node| {
+            node.value().attr("rel").is_some_and(|rel| {
+                rel.split_whitespace()
+                    .any(|token| token.eq_ignore_ascii_case("icon"))
+            })
+        })
+        .filter_map(|node| page_url.join(node.value().attr("href")?.trim()).ok())
+        .find(|url| matches!(url.scheme(), "http" | "https"))
+        .map(|url| url.to_string())
 }
 
 // ============= Agent Commands =============
@@ -1536,6 +4878,12 @@ pub struct AgentProviderConfig {
     pub api_key: String,
     pub model: String,
     pub api_version: Option<String>,
+    /// Local request budget per minute; `None` disables the limiter.
+    #[serde(default)]
+    pub requests_per_minute: Option<u32>,
+    /// Whole-request timeout; a stalled call fails over to the local fallback.
+    #[serde(default = "default_provider_timeout_ms")]
+    pub request_timeout_ms: u64,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -1550,6 +4898,16 @@ pub struct AgentSettings {
     pub minimax: AgentProviderConfig,
     #[serde(default)]
     pub codex: AgentCodexConfig,
+    /// When off, `agent_chat` only proposes actions and never runs them itself.
+    #[serde(default = "default_true")]
+    pub auto_execute: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentProfile {
+    pub name: String,
+    pub settings: AgentSettings,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -1592,7 +4950,18 @@ pub struct AgentMessage {
 pub struct AgentChatRequest {
     pub request_id: Option<String>,
     pub messages: Vec<AgentMessage>,
-    pub settings: AgentSettings,
+    /// Falls back to the settings persisted via `save_agent_settings` when omitted.
+    #[serde(default)]
+    pub settings: Option<AgentSettings>,
+    /// One-off provider for this call only; the saved settings are left untouched.
+    #[serde(default)]
+    pub provider_override: Option<String>,
+    /// One-off model for the effective provider of this call.
+    #[serde(default)]
+    pub model_override: Option<String>,
+    /// Named profile from `save_agent_profile`; ignored when `settings` is given.
+    #[serde(default)]
+    pub profile: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -1610,6 +4979,18 @@ pub struct AgentActionProposal {
 pub struct AgentChatResponse {
     pub reply: String,
     pub actions: Vec<AgentActionProposal>,
+    /// Ordered steps for multi-step tasks, shown before anything runs.
+    pub plan: Vec<AgentPlanStep>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentPlanStep {
+    pub step: u32,
+    pub description: String,
+    /// Id of the action in `actions` that carries out this step, if any.
+    #[serde(default)]
+    pub action_ref: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -1623,6 +5004,11 @@ pub struct AgentExecuteActionsRequest {
     #[serde(default)]
     pub request_id: Option<String>,
     pub actions: Vec<AgentActionProposal>,
+    /// Defaults to true: the first failure rolls back the whole batch. When false, each
+    /// write runs in its own savepoint, so a failure only undoes that action and the
+    /// rest of the batch still commits. The batch is then no longer atomic.
+    #[serde(default)]
+    pub stop_on_error: Option<bool>,
 }
 
 #[derive(Debug, Serialize)]
@@ -1643,6 +5029,8 @@ pub struct AgentExecutionAuditRecord {
     pub after_state: Option<Value>,
     pub success: bool,
     pub error: Option<String>,
+    /// `stopOnError` of the batch this record belongs to.
+    pub stop_on_error: bool,
     pub created_at: String,
 }
 
@@ -1675,12 +5063,71 @@ pub struct AgentCodexHealth {
     pub message: String,
 }
 
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentProviderError {
+    pub id: String,
+    pub request_id: String,
+    pub provider: String,
+    pub status: Option<i64>,
+    pub body: String,
+    pub created_at: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentPromptPreview {
+    pub provider: String,
+    pub system: Option<String>,
+    pub messages: Vec<Value>,
+    pub prompt: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentTokenEstimate {
+    pub provider: String,
+    /// `tiktoken` for OpenAI-family providers, `heuristic` otherwise.
+    pub method: String,
+    pub tokens: usize,
+    pub chars: usize,
+}
+
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AgentCapabilities {
     pub builtin_tools: Vec<String>,
     pub skills: Vec<String>,
     pub mcp_servers: Vec<String>,
+    /// Tools reported by `tools/list` on each enabled server; servers that fail
+    /// to answer contribute nothing.
+    pub mcp_tools: Vec<McpToolInfo>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct McpToolInfo {
+    pub server: String,
+    pub tool: String,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentActionFieldSchema {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub field_type: String,
+    pub required: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentActionSchema {
+    #[serde(rename = "type")]
+    pub action_type: String,
+    pub read_only: bool,
+    pub fields: Vec<AgentActionFieldSchema>,
 }
 
 #[derive(Debug, Serialize)]
@@ -1710,6 +5157,13 @@ pub struct McpServerConfig {
     pub cwd: Option<String>,
     #[serde(default = "default_true")]
     pub enabled: bool,
+    /// Limit for one `agent_call_mcp_tool` call, including the server's startup.
+    #[serde(default = "default_mcp_tool_timeout_secs")]
+    pub tool_timeout_secs: u64,
+    /// Env keys whose stored value could not be decrypted (e.g. after the keychain
+    /// was reset). They keep the encrypted value until re-entered; not persisted.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub locked_env: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -1730,6 +5184,18 @@ pub struct SkillConfig {
     pub source: String,
 }
 
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkillValidationResult {
+    /// Skill directory that was checked.
+    pub path: String,
+    pub source: String,
+    /// Manifest id, when the manifest was readable enough to have one.
+    pub id: Option<String>,
+    pub ok: bool,
+    pub error: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct AgentCommandConfig {
@@ -1754,6 +5220,9 @@ pub struct AgentToolingConfig {
     pub mcp_servers: Vec<McpServerConfig>,
     pub skills: Vec<SkillConfig>,
     pub commands: Vec<AgentCommandConfig>,
+    /// User entries that override a builtin with the same name/id/slug, and skills or
+    /// commands skipped because they failed to load.
+    pub warnings: Vec<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -1767,6 +5236,25 @@ pub struct DeleteMcpServerRequest {
     pub name: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct CallMcpToolRequest {
+    pub server: String,
+    pub tool: String,
+    #[serde(default)]
+    pub arguments: Option<Value>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpToolCallResponse {
+    /// The tool's `content` blocks as returned by the server.
+    pub content: Value,
+    /// Text blocks joined, for showing the result directly.
+    pub text: String,
+    /// Set when the tool itself reported a failure (as opposed to a protocol error).
+    pub is_error: bool,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct ImportSkillRequest {
     pub path: String,
@@ -1794,21 +5282,68 @@ pub struct ImportCommandMarkdownRequest {
     pub path: String,
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportAgentBundleRequest {
+    pub path: String,
+    pub include_secrets: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImportAgentBundleRequest {
+    pub path: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentBundleSummary {
+    pub path: String,
+    pub mcp_servers: usize,
+    pub commands: usize,
+    pub skills: usize,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandMarkdownPreview {
+    pub valid: bool,
+    pub command: Option<AgentCommandConfig>,
+    pub error: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct DeleteCommandRequest {
     pub slug: String,
 }
 
+static AGENT_CHAT_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
 #[command]
 pub async fn agent_chat(
     app: AppHandle,
     request: AgentChatRequest,
 ) -> Result<AgentChatResponse, String> {
-    let snapshot = build_context_snapshot().await?;
+    let settings = apply_provider_override(
+        resolve_agent_profile_settings(&app, request.settings.clone(), request.profile.as_deref())?,
+        request.provider_override.as_deref(),
+        request.model_override.as_deref(),
+    );
     let request_id = request
         .request_id
         .clone()
-        .unwrap_or_else(|| format!("req-{}", chrono::Utc::now().timestamp_millis()));
+        .unwrap_or_else(|| format!("req-{}", new_entity_id()));
+    // One chat at a time: a new request waits for the previous auto-execution so
+    // two batches never run against overlapping state.
+    let _chat_turn = match AGENT_CHAT_LOCK.try_lock() {
+        Ok(guard) => guard,
+        Err(_) => {
+            emit_agent_event(&app, &request_id, "queued", "等待上一个请求完成", None);
+            AGENT_CHAT_LOCK.lock().await
+        }
+    };
+    // Taken after the wait so it reflects whatever the previous request changed
+    let snapshot = build_context_snapshot().await?;
     emit_agent_event(
         &app,
         &request_id,
@@ -1817,44 +5352,116 @@ pub async fn agent_chat(
         None,
     );
 
-    match call_provider(&app, &request_id, &request, &snapshot).await {
+    match call_provider(&app, &request_id, &settings, &request.messages, &snapshot).await {
         Ok(mut response) => {
-            if !response.actions.is_empty() {
+            for action in &mut response.actions {
+                if is_approval_action(&action.r#type) {
+                    action.requires_approval = true;
+                }
+            }
+            let runnable: Vec<AgentActionProposal> = if settings.auto_execute {
+                response
+                    .actions
+                    .iter()
+                    .filter(|action| !is_approval_action(&action.r#type))
+                    .cloned()
+                    .collect()
+            } else {
+                vec![]
+            };
+            if !response.actions.is_empty() && runnable.is_empty() {
+                emit_agent_event(
+                    &app,
+                    &request_id,
+                    "awaiting_approval",
+                    "已生成动作，等待手动确认",
+                    Some(json!({ "count": response.actions.len() })),
+                );
+            } else if !runnable.is_empty() {
                 emit_agent_event(
                     &app,
                     &request_id,
                     "executing",
                     "已生成动作，开始自动执行",
-                    Some(json!({ "count": response.actions.len() })),
+                    Some(json!({ "count": runnable.len() })),
                 );
                 let execution = agent_execute_actions_atomic(
                     app.clone(),
                     AgentExecuteActionsRequest {
                         request_id: Some(request_id.clone()),
-                        actions: response.actions.clone(),
+                        actions: runnable,
+                        stop_on_error: None,
                     },
                 )
                 .await?;
 
                 if execution.success {
-                    response.reply = format!(
-                        "{}\n\n已自动执行 {} 条动作（batch: {}）。",
-                        response.reply,
-                        execution.records.len(),
-                        execution.batch_id
-                    );
+                    let read_results = collect_read_results(&execution.records);
+                    if !read_results.is_empty() {
+                        emit_agent_event(
+                            &app,
+                            &request_id,
+                            "planning",
+                            "已获取查询结果，正在生成回答",
+                            Some(json!({ "count": read_results.len() })),
+                        );
+                        let mut follow_up = request.messages.clone();
+                        follow_up.push(AgentMessage {
+                            role: "assistant".to_string(),
+                            content: response.reply.clone(),
+                        });
+                        follow_up.push(AgentMessage {
+                            role: "user".to_string(),
+                            content: format!(
+                                "查询结果: {}\n请基于查询结果直接回答我的问题，actions 返回空数组。",
+                                Value::Array(read_results)
+                            ),
+                        });
+                        match call_provider(&app, &request_id, &settings, &follow_up, &snapshot)
+                            .await
+                        {
+                            Ok(answer) => response.reply = answer.reply,
+                            Err(error) => {
+                                response.reply = format!(
+                                    "{}\n\n查询已完成，但生成回答失败：{}",
+                                    response.reply, error
+                                );
+                            }
+                        }
+                    }
+                    let write_count = execution
+                        .records
+                        .iter()
+                        .filter(|record| !is_read_action(&record.action_type))
+                        .count();
+                    if write_count > 0 {
+                        response.reply = format!(
+                            "{}\n\n已自动执行 {} 条动作（batch: {}）。",
+                            response.reply, write_count, execution.batch_id
+                        );
+                    }
                 } else {
                     response.reply = format!(
                         "{}\n\n自动执行失败（batch: {}）：{}",
                         response.reply, execution.batch_id, execution.message
                     );
                 }
-                response.actions = vec![];
+                // Approval-only actions are handed back for the user to confirm
+                response
+                    .actions
+                    .retain(|action| is_approval_action(&action.r#type));
+                if !response.actions.is_empty() {
+                    response.reply = format!(
+                        "{}\n\n另有 {} 条动作需要你确认后执行。",
+                        response.reply,
+                        response.actions.len()
+                    );
+                }
             }
 
             persist_agent_session(
                 &request_id,
-                &request.settings.provider,
+                &settings.provider,
                 &request.messages,
                 &response.reply,
             )
@@ -1874,7 +5481,7 @@ pub async fn agent_chat(
             let response = local_fallback_response(&request.messages, &snapshot, Some(error));
             persist_agent_session(
                 &request_id,
-                &request.settings.provider,
+                &settings.provider,
                 &request.messages,
                 &response.reply,
             )
@@ -1892,20 +5499,44 @@ pub async fn agent_execute_action(
     let pool = get_db_pool()?;
     let action = request.action;
     validate_action(&action.r#type, &action.payload)?;
+    if is_read_action(&action.r#type) {
+        let mut conn = pool
+            .acquire()
+            .await
+            .map_err(|e| format!("Failed to acquire connection: {}", e))?;
+        let result = execute_read_action(&mut conn, &action).await?;
+        return Ok(AgentExecuteResponse {
+            success: true,
+            message: result.to_string(),
+        });
+    }
     let result = match action.r#type.as_str() {
         "todo.create" => {
             let title = get_required_str(&action.payload, "title")?;
-            let priority = get_optional_str(&action.payload, "priority").unwrap_or("normal");
-            let id = chrono::Utc::now().timestamp_millis().to_string();
+            let priority = normalize_todo_priority(
+                get_optional_str(&action.payload, "priority").unwrap_or("normal"),
+            )?;
+            let id = new_entity_id();
             sqlx::query("INSERT INTO todos (id, title, priority) VALUES (?1, ?2, ?3)")
                 .bind(&id)
                 .bind(title)
-                .bind(priority)
+                .bind(&priority)
                 .execute(pool)
                 .await
                 .map_err(|e| format!("Failed to create todo: {}", e))?;
             "待办已创建".to_string()
         }
+        "todo.bulk_create" => {
+            let mut tx = pool
+                .begin()
+                .await
+                .map_err(|e| format!("Failed to start transaction: {}", e))?;
+            let created = bulk_create_todos(&mut tx, &action.payload).await?;
+            tx.commit()
+                .await
+                .map_err(|e| format!("Failed to commit transaction: {}", e))?;
+            format!("已批量创建 {} 条待办", created)
+        }
         "todo.update" => {
             let id = get_required_str(&action.payload, "id")?;
             let title = get_optional_str(&action.payload, "title");
@@ -1913,37 +5544,20 @@ pub async fn agent_execute_action(
                 .payload
                 .get("completed")
                 .and_then(|value| value.as_bool());
-            let priority = get_optional_str(&action.payload, "priority");
+            let priority = get_optional_str(&action.payload, "priority")
+                .map(normalize_todo_priority)
+                .transpose()?;
 
             if title.is_none() && completed.is_none() && priority.is_none() {
                 return Err("todo.update 缺少可更新字段".to_string());
             }
 
-            let mut updates: Vec<String> = Vec::new();
-            if title.is_some() {
-                updates.push("title = ?".to_string());
-            }
-            if completed.is_some() {
-                updates.push("completed = ?".to_string());
-            }
-            if priority.is_some() {
-                updates.push("priority = ?".to_string());
-            }
-            let query = format!("UPDATE todos SET {} WHERE id = ?", updates.join(", "));
-            let mut query_builder = sqlx::query(&query);
-
-            if let Some(value) = title {
-                query_builder = query_builder.bind(value);
-            }
-            if let Some(value) = completed {
-                query_builder = query_builder.bind(if value { 1 } else { 0 });
-            }
-            if let Some(value) = priority {
-                query_builder = query_builder.bind(value);
-            }
-            query_builder = query_builder.bind(id);
-            query_builder
-                .execute(pool)
+            let mut updates = UpdateBuilder::new();
+            updates.set_opt("title", title);
+            updates.set_completed(completed);
+            updates.set_opt("priority", priority);
+            updates
+                .execute("todos", id, pool)
                 .await
                 .map_err(|e| format!("Failed to update todo: {}", e))?;
             "待办已更新".to_string()
@@ -1960,7 +5574,7 @@ pub async fn agent_execute_action(
         "project.create" => {
             let title = get_required_str(&action.payload, "title")?;
             let deadline = get_required_str(&action.payload, "deadline")?;
-            let id = chrono::Utc::now().timestamp_millis().to_string();
+            let id = new_entity_id();
             sqlx::query(
                 "INSERT INTO projects (id, title, deadline, progress, status) VALUES (?1, ?2, ?3, 0, 'active')",
             )
@@ -1998,10 +5612,12 @@ pub async fn agent_execute_action(
         }
         "event.create" => {
             let title = get_required_str(&action.payload, "title")?;
-            let date = get_required_str(&action.payload, "date")?;
-            let color = get_optional_str(&action.payload, "color").unwrap_or("blue");
+            let date = normalize_event_date(get_required_str(&action.payload, "date")?)?;
+            let (color, color_warning) = get_optional_str(&action.payload, "color")
+                .map(normalize_event_color)
+                .unwrap_or(("blue", None));
             let note = get_optional_str(&action.payload, "note");
-            let id = chrono::Utc::now().timestamp_millis().to_string();
+            let id = new_entity_id();
             sqlx::query(
                 "INSERT INTO events (id, title, date, color, note) VALUES (?1, ?2, ?3, ?4, ?5)",
             )
@@ -2013,50 +5629,29 @@ pub async fn agent_execute_action(
             .execute(pool)
             .await
             .map_err(|e| format!("Failed to create event: {}", e))?;
-            "日程已创建".to_string()
+            with_color_warning("日程已创建", color_warning)
         }
         "event.update" => {
             let id = get_required_str(&action.payload, "id")?;
             let title = get_optional_str(&action.payload, "title");
-            let date = get_optional_str(&action.payload, "date");
-            let color = get_optional_str(&action.payload, "color");
+            let date = get_optional_str(&action.payload, "date")
+                .map(normalize_event_date)
+                .transpose()?;
+            let color = get_optional_str(&action.payload, "color").map(normalize_event_color);
             let note = get_optional_str(&action.payload, "note");
             if title.is_none() && date.is_none() && color.is_none() && note.is_none() {
                 return Err("event.update 缺少可更新字段".to_string());
             }
-            let mut updates: Vec<String> = Vec::new();
-            if title.is_some() {
-                updates.push("title = ?".to_string());
-            }
-            if date.is_some() {
-                updates.push("date = ?".to_string());
-            }
-            if color.is_some() {
-                updates.push("color = ?".to_string());
-            }
-            if note.is_some() {
-                updates.push("note = ?".to_string());
-            }
-            let query = format!("UPDATE events SET {} WHERE id = ?", updates.join(", "));
-            let mut query_builder = sqlx::query(&query);
-            if let Some(value) = title {
-                query_builder = query_builder.bind(value);
-            }
-            if let Some(value) = date {
-                query_builder = query_builder.bind(value);
-            }
-            if let Some(value) = color {
-                query_builder = query_builder.bind(value);
-            }
-            if let Some(value) = note {
-                query_builder = query_builder.bind(value);
-            }
-            query_builder = query_builder.bind(id);
-            query_builder
-                .execute(pool)
+            let mut updates = UpdateBuilder::new();
+            updates.set_opt("title", title);
+            updates.set_opt("date", date);
+            updates.set_opt("color", color.as_ref().map(|(color, _)| *color));
+            updates.set_opt("note", note);
+            updates
+                .execute("events", id, pool)
                 .await
                 .map_err(|e| format!("Failed to update event: {}", e))?;
-            "日程已更新".to_string()
+            with_color_warning("日程已更新", color.and_then(|(_, warning)| warning))
         }
         "event.delete" => {
             let id = get_required_str(&action.payload, "id")?;
@@ -2069,7 +5664,7 @@ pub async fn agent_execute_action(
         }
         "personal.create" => {
             let title = get_required_str(&action.payload, "title")?;
-            let id = chrono::Utc::now().timestamp_millis().to_string();
+            let id = new_entity_id();
             let budget = action
                 .payload
                 .get("budget")
@@ -2077,8 +5672,13 @@ pub async fn agent_execute_action(
             let date = get_optional_str(&action.payload, "date");
             let location = get_optional_str(&action.payload, "location");
             let note = get_optional_str(&action.payload, "note");
+            let completed = action
+                .payload
+                .get("completed")
+                .and_then(|value| value.as_bool())
+                .unwrap_or(false);
             sqlx::query(
-                "INSERT INTO personal_tasks (id, title, budget, date, location, note) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                "INSERT INTO personal_tasks (id, title, budget, date, location, note, completed, completed_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, CASE WHEN ?7 = 1 THEN CURRENT_TIMESTAMP END)",
             )
             .bind(&id)
             .bind(title)
@@ -2086,6 +5686,7 @@ pub async fn agent_execute_action(
             .bind(date)
             .bind(location)
             .bind(note)
+            .bind(if completed { 1 } else { 0 })
             .execute(pool)
             .await
             .map_err(|e| format!("Failed to create personal task: {}", e))?;
@@ -2101,57 +5702,54 @@ pub async fn agent_execute_action(
             let date = get_optional_str(&action.payload, "date");
             let location = get_optional_str(&action.payload, "location");
             let note = get_optional_str(&action.payload, "note");
+            let completed = action
+                .payload
+                .get("completed")
+                .and_then(|value| value.as_bool());
             if title.is_none()
                 && budget.is_none()
                 && date.is_none()
                 && location.is_none()
                 && note.is_none()
+                && completed.is_none()
             {
                 return Err("personal.update 缺少可更新字段".to_string());
             }
-            let mut updates: Vec<String> = Vec::new();
-            if title.is_some() {
-                updates.push("title = ?".to_string());
-            }
-            if budget.is_some() {
-                updates.push("budget = ?".to_string());
-            }
-            if date.is_some() {
-                updates.push("date = ?".to_string());
-            }
-            if location.is_some() {
-                updates.push("location = ?".to_string());
-            }
-            if note.is_some() {
-                updates.push("note = ?".to_string());
-            }
-            let query = format!(
-                "UPDATE personal_tasks SET {} WHERE id = ?",
-                updates.join(", ")
-            );
-            let mut query_builder = sqlx::query(&query);
-            if let Some(value) = title {
-                query_builder = query_builder.bind(value);
-            }
-            if let Some(value) = budget {
-                query_builder = query_builder.bind(value);
-            }
-            if let Some(value) = date {
-                query_builder = query_builder.bind(value);
-            }
-            if let Some(value) = location {
-                query_builder = query_builder.bind(value);
-            }
-            if let Some(value) = note {
-                query_builder = query_builder.bind(value);
-            }
-            query_builder = query_builder.bind(id);
-            query_builder
-                .execute(pool)
+            let mut updates = UpdateBuilder::new();
+            updates.set_opt("title", title);
+            updates.set_opt("budget", budget);
+            updates.set_opt("date", date);
+            updates.set_opt("location", location);
+            updates.set_opt("note", note);
+            updates.set_completed(completed);
+            updates
+                .execute("personal_tasks", id, pool)
                 .await
                 .map_err(|e| format!("Failed to update personal task: {}", e))?;
             "个人事务已更新".to_string()
         }
+        "personal.to_event" => {
+            let id = get_required_str(&action.payload, "id")?;
+            let mark_completed = action
+                .payload
+                .get("markCompleted")
+                .and_then(|value| value.as_bool())
+                .unwrap_or(false);
+            let mut tx = pool
+                .begin()
+                .await
+                .map_err(|e| format!("Failed to start transaction: {}", e))?;
+            convert_personal_task_to_event(&mut tx, id, mark_completed).await?;
+            tx.commit()
+                .await
+                .map_err(|e| format!("Failed to commit transaction: {}", e))?;
+            "已将个人事务转为日程".to_string()
+        }
+        "info.update_settings" => {
+            let request = info_settings_request_from_payload(&action.payload).await?;
+            upsert_info_settings(pool, request).await?;
+            "信息设置已更新".to_string()
+        }
         "personal.delete" => {
             let id = get_required_str(&action.payload, "id")?;
             sqlx::query("DELETE FROM personal_tasks WHERE id = ?1")
@@ -2177,7 +5775,7 @@ pub async fn agent_execute_actions_atomic(
     request: AgentExecuteActionsRequest,
 ) -> Result<AgentExecuteActionsResponse, String> {
     let pool = get_db_pool()?;
-    let batch_id = format!("batch-{}", chrono::Utc::now().timestamp_millis());
+    let batch_id = format!("batch-{}", new_entity_id());
     let mut tx = pool
         .begin()
         .await
@@ -2189,6 +5787,7 @@ pub async fn agent_execute_actions_atomic(
     let mut completed = 0usize;
     let mut success = 0usize;
     let mut failed = 0usize;
+    let stop_on_error = request.stop_on_error.unwrap_or(true);
 
     if let Some(request_id) = &request.request_id {
         emit_agent_event(
@@ -2197,6 +5796,7 @@ pub async fn agent_execute_actions_atomic(
             "executing",
             "开始执行动作",
             Some(json!({
+                "batchId": batch_id,
                 "total": total,
                 "completed": completed,
                 "success": success,
@@ -2206,18 +5806,82 @@ pub async fn agent_execute_actions_atomic(
     }
 
     for action in &request.actions {
-        validate_action(&action.r#type, &action.payload)?;
+        if let Err(error) = validate_action(&action.r#type, &action.payload) {
+            if stop_on_error {
+                return Err(error);
+            }
+            completed += 1;
+            failed += 1;
+            records.push(failed_audit_record(
+                &batch_id,
+                action,
+                error,
+                stop_on_error,
+                &now,
+            ));
+            continue;
+        }
+        if is_read_action(&action.r#type) {
+            // Reads mutate nothing, so a failed read is recorded without rolling back
+            let result = execute_read_action(&mut tx, action).await;
+            completed += 1;
+            let (after_state, error) = match result {
+                Ok(data) => {
+                    success += 1;
+                    (Some(json!({ "message": "查询完成", "result": data })), None)
+                }
+                Err(error) => {
+                    failed += 1;
+                    (None, Some(error))
+                }
+            };
+            records.push(AgentExecutionAuditRecord {
+                id: format!("audit-{}", new_entity_id()),
+                batch_id: batch_id.clone(),
+                action_id: action.id.clone(),
+                action_type: action.r#type.clone(),
+                payload: action.payload.clone(),
+                before_state: None,
+                after_state,
+                success: error.is_none(),
+                error,
+                stop_on_error,
+                created_at: now.clone(),
+            });
+            if let Some(request_id) = &request.request_id {
+                emit_agent_event(
+                    &app,
+                    request_id,
+                    "executing",
+                    "只读查询已执行",
+                    Some(json!({
+                        "total": total,
+                        "completed": completed,
+                        "success": success,
+                        "failed": failed,
+                        "actionType": action.r#type,
+                        "actionId": action.id
+                    })),
+                );
+            }
+            continue;
+        }
         let before_state = None;
-        let result = execute_action_with_transaction(&mut tx, action).await;
+        // `abort` means the failure leaves the whole transaction to be rolled back
+        let (result, abort) = if stop_on_error {
+            (execute_action_with_transaction(&mut tx, action).await, true)
+        } else {
+            match execute_action_in_savepoint(&mut tx, action).await {
+                Ok(result) => (result, false),
+                Err(error) => (Err(error), true),
+            }
+        };
         match result {
             Ok(message) => {
                 completed += 1;
                 success += 1;
                 records.push(AgentExecutionAuditRecord {
-                    id: format!(
-                        "audit-{}",
-                        chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0)
-                    ),
+                    id: format!("audit-{}", new_entity_id()),
                     batch_id: batch_id.clone(),
                     action_id: action.id.clone(),
                     action_type: action.r#type.clone(),
@@ -2226,6 +5890,7 @@ pub async fn agent_execute_actions_atomic(
                     after_state: Some(json!({ "message": message })),
                     success: true,
                     error: None,
+                    stop_on_error,
                     created_at: now.clone(),
                 });
                 if let Some(request_id) = &request.request_id {
@@ -2245,12 +5910,41 @@ pub async fn agent_execute_actions_atomic(
                     );
                 }
             }
+            Err(error) if !abort => {
+                completed += 1;
+                failed += 1;
+                records.push(failed_audit_record(
+                    &batch_id,
+                    action,
+                    error,
+                    stop_on_error,
+                    &now,
+                ));
+                if let Some(request_id) = &request.request_id {
+                    emit_agent_event(
+                        &app,
+                        request_id,
+                        "executing",
+                        "动作执行失败，已跳过",
+                        Some(json!({
+                            "total": total,
+                            "completed": completed,
+                            "success": success,
+                            "failed": failed,
+                            "actionType": action.r#type,
+                            "actionId": action.id
+                        })),
+                    );
+                }
+            }
             Err(error) => {
                 completed += 1;
                 failed += 1;
-                tx.rollback()
-                    .await
-                    .map_err(|e| format!("Failed to rollback transaction: {}", e))?;
+                // The audit below is written even if the rollback itself fails
+                let error = match tx.rollback().await {
+                    Ok(()) => error,
+                    Err(e) => format!("{}; Failed to rollback transaction: {}", error, e),
+                };
                 if let Some(request_id) = &request.request_id {
                     emit_agent_event(
                         &app,
@@ -2274,21 +5968,14 @@ pub async fn agent_execute_actions_atomic(
                         Some(json!({ "reason": error.clone(), "retryable": true })),
                     );
                 }
-                let failed = AgentExecutionAuditRecord {
-                    id: format!(
-                        "audit-{}",
-                        chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0)
-                    ),
-                    batch_id: batch_id.clone(),
-                    action_id: action.id.clone(),
-                    action_type: action.r#type.clone(),
-                    payload: action.payload.clone(),
-                    before_state: None,
-                    after_state: None,
-                    success: false,
-                    error: Some(error.clone()),
-                    created_at: now,
-                };
+                let mut failed =
+                    failed_audit_record(&batch_id, action, error.clone(), stop_on_error, &now);
+                // Nothing else from the batch is persisted, so keep the whole
+                // proposal list here for `agent_retry_batch`.
+                failed.after_state = Some(json!({
+                    "rolledBack": true,
+                    "actions": request.actions,
+                }));
                 persist_audit_records(&[failed.clone()]).await;
                 return Ok(AgentExecuteActionsResponse {
                     success: false,
@@ -2306,6 +5993,18 @@ pub async fn agent_execute_actions_atomic(
 
     persist_audit_records(&records).await;
 
+    if !stop_on_error {
+        let write_failed = records
+            .iter()
+            .any(|record| !record.success && !is_read_action(&record.action_type));
+        return Ok(AgentExecuteActionsResponse {
+            success: !write_failed,
+            batch_id,
+            message: format!("批量动作已执行：成功 {}，失败 {}", success, failed),
+            records,
+        });
+    }
+
     Ok(AgentExecuteActionsResponse {
         success: true,
         batch_id,
@@ -2314,6 +6013,343 @@ pub async fn agent_execute_actions_atomic(
     })
 }
 
+/// Runs one write action under the `agent_action` savepoint, so a failure only
+/// undoes that action. The inner result is the action's own outcome (savepoint
+/// create/release errors count as the action failing); the outer `Err` means the
+/// savepoint could not be rolled back and the transaction must be abandoned.
+async fn execute_action_in_savepoint(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    action: &AgentActionProposal,
+) -> Result<Result<String, String>, String> {
+    if let Err(e) = sqlx::query("SAVEPOINT agent_action")
+        .execute(&mut **tx)
+        .await
+    {
+        return Ok(Err(format!("Failed to create savepoint: {}", e)));
+    }
+    let error = match execute_action_with_transaction(tx, action).await {
+        Ok(message) => match sqlx::query("RELEASE SAVEPOINT agent_action")
+            .execute(&mut **tx)
+            .await
+        {
+            Ok(_) => return Ok(Ok(message)),
+            Err(e) => format!("Failed to release savepoint: {}", e),
+        },
+        Err(error) => error,
+    };
+    // ROLLBACK TO leaves the savepoint on the stack, so it is released separately
+    sqlx::query("ROLLBACK TO SAVEPOINT agent_action")
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| format!("{}; Failed to rollback savepoint: {}", error, e))?;
+    sqlx::query("RELEASE SAVEPOINT agent_action")
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| format!("{}; Failed to release savepoint: {}", error, e))?;
+    Ok(Err(error))
+}
+
+fn failed_audit_record(
+    batch_id: &str,
+    action: &AgentActionProposal,
+    error: String,
+    stop_on_error: bool,
+    created_at: &str,
+) -> AgentExecutionAuditRecord {
+    AgentExecutionAuditRecord {
+        id: format!("audit-{}", new_entity_id()),
+        batch_id: batch_id.to_string(),
+        action_id: action.id.clone(),
+        action_type: action.r#type.clone(),
+        payload: action.payload.clone(),
+        before_state: None,
+        after_state: None,
+        success: false,
+        error: Some(error),
+        stop_on_error,
+        created_at: created_at.to_string(),
+    }
+}
+
+/// Writes a markdown transcript of one agent request: the session(s), the
+/// streamed stages and the audited actions of any batch it started.
+#[command]
+pub async fn export_agent_session(request_id: String, path: String) -> Result<String, String> {
+    let pool = get_db_pool()?;
+    let request_id = request_id.trim().to_string();
+    let sessions = sqlx::query(
+        "SELECT provider, user_message, reply, created_at FROM agent_sessions WHERE request_id = ?1 ORDER BY created_at, id",
+    )
+    .bind(&request_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch agent sessions: {}", e))?;
+    let events = sqlx::query(
+        "SELECT stage, message, meta_json, created_at FROM agent_events WHERE request_id = ?1 ORDER BY created_at, id",
+    )
+    .bind(&request_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch agent events: {}", e))?;
+    if sessions.is_empty() && events.is_empty() {
+        return Err(format!("未找到会话记录: {}", request_id));
+    }
+
+    let mut markdown = format!("# Agent 会话 {}\n", request_id);
+    for session in &sessions {
+        let provider: String = session.get("provider");
+        let created_at: Option<String> = session.get("created_at");
+        let user_message: Option<String> = session.get("user_message");
+        let reply: String = session.get("reply");
+        markdown.push_str(&format!(
+            "\n- Provider: {}\n- 时间: {}\n\n## 用户\n\n{}\n\n## 回复\n\n{}\n",
+            provider,
+            created_at.unwrap_or_default(),
+            user_message.unwrap_or_default(),
+            reply
+        ));
+    }
+
+    let mut batch_ids: Vec<String> = Vec::new();
+    if !events.is_empty() {
+        markdown.push_str("\n## 执行阶段\n\n");
+    }
+    for event in &events {
+        let stage: String = event.get("stage");
+        let message: String = event.get("message");
+        let created_at: Option<String> = event.get("created_at");
+        let meta_json: Option<String> = event.get("meta_json");
+        markdown.push_str(&format!(
+            "- `{}` **{}** {}\n",
+            created_at.unwrap_or_default(),
+            stage,
+            message
+        ));
+        let batch_id = meta_json
+            .and_then(|raw| serde_json::from_str::<Value>(&raw).ok())
+            .and_then(|meta| meta.get("batchId")?.as_str().map(str::to_string));
+        if let Some(batch_id) = batch_id {
+            if !batch_ids.contains(&batch_id) {
+                batch_ids.push(batch_id);
+            }
+        }
+    }
+
+    for batch_id in &batch_ids {
+        let audits = sqlx::query(
+            "SELECT action_id, action_type, payload_json, success, error_message FROM agent_action_audits WHERE batch_id = ?1 ORDER BY created_at, id",
+        )
+        .bind(batch_id)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Failed to fetch agent audits: {}", e))?;
+        markdown.push_str(&format!("\n## 执行的动作（batch: {}）\n\n", batch_id));
+        if audits.is_empty() {
+            markdown.push_str("（无审计记录）\n");
+        }
+        for audit in audits {
+            let action_id: String = audit.get("action_id");
+            let action_type: String = audit.get("action_type");
+            let payload_json: String = audit.get("payload_json");
+            let success = audit.get::<i64, _>("success") != 0;
+            let error_message: Option<String> = audit.get("error_message");
+            markdown.push_str(&format!(
+                "- {} `{}` ({})\n  - payload: `{}`\n",
+                if success { "成功" } else { "失败" },
+                action_type,
+                action_id,
+                payload_json
+            ));
+            if let Some(error) = error_message {
+                markdown.push_str(&format!("  - 错误: {}\n", error));
+            }
+        }
+    }
+
+    let output_path = PathBuf::from(path.trim());
+    if let Some(parent) = output_path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create transcript dir: {}", e))?;
+    }
+    fs::write(&output_path, markdown).map_err(|e| format!("Failed to write transcript: {}", e))?;
+    Ok(output_path.to_string_lossy().to_string())
+}
+
+const DEFAULT_AUDIT_QUERY_LIMIT: i64 = 100;
+const MAX_AUDIT_QUERY_LIMIT: i64 = 1000;
+
+/// Audited agent actions, newest first, optionally narrowed to one batch and/or to
+/// records created at or after `since`.
+#[command]
+pub async fn get_audit_records(
+    batch_id: Option<String>,
+    since: Option<String>,
+    limit: Option<i64>,
+) -> Result<Vec<AgentExecutionAuditRecord>, String> {
+    let pool = get_db_pool()?;
+    let batch_id = batch_id
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+    let since = since
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(normalize_since_timestamp)
+        .transpose()?;
+    let limit = limit
+        .unwrap_or(DEFAULT_AUDIT_QUERY_LIMIT)
+        .clamp(1, MAX_AUDIT_QUERY_LIMIT);
+
+    let mut sql = "SELECT id, batch_id, action_id, action_type, payload_json, before_state_json, after_state_json, success, error_message, stop_on_error, created_at FROM agent_action_audits WHERE 1 = 1".to_string();
+    if batch_id.is_some() {
+        sql.push_str(" AND batch_id = ?");
+    }
+    if since.is_some() {
+        sql.push_str(" AND datetime(created_at) >= datetime(?)");
+    }
+    sql.push_str(" ORDER BY created_at DESC, id DESC LIMIT ?");
+    let mut query = sqlx::query(&sql);
+    if let Some(value) = &batch_id {
+        query = query.bind(value);
+    }
+    if let Some(value) = &since {
+        query = query.bind(value);
+    }
+    let rows = query
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Failed to query agent audits: {}", e))?;
+
+    let parse_json =
+        |raw: Option<String>| raw.and_then(|raw| serde_json::from_str::<Value>(&raw).ok());
+    Ok(rows
+        .into_iter()
+        .map(|row| AgentExecutionAuditRecord {
+            id: row.get("id"),
+            batch_id: row.get("batch_id"),
+            action_id: row.get("action_id"),
+            action_type: row.get("action_type"),
+            payload: parse_json(row.get("payload_json")).unwrap_or(Value::Null),
+            before_state: parse_json(row.get("before_state_json")),
+            after_state: parse_json(row.get("after_state_json")),
+            success: row.get::<i64, _>("success") != 0,
+            error: row.get("error_message"),
+            stop_on_error: row.get::<i64, _>("stop_on_error") != 0,
+            created_at: row
+                .get::<Option<String>, _>("created_at")
+                .unwrap_or_default(),
+        })
+        .collect())
+}
+
+const MAX_REPLAYED_AGENT_EVENTS: i64 = 500;
+
+/// The persisted `agent_stream` events of one request, oldest first, so a reopened
+/// window can rebuild the progress timeline. `limit` keeps only the latest N.
+#[command]
+pub async fn replay_agent_events(
+    request_id: String,
+    limit: Option<i64>,
+) -> Result<Vec<AgentStreamEvent>, String> {
+    let pool = get_db_pool()?;
+    let limit = limit
+        .unwrap_or(MAX_REPLAYED_AGENT_EVENTS)
+        .clamp(1, MAX_REPLAYED_AGENT_EVENTS);
+    // Ids carry a nanosecond timestamp, which orders events within the same second
+    let rows = sqlx::query(
+        "SELECT request_id, stage, message, meta_json, created_at FROM (SELECT * FROM agent_events WHERE request_id = ?1 ORDER BY created_at DESC, id DESC LIMIT ?2) ORDER BY created_at, id",
+    )
+    .bind(request_id.trim())
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch agent events: {}", e))?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            let created_at: String = row
+                .get::<Option<String>, _>("created_at")
+                .unwrap_or_default();
+            AgentStreamEvent {
+                request_id: row.get("request_id"),
+                stage: row.get("stage"),
+                message: row.get("message"),
+                meta: row
+                    .get::<Option<String>, _>("meta_json")
+                    .and_then(|raw| serde_json::from_str(&raw).ok()),
+                // Stored as SQLite's UTC `YYYY-MM-DD HH:MM:SS`; live events use RFC3339
+                created_at: chrono::NaiveDateTime::parse_from_str(&created_at, "%Y-%m-%d %H:%M:%S")
+                    .map(|value| value.and_utc().to_rfc3339())
+                    .unwrap_or(created_at),
+            }
+        })
+        .collect())
+}
+
+/// Re-runs a failed batch from its audit trail, with the batch's original
+/// `stopOnError`. A rolled-back batch is replayed in full; otherwise only the
+/// failed writes are retried, since the rest were already committed.
+#[command]
+pub async fn agent_retry_batch(
+    app: AppHandle,
+    batch_id: String,
+) -> Result<AgentExecuteActionsResponse, String> {
+    let batch_id = batch_id.trim().to_string();
+    if batch_id.is_empty() {
+        return Err("batchId 不能为空".to_string());
+    }
+    let records =
+        get_audit_records(Some(batch_id.clone()), None, Some(MAX_AUDIT_QUERY_LIMIT)).await?;
+    let stop_on_error = records
+        .first()
+        .ok_or_else(|| format!("未找到批次: {}", batch_id))?
+        .stop_on_error;
+    if records.iter().all(|record| record.success) {
+        return Err("该批次没有失败的动作，无需重试".to_string());
+    }
+
+    // Only a rolled-back batch keeps its whole proposal list
+    let stored_actions = records.iter().find_map(|record| {
+        record
+            .after_state
+            .as_ref()
+            .and_then(|state| state.get("actions"))
+            .cloned()
+    });
+    let actions: Vec<AgentActionProposal> = match stored_actions {
+        Some(actions) => serde_json::from_value(actions)
+            .map_err(|e| format!("Failed to parse stored batch actions: {}", e))?,
+        None => records
+            .iter()
+            .rev()
+            .filter(|record| !record.success && !is_read_action(&record.action_type))
+            .map(|record| AgentActionProposal {
+                id: record.action_id.clone(),
+                r#type: record.action_type.clone(),
+                title: record.action_type.clone(),
+                reason: format!("重试批次 {}", batch_id),
+                payload: record.payload.clone(),
+                requires_approval: false,
+            })
+            .collect(),
+    };
+    if actions.is_empty() {
+        return Err("该批次没有可重试的动作".to_string());
+    }
+
+    agent_execute_actions_atomic(
+        app,
+        AgentExecuteActionsRequest {
+            request_id: None,
+            actions,
+            stop_on_error: Some(stop_on_error),
+        },
+    )
+    .await
+}
+
 #[command]
 pub async fn agent_list_capabilities(app: AppHandle) -> Result<AgentCapabilities, String> {
     let tooling = load_tooling_config(&app)?;
@@ -2323,15 +6359,20 @@ pub async fn agent_list_capabilities(app: AppHandle) -> Result<AgentCapabilities
         .filter(|item| item.enabled)
         .map(|item| item.id)
         .collect::<Vec<String>>();
-    let mcp_servers = tooling
+    let enabled_servers = tooling
         .mcp_servers
         .into_iter()
         .filter(|item| item.enabled)
+        .collect::<Vec<McpServerConfig>>();
+    let mcp_tools = list_mcp_tools(&enabled_servers).await;
+    let mcp_servers = enabled_servers
+        .into_iter()
         .map(|item| item.name)
         .collect::<Vec<String>>();
     Ok(AgentCapabilities {
         builtin_tools: vec![
             "todo.create".to_string(),
+            "todo.bulk_create".to_string(),
             "todo.update".to_string(),
             "todo.delete".to_string(),
             "project.create".to_string(),
@@ -2343,13 +6384,46 @@ pub async fn agent_list_capabilities(app: AppHandle) -> Result<AgentCapabilities
             "personal.create".to_string(),
             "personal.update".to_string(),
             "personal.delete".to_string(),
+            "personal.to_event".to_string(),
+            "info.update_settings".to_string(),
             "query.snapshot".to_string(),
+            "todo.list".to_string(),
+            "project.list".to_string(),
+            "event.list_by_date".to_string(),
         ],
         skills,
         mcp_servers,
+        mcp_tools,
     })
 }
 
+#[command]
+pub async fn agent_get_action_schemas() -> Result<Vec<AgentActionSchema>, String> {
+    Ok(AGENT_ACTION_SPECS
+        .iter()
+        .map(|spec| AgentActionSchema {
+            action_type: spec.action_type.to_string(),
+            read_only: is_read_action(spec.action_type),
+            fields: spec
+                .fields
+                .iter()
+                .map(|field| AgentActionFieldSchema {
+                    name: field.name.to_string(),
+                    field_type: field.kind.to_string(),
+                    required: field.required,
+                })
+                .collect(),
+        })
+        .collect())
+}
+
+/// Runs `parse_llm_response` on arbitrary text, e.g. a model output copied from a
+/// bug report, without calling any provider.
+#[command]
+pub async fn agent_parse_test(content: String) -> Result<AgentChatResponse, String> {
+    parse_llm_response(&content)
+}
+
 #[command]
 pub async fn agent_reload_skills(app: AppHandle) -> Result<ReloadSkillsResponse, String> {
     let reloaded = load_tooling_config(&app)?.skills.len();
@@ -2410,6 +6484,55 @@ pub async fn agent_delete_mcp_server(
     write_user_mcp_servers(&app, &servers)
 }
 
+/// Runs one `tools/call` against a configured MCP server (enabled or not) so a tool
+/// can be tried before the agent relies on it. The server is started for this call
+/// only and killed once it answers or the timeout hits.
+#[command]
+pub async fn agent_call_mcp_tool(
+    app: AppHandle,
+    request: CallMcpToolRequest,
+) -> Result<McpToolCallResponse, String> {
+    let server = load_tooling_config(&app)?
+        .mcp_servers
+        .into_iter()
+        .find(|item| item.name.eq_ignore_ascii_case(request.server.trim()))
+        .ok_or_else(|| format!("MCP server not found: {}", request.server))?;
+    validate_mcp_server(&server)?;
+    let tool = request.tool.trim();
+    if tool.is_empty() {
+        return Err("MCP tool name cannot be empty".to_string());
+    }
+    let arguments = request.arguments.unwrap_or_else(|| json!({}));
+    if !arguments.is_object() {
+        return Err("MCP tool arguments must be a JSON object".to_string());
+    }
+
+    let tool_timeout = mcp_tool_timeout(&server);
+    let result = timeout(tool_timeout, call_mcp_tool(&server, tool, arguments))
+        .await
+        .map_err(|_| format!("MCP tool call timed out after {}s", tool_timeout.as_secs()))??;
+
+    let content = result
+        .get("content")
+        .cloned()
+        .unwrap_or_else(|| Value::Array(vec![]));
+    let text = content
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|block| block.get("text").and_then(|value| value.as_str()))
+        .collect::<Vec<&str>>()
+        .join("\n");
+    Ok(McpToolCallResponse {
+        is_error: result
+            .get("isError")
+            .and_then(|value| value.as_bool())
+            .unwrap_or(false),
+        content,
+        text,
+    })
+}
+
 #[command]
 pub async fn agent_import_skill(
     app: AppHandle,
@@ -2430,6 +6553,20 @@ pub async fn agent_import_skill(
     read_skill_manifest(&dst, "user")
 }
 
+/// Re-reads every builtin and user skill manifest and reports each result, including
+/// the skills `load_tooling_config` drops because they fail to parse. The active
+/// config is left untouched.
+#[command]
+pub async fn agent_validate_skills(app: AppHandle) -> Result<Vec<SkillValidationResult>, String> {
+    let mut results = Vec::new();
+    if let Some(root) = builtin_skills_root() {
+        results.extend(validate_skills_in_dir(&root, "builtin"));
+    }
+    let user_root = ensure_user_skills_dir(&app)?;
+    results.extend(validate_skills_in_dir(&user_root, "user"));
+    Ok(results)
+}
+
 #[command]
 pub async fn agent_toggle_skill(app: AppHandle, request: ToggleSkillRequest) -> Result<(), String> {
     let user_skills_root = ensure_user_skills_dir(&app)?;
@@ -2485,7 +6622,37 @@ pub async fn agent_import_command_markdown(
     app: AppHandle,
     request: ImportCommandMarkdownRequest,
 ) -> Result<AgentCommandConfig, String> {
-    let src_path = PathBuf::from(request.path);
+    let parsed = load_importable_command_markdown(&request.path)?;
+
+    let user_commands_root = ensure_user_commands_dir(&app)?;
+    let file_name = format!("{}.md", sanitize_slug(&parsed.slug));
+    let command_file = user_commands_root.join(file_name);
+    fs::write(command_file, build_command_markdown(&parsed))
+        .map_err(|e| format!("Failed to write imported command file: {}", e))?;
+    Ok(parsed)
+}
+
+/// Parses and validates a command markdown file without writing it to the commands dir.
+#[command]
+pub async fn agent_preview_command_markdown(
+    request: ImportCommandMarkdownRequest,
+) -> Result<CommandMarkdownPreview, String> {
+    Ok(match load_importable_command_markdown(&request.path) {
+        Ok(command) => CommandMarkdownPreview {
+            valid: true,
+            command: Some(command),
+            error: None,
+        },
+        Err(error) => CommandMarkdownPreview {
+            valid: false,
+            command: None,
+            error: Some(error),
+        },
+    })
+}
+
+fn load_importable_command_markdown(path: &str) -> Result<AgentCommandConfig, String> {
+    let src_path = PathBuf::from(path);
     if !src_path.exists() {
         return Err("Command markdown path does not exist".to_string());
     }
@@ -2495,12 +6662,6 @@ pub async fn agent_import_command_markdown(
     let mut parsed = parse_command_markdown(&src_path, "user")?;
     parsed.source = "user".to_string();
     validate_agent_command(&parsed)?;
-
-    let user_commands_root = ensure_user_commands_dir(&app)?;
-    let file_name = format!("{}.md", sanitize_slug(&parsed.slug));
-    let command_file = user_commands_root.join(file_name);
-    fs::write(command_file, build_command_markdown(&parsed))
-        .map_err(|e| format!("Failed to write imported command file: {}", e))?;
     Ok(parsed)
 }
 
@@ -2519,9 +6680,101 @@ pub async fn agent_delete_command(
     Ok(())
 }
 
+/// Packs user MCP servers, commands and skills into a zip with a `manifest.json`.
+/// MCP env secrets are blanked unless `include_secrets` is set.
+#[command]
+pub async fn export_agent_bundle(
+    app: AppHandle,
+    request: ExportAgentBundleRequest,
+) -> Result<AgentBundleSummary, String> {
+    let include_secrets = request.include_secrets.unwrap_or(false);
+    let output_path = PathBuf::from(request.path.trim());
+    if output_path.as_os_str().is_empty() {
+        return Err("导出路径不能为空".to_string());
+    }
+
+    let mut warnings = Vec::new();
+    let mut agent_files = collect_agent_files(&app, &mut warnings)?;
+    if !include_secrets {
+        sanitize_mcp_server_env(&mut agent_files.mcp_servers);
+        warnings.push("MCP 环境变量中的敏感字段已脱敏".to_string());
+    }
+
+    let manifest = json!({
+        "schemaVersion": AGENT_BUNDLE_SCHEMA_VERSION,
+        "exportedAt": chrono::Utc::now().to_rfc3339(),
+        "includeSecrets": include_secrets,
+        "mcpServers": agent_files.mcp_servers.len(),
+        "commands": agent_files.user_commands.len(),
+        "skills": agent_files.user_skills.len(),
+    });
+    let mut entries: Vec<(String, String)> = vec![
+        (
+            "manifest.json".to_string(),
+            serde_json::to_string_pretty(&manifest)
+                .map_err(|e| format!("Failed to serialize bundle manifest: {}", e))?,
+        ),
+        (
+            "mcp/servers.json".to_string(),
+            serde_json::to_string_pretty(&McpServerFile {
+                servers: agent_files.mcp_servers.clone(),
+            })
+            .map_err(|e| format!("Failed to serialize MCP config: {}", e))?,
+        ),
+    ];
+    for file in &agent_files.user_commands {
+        entries.push((format!("commands/{}", file.path), file.content.clone()));
+    }
+    for skill in &agent_files.user_skills {
+        for file in &skill.files {
+            let path = file.path.replace('\\', "/");
+            entries.push((
+                format!("skills/{}/{}", skill.id, path),
+                file.content.clone(),
+            ));
+        }
+    }
+
+    if let Some(parent) = output_path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("创建导出目录失败 ({}): {}", parent.display(), e))?;
+    }
+    write_zip_entries(&output_path, &entries)?;
+
+    Ok(AgentBundleSummary {
+        path: output_path.to_string_lossy().to_string(),
+        mcp_servers: agent_files.mcp_servers.len(),
+        commands: agent_files.user_commands.len(),
+        skills: agent_files.user_skills.len(),
+        warnings,
+    })
+}
+
+/// Unpacks a bundle from `export_agent_bundle`. Every entry goes through the normal
+/// validators; anything whose name/slug/id already exists is skipped, not overwritten.
+#[command]
+pub async fn import_agent_bundle(
+    app: AppHandle,
+    request: ImportAgentBundleRequest,
+) -> Result<AgentBundleSummary, String> {
+    let input_path = PathBuf::from(request.path.trim());
+    let staging_dir = backup_work_dir(&app)?.join(format!(
+        "bundle-{}",
+        chrono::Utc::now().format("%Y%m%d-%H%M%S%3f")
+    ));
+    extract_zip_to_dir(&input_path, &staging_dir)?;
+    let result = import_agent_bundle_from_dir(&app, &staging_dir, &input_path);
+    let _ = fs::remove_dir_all(&staging_dir);
+    result
+}
+
 #[command]
-pub async fn agent_codex_health(request: AgentChatRequest) -> Result<AgentCodexHealth, String> {
-    let binary = resolve_codex_binary(request.settings.codex.binary_path.as_deref());
+pub async fn agent_codex_health(
+    app: AppHandle,
+    request: AgentChatRequest,
+) -> Result<AgentCodexHealth, String> {
+    let settings = resolve_agent_settings(&app, request.settings)?;
+    let binary = resolve_codex_binary(settings.codex.binary_path.as_deref());
     let Ok(binary) = binary else {
         return Ok(AgentCodexHealth {
             found: false,
@@ -2532,12 +6785,8 @@ pub async fn agent_codex_health(request: AgentChatRequest) -> Result<AgentCodexH
         });
     };
 
-    let mcp_available = probe_codex_mcp(&binary, &request.settings.codex)
-        .await
-        .is_ok();
-    let exec_available = probe_codex_exec(&binary, &request.settings.codex)
-        .await
-        .is_ok();
+    let mcp_available = probe_codex_mcp(&binary, &settings.codex).await.is_ok();
+    let exec_available = probe_codex_exec(&binary, &settings.codex).await.is_ok();
 
     Ok(AgentCodexHealth {
         found: true,
@@ -2552,6 +6801,323 @@ pub async fn agent_codex_health(request: AgentChatRequest) -> Result<AgentCodexH
     })
 }
 
+#[command]
+pub async fn get_recent_provider_errors(
+    limit: Option<i64>,
+) -> Result<Vec<AgentProviderError>, String> {
+    let pool = get_db_pool()?;
+    let rows = sqlx::query(
+        "SELECT id, request_id, provider, status, body, created_at
+         FROM agent_provider_errors
+         ORDER BY created_at DESC, id DESC
+         LIMIT ?1",
+    )
+    .bind(limit.unwrap_or(50).clamp(1, 500))
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch provider errors: {}", e))?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| AgentProviderError {
+            id: row.get("id"),
+            request_id: row.get("request_id"),
+            provider: row.get("provider"),
+            status: row.get("status"),
+            body: row.get("body"),
+            created_at: row.get("created_at"),
+        })
+        .collect())
+}
+
+/// Assembles the exact prompt the configured provider would receive, without calling it.
+#[command]
+pub async fn agent_preview_prompt(
+    app: AppHandle,
+    request: AgentChatRequest,
+) -> Result<AgentPromptPreview, String> {
+    let settings = resolve_agent_settings(&app, request.settings)?;
+    let snapshot = build_context_snapshot().await?;
+    build_prompt_preview(&settings, &request.messages, &snapshot)
+}
+
+/// The exact prompt `call_provider` would send for these messages.
+fn build_prompt_preview(
+    settings: &AgentSettings,
+    messages: &[AgentMessage],
+    snapshot: &Value,
+) -> Result<AgentPromptPreview, String> {
+    let provider = settings.provider.clone();
+    match provider.as_str() {
+        "openai" | "minimax" => Ok(AgentPromptPreview {
+            messages: build_chat_messages(messages, snapshot),
+            provider,
+            system: None,
+            prompt: None,
+        }),
+        "anthropic" => Ok(AgentPromptPreview {
+            provider,
+            system: Some(build_anthropic_system(messages, snapshot)),
+            messages: build_anthropic_messages(messages),
+            prompt: None,
+        }),
+        "codex_local" => Ok(AgentPromptPreview {
+            provider,
+            system: None,
+            messages: vec![],
+            prompt: Some(build_codex_prompt(messages, snapshot)),
+        }),
+        _ => Err(format!("Unsupported provider: {}", provider)),
+    }
+}
+
+static OPENAI_TOKENIZER: OnceLock<Option<tiktoken_rs::CoreBPE>> = OnceLock::new();
+
+/// Approximate size of the prompt `agent_chat` would send, so the UI can warn
+/// before a request gets truncated or rejected.
+#[command]
+pub async fn agent_estimate_tokens(
+    app: AppHandle,
+    settings: Option<AgentSettings>,
+    messages: Vec<AgentMessage>,
+) -> Result<AgentTokenEstimate, String> {
+    let settings = resolve_agent_settings(&app, settings)?;
+    let snapshot = build_context_snapshot().await?;
+    let preview = build_prompt_preview(&settings, &messages, &snapshot)?;
+
+    let mut text = String::new();
+    for part in preview.system.iter().chain(preview.prompt.iter()) {
+        text.push_str(part);
+        text.push('\n');
+    }
+    for message in &preview.messages {
+        if let Some(content) = message.get("content").and_then(|value| value.as_str()) {
+            text.push_str(content);
+            text.push('\n');
+        }
+    }
+
+    // Codex runs OpenAI models, so it shares their tokenizer
+    let tokenizer = match preview.provider.as_str() {
+        "openai" | "codex_local" => OPENAI_TOKENIZER
+            .get_or_init(|| tiktoken_rs::o200k_base().ok())
+            .as_ref(),
+        _ => None,
+    };
+    let (tokens, method) = match tokenizer {
+        Some(bpe) => (bpe.encode_with_special_tokens(&text).len(), "tiktoken"),
+        None => (estimate_tokens_heuristic(&text), "heuristic"),
+    };
+
+    Ok(AgentTokenEstimate {
+        provider: preview.provider,
+        method: method.to_string(),
+        tokens,
+        chars: text.chars().count(),
+    })
+}
+
+/// Roughly four Latin characters per token; CJK characters usually cost about a
+/// token each, so they are counted individually.
+fn estimate_tokens_heuristic(text: &str) -> usize {
+    let (cjk, other) = text.chars().fold((0usize, 0usize), |(cjk, other), ch| {
+        if ('\u{2E80}'..='\u{9FFF}').contains(&ch) || ('\u{F900}'..='\u{FAFF}').contains(&ch) {
+            (cjk + 1, other)
+        } else {
+            (cjk, other + 1)
+        }
+    });
+    cjk + other.div_ceil(4)
+}
+
+/// Asks the configured provider for a short prose briefing of today's info items.
+#[command]
+pub async fn summarize_today_info(
+    app: AppHandle,
+    settings: Option<AgentSettings>,
+) -> Result<String, String> {
+    let items = get_today_info_items(None).await?;
+    if items.is_empty() {
+        return Ok("今天还没有抓取到信息，先刷新一下信息源吧。".to_string());
+    }
+
+    let settings = resolve_agent_settings(&app, settings)?;
+    let snapshot = build_context_snapshot().await?;
+    let request_id = format!("summary-{}", new_entity_id());
+    let item_lines = items
+        .iter()
+        .enumerate()
+        .map(|(index, item)| {
+            format!(
+                "{}. {}\n   {}\n   {}",
+                index + 1,
+                item.title,
+                item.summary.as_deref().unwrap_or(""),
+                item.link
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+    let messages = vec![AgentMessage {
+        role: "user".to_string(),
+        content: format!(
+            "请把以下今日信息整理成一段简短的中文简报（不超过 300 字），突出最值得关注的几条。只在 reply 中返回简报正文，actions 必须为空数组。\n\n{}",
+            item_lines
+        ),
+    }];
+
+    let response = call_provider(&app, &request_id, &settings, &messages, &snapshot).await?;
+    Ok(response.reply)
+}
+
+/// Returns the persisted settings with API keys blanked; the keys never leave the backend.
+#[command]
+pub async fn get_agent_settings(app: AppHandle) -> Result<Option<AgentSettings>, String> {
+    Ok(load_agent_settings(&app)?.map(|settings| redact_agent_settings(&settings)))
+}
+
+/// Persists settings encrypted at rest. An empty `apiKey` keeps the stored key for that
+/// provider, so the frontend can save the redacted form it got from `get_agent_settings`.
+#[command]
+pub async fn save_agent_settings(
+    app: AppHandle,
+    settings: AgentSettings,
+) -> Result<AgentSettings, String> {
+    let mut settings = settings;
+    if let Some(existing) = load_agent_settings(&app)? {
+        keep_existing_api_key(&mut settings.openai, &existing.openai);
+        keep_existing_api_key(&mut settings.anthropic, &existing.anthropic);
+        keep_existing_api_key(&mut settings.minimax, &existing.minimax);
+    }
+    write_agent_settings(&app, &settings)?;
+    Ok(redact_agent_settings(&settings))
+}
+
+/// Stores a provider API key in the OS keychain and returns the `keychain:service/account`
+/// reference to save as that provider's `apiKey` instead of the key itself.
+#[command]
+pub async fn set_provider_key_in_keychain(provider: String, key: String) -> Result<String, String> {
+    let provider = provider.trim().to_lowercase();
+    if !matches!(provider.as_str(), "openai" | "anthropic" | "minimax") {
+        return Err(format!("Unsupported provider: {}", provider));
+    }
+    let key = key.trim();
+    if key.is_empty() {
+        return Err("API key is empty".to_string());
+    }
+    let account = format!("provider-{}", provider);
+    keyring::Entry::new(KEYCHAIN_SERVICE, &account)
+        .and_then(|entry| entry.set_password(key))
+        .map_err(|e| format!("Failed to store API key in keychain: {}", e))?;
+    Ok(format!(
+        "{}{}/{}",
+        KEYCHAIN_KEY_PREFIX, KEYCHAIN_SERVICE, account
+    ))
+}
+
+/// Named settings profiles, redacted like `get_agent_settings`. The settings saved via
+/// `save_agent_settings` are listed first as the `default` profile.
+#[command]
+pub async fn list_agent_profiles(app: AppHandle) -> Result<Vec<AgentProfile>, String> {
+    let mut profiles = Vec::new();
+    if let Some(settings) = load_agent_settings(&app)? {
+        profiles.push(AgentProfile {
+            name: DEFAULT_AGENT_PROFILE.to_string(),
+            settings: redact_agent_settings(&settings),
+        });
+    }
+    profiles.extend(
+        load_agent_profiles(&app)?
+            .into_iter()
+            .map(|(name, settings)| AgentProfile {
+                name,
+                settings: redact_agent_settings(&settings),
+            }),
+    );
+    Ok(profiles)
+}
+
+/// Creates or replaces a named profile. As with `save_agent_settings`, an empty
+/// `apiKey` keeps the key already stored for that profile; saving `default` is the
+/// same as `save_agent_settings`.
+#[command]
+pub async fn save_agent_profile(
+    app: AppHandle,
+    name: String,
+    settings: AgentSettings,
+) -> Result<AgentSettings, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Profile 名称不能为空".to_string());
+    }
+    if name.chars().count() > MAX_AGENT_PROFILE_NAME_CHARS {
+        return Err(format!(
+            "Profile 名称不能超过 {} 个字符",
+            MAX_AGENT_PROFILE_NAME_CHARS
+        ));
+    }
+    if name == DEFAULT_AGENT_PROFILE {
+        return save_agent_settings(app, settings).await;
+    }
+    let mut settings = settings;
+    let mut profiles = load_agent_profiles(&app)?;
+    if let Some(existing) = profiles.get(name) {
+        keep_existing_api_key(&mut settings.openai, &existing.openai);
+        keep_existing_api_key(&mut settings.anthropic, &existing.anthropic);
+        keep_existing_api_key(&mut settings.minimax, &existing.minimax);
+    }
+    profiles.insert(name.to_string(), settings.clone());
+    write_agent_profiles(&app, &profiles)?;
+    Ok(redact_agent_settings(&settings))
+}
+
+/// Writes the saved settings as plain JSON with every sensitive field blanked, so the file
+/// shows which providers and models are configured without carrying any secrets.
+#[command]
+pub async fn export_agent_settings_template(
+    app: AppHandle,
+    path: String,
+) -> Result<String, String> {
+    let output_path = PathBuf::from(path.trim());
+    if output_path.as_os_str().is_empty() {
+        return Err("导出路径不能为空".to_string());
+    }
+    let settings = load_agent_settings(&app)?
+        .ok_or_else(|| "Agent settings are not configured; save them first".to_string())?;
+    let content = serde_json::to_string_pretty(&redact_agent_settings(&settings))
+        .map_err(|e| format!("Failed to serialize agent settings template: {}", e))?;
+
+    if let Some(parent) = output_path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("创建导出目录失败 ({}): {}", parent.display(), e))?;
+    }
+    fs::write(&output_path, content)
+        .map_err(|e| format!("写入模板文件失败 ({}): {}", output_path.display(), e))?;
+    Ok(output_path.to_string_lossy().to_string())
+}
+
+/// Applies a template from `export_agent_settings_template`. API keys already stored
+/// locally always win over whatever the template carries.
+#[command]
+pub async fn import_agent_settings_template(
+    app: AppHandle,
+    path: String,
+) -> Result<AgentSettings, String> {
+    let input_path = PathBuf::from(path.trim());
+    let content = fs::read_to_string(&input_path)
+        .map_err(|e| format!("读取模板文件失败 ({}): {}", input_path.display(), e))?;
+    let mut settings: AgentSettings =
+        serde_json::from_str(&content).map_err(|e| format!("模板文件解析失败: {}", e))?;
+
+    if let Some(existing) = load_agent_settings(&app)? {
+        prefer_existing_api_key(&mut settings.openai, &existing.openai);
+        prefer_existing_api_key(&mut settings.anthropic, &existing.anthropic);
+        prefer_existing_api_key(&mut settings.minimax, &existing.minimax);
+    }
+    write_agent_settings(&app, &settings)?;
+    Ok(redact_agent_settings(&settings))
+}
+
 fn get_required_str<'a>(payload: &'a Value, key: &str) -> Result<&'a str, String> {
     payload
         .get(key)
@@ -2560,36 +7126,303 @@ fn get_required_str<'a>(payload: &'a Value, key: &str) -> Result<&'a str, String
         .ok_or_else(|| format!("Missing required field: {}", key))
 }
 
-fn get_optional_str<'a>(payload: &'a Value, key: &str) -> Option<&'a str> {
-    payload
-        .get(key)
-        .and_then(|value| value.as_str())
-        .filter(|value| !value.trim().is_empty())
+fn get_optional_str<'a>(payload: &'a Value, key: &str) -> Option<&'a str> {
+    payload
+        .get(key)
+        .and_then(|value| value.as_str())
+        .filter(|value| !value.trim().is_empty())
+}
+
+fn validate_action(action_type: &str, payload: &Value) -> Result<(), String> {
+    if !AGENT_ACTION_SPECS
+        .iter()
+        .any(|spec| spec.action_type == action_type)
+    {
+        return Err(format!("Action is not allowed: {}", action_type));
+    }
+    if !payload.is_object() {
+        return Err("Action payload must be an object".to_string());
+    }
+    Ok(())
+}
+
+struct ActionFieldSpec {
+    name: &'static str,
+    kind: &'static str,
+    required: bool,
+}
+
+struct ActionSpec {
+    action_type: &'static str,
+    fields: &'static [ActionFieldSpec],
+}
+
+const fn required(name: &'static str, kind: &'static str) -> ActionFieldSpec {
+    ActionFieldSpec {
+        name,
+        kind,
+        required: true,
+    }
+}
+
+const fn optional(name: &'static str, kind: &'static str) -> ActionFieldSpec {
+    ActionFieldSpec {
+        name,
+        kind,
+        required: false,
+    }
+}
+
+/// Every action type the agent may propose, with the payload fields the executors
+/// read. This is the allow-list for `validate_action` and the source of
+/// `agent_get_action_schemas`, so adding an action starts here.
+const AGENT_ACTION_SPECS: &[ActionSpec] = &[
+    ActionSpec {
+        action_type: "todo.create",
+        fields: &[
+            required("title", "string"),
+            optional("priority", "string"),
+            optional("id", "string"),
+        ],
+    },
+    ActionSpec {
+        action_type: "todo.bulk_create",
+        fields: &[required("titles", "array"), optional("priority", "string")],
+    },
+    ActionSpec {
+        action_type: "todo.update",
+        fields: &[
+            required("id", "string"),
+            optional("title", "string"),
+            optional("completed", "boolean"),
+            optional("priority", "string"),
+        ],
+    },
+    ActionSpec {
+        action_type: "todo.delete",
+        fields: &[required("id", "string")],
+    },
+    ActionSpec {
+        action_type: "project.create",
+        fields: &[
+            required("title", "string"),
+            required("deadline", "string"),
+            optional("id", "string"),
+        ],
+    },
+    ActionSpec {
+        action_type: "project.update_progress",
+        fields: &[required("id", "string"), required("progress", "integer")],
+    },
+    ActionSpec {
+        action_type: "project.delete",
+        fields: &[required("id", "string")],
+    },
+    ActionSpec {
+        action_type: "event.create",
+        fields: &[
+            required("title", "string"),
+            required("date", "string"),
+            optional("color", "string"),
+            optional("note", "string"),
+            optional("id", "string"),
+        ],
+    },
+    ActionSpec {
+        action_type: "event.update",
+        fields: &[
+            required("id", "string"),
+            optional("title", "string"),
+            optional("date", "string"),
+            optional("color", "string"),
+            optional("note", "string"),
+        ],
+    },
+    ActionSpec {
+        action_type: "event.delete",
+        fields: &[required("id", "string")],
+    },
+    ActionSpec {
+        action_type: "personal.create",
+        fields: &[
+            required("title", "string"),
+            optional("budget", "number"),
+            optional("date", "string"),
+            optional("location", "string"),
+            optional("note", "string"),
+            optional("completed", "boolean"),
+            optional("id", "string"),
+        ],
+    },
+    ActionSpec {
+        action_type: "personal.update",
+        fields: &[
+            required("id", "string"),
+            optional("title", "string"),
+            optional("budget", "number"),
+            optional("date", "string"),
+            optional("location", "string"),
+            optional("note", "string"),
+            optional("completed", "boolean"),
+        ],
+    },
+    ActionSpec {
+        action_type: "personal.delete",
+        fields: &[required("id", "string")],
+    },
+    ActionSpec {
+        action_type: "personal.to_event",
+        fields: &[
+            required("id", "string"),
+            optional("markCompleted", "boolean"),
+        ],
+    },
+    ActionSpec {
+        action_type: "info.update_settings",
+        fields: &[
+            optional("pushTime", "string"),
+            optional("includeKeywords", "array"),
+            optional("excludeKeywords", "array"),
+            optional("maxItemsPerDay", "integer"),
+            optional("maxConcurrentFetches", "integer"),
+            optional("retentionDays", "integer"),
+            optional("quietHoursStart", "string"),
+            optional("quietHoursEnd", "string"),
+        ],
+    },
+    ActionSpec {
+        action_type: "query.snapshot",
+        fields: &[],
+    },
+    ActionSpec {
+        action_type: "todo.list",
+        fields: &[
+            optional("completed", "boolean"),
+            optional("priority", "string"),
+            optional("limit", "integer"),
+        ],
+    },
+    ActionSpec {
+        action_type: "project.list",
+        fields: &[optional("status", "string"), optional("limit", "integer")],
+    },
+    ActionSpec {
+        action_type: "event.list_by_date",
+        fields: &[required("date", "string"), optional("limit", "integer")],
+    },
+];
+
+/// Actions that only read data. They run outside the rollback logic and their
+/// results are fed back to the model in a follow-up turn.
+const AGENT_READ_ACTIONS: [&str; 3] = ["todo.list", "project.list", "event.list_by_date"];
+const AGENT_READ_DEFAULT_LIMIT: i64 = 50;
+/// Actions that change a global preference. They are never auto-executed and always
+/// wait for the user to confirm them.
+const AGENT_APPROVAL_ACTIONS: [&str; 1] = ["info.update_settings"];
+const AGENT_READ_MAX_LIMIT: i64 = 200;
+
+fn is_read_action(action_type: &str) -> bool {
+    AGENT_READ_ACTIONS.contains(&action_type)
+}
+
+fn is_approval_action(action_type: &str) -> bool {
+    AGENT_APPROVAL_ACTIONS.contains(&action_type)
 }
 
-fn validate_action(action_type: &str, payload: &Value) -> Result<(), String> {
-    let allowed = [
-        "todo.create",
-        "todo.update",
-        "todo.delete",
-        "project.create",
-        "project.update_progress",
-        "project.delete",
-        "event.create",
-        "event.update",
-        "event.delete",
-        "personal.create",
-        "personal.update",
-        "personal.delete",
-        "query.snapshot",
-    ];
-    if !allowed.contains(&action_type) {
-        return Err(format!("Action is not allowed: {}", action_type));
-    }
-    if !payload.is_object() {
-        return Err("Action payload must be an object".to_string());
+async fn execute_read_action(
+    conn: &mut sqlx::SqliteConnection,
+    action: &AgentActionProposal,
+) -> Result<Value, String> {
+    let limit = action
+        .payload
+        .get("limit")
+        .and_then(|value| value.as_i64())
+        .unwrap_or(AGENT_READ_DEFAULT_LIMIT)
+        .clamp(1, AGENT_READ_MAX_LIMIT);
+    match action.r#type.as_str() {
+        "todo.list" => {
+            let completed = action
+                .payload
+                .get("completed")
+                .and_then(|value| value.as_bool());
+            let priority = get_optional_str(&action.payload, "priority")
+                .map(normalize_todo_priority)
+                .transpose()?;
+            let mut sql = "SELECT id, title, completed, priority, created_at, completed_at, project_id, snoozed_until, pinned FROM todos WHERE 1 = 1".to_string();
+            if completed.is_some() {
+                sql.push_str(" AND completed = ?");
+            }
+            if priority.is_some() {
+                sql.push_str(" AND priority = ?");
+            }
+            sql.push_str(" ORDER BY created_at DESC LIMIT ?");
+            let mut query = sqlx::query(&sql);
+            if let Some(value) = completed {
+                query = query.bind(if value { 1 } else { 0 });
+            }
+            if let Some(value) = &priority {
+                query = query.bind(value);
+            }
+            let rows = query
+                .bind(limit)
+                .fetch_all(&mut *conn)
+                .await
+                .map_err(|e| format!("Failed to list todos: {}", e))?;
+            let todos: Vec<Todo> = rows.into_iter().map(row_to_todo).collect();
+            Ok(json!({ "count": todos.len(), "todos": todos }))
+        }
+        "project.list" => {
+            let status = get_optional_str(&action.payload, "status");
+            let mut sql =
+                "SELECT id, title, deadline, progress, status, pinned FROM projects WHERE 1 = 1"
+                    .to_string();
+            if status.is_some() {
+                sql.push_str(" AND status = ?");
+            }
+            sql.push_str(" ORDER BY deadline LIMIT ?");
+            let mut query = sqlx::query(&sql);
+            if let Some(value) = status {
+                query = query.bind(value);
+            }
+            let rows = query
+                .bind(limit)
+                .fetch_all(&mut *conn)
+                .await
+                .map_err(|e| format!("Failed to list projects: {}", e))?;
+            let projects: Vec<Project> = rows.into_iter().map(row_to_project).collect();
+            Ok(json!({ "count": projects.len(), "projects": projects }))
+        }
+        "event.list_by_date" => {
+            let date = normalize_event_date(get_required_str(&action.payload, "date")?)?;
+            let rows = sqlx::query(
+                "SELECT id, title, date, color, note, pinned FROM events WHERE date = ?1 ORDER BY title LIMIT ?2",
+            )
+            .bind(&date)
+            .bind(limit)
+            .fetch_all(&mut *conn)
+            .await
+            .map_err(|e| format!("Failed to list events: {}", e))?;
+            let events: Vec<CalendarEvent> = rows.into_iter().map(row_to_event).collect();
+            Ok(json!({ "date": date, "count": events.len(), "events": events }))
+        }
+        _ => Err(format!("Unsupported read action: {}", action.r#type)),
     }
-    Ok(())
+}
+
+/// Collects successful read results from an executed batch for the follow-up turn.
+fn collect_read_results(records: &[AgentExecutionAuditRecord]) -> Vec<Value> {
+    records
+        .iter()
+        .filter(|record| record.success && is_read_action(&record.action_type))
+        .filter_map(|record| {
+            let result = record.after_state.as_ref()?.get("result")?;
+            Some(json!({
+                "type": record.action_type,
+                "payload": record.payload,
+                "result": result
+            }))
+        })
+        .collect()
 }
 
 async fn execute_action_with_transaction(
@@ -2599,22 +7432,28 @@ async fn execute_action_with_transaction(
     match action.r#type.as_str() {
         "todo.create" => {
             let title = get_required_str(&action.payload, "title")?;
-            let priority = get_optional_str(&action.payload, "priority").unwrap_or("normal");
+            let priority = normalize_todo_priority(
+                get_optional_str(&action.payload, "priority").unwrap_or("normal"),
+            )?;
             let id = action
                 .payload
                 .get("id")
                 .and_then(|item| item.as_str())
-                .unwrap_or(&chrono::Utc::now().timestamp_millis().to_string())
-                .to_string();
+                .map(str::to_string)
+                .unwrap_or_else(new_entity_id);
             sqlx::query("INSERT INTO todos (id, title, priority) VALUES (?1, ?2, ?3)")
                 .bind(&id)
                 .bind(title)
-                .bind(priority)
+                .bind(&priority)
                 .execute(&mut **tx)
                 .await
                 .map_err(|e| format!("Failed to create todo: {}", e))?;
             Ok("待办已创建".to_string())
         }
+        "todo.bulk_create" => {
+            let created = bulk_create_todos(tx, &action.payload).await?;
+            Ok(format!("已批量创建 {} 条待办", created))
+        }
         "todo.update" => {
             let id = get_required_str(&action.payload, "id")?;
             let title = get_optional_str(&action.payload, "title");
@@ -2622,34 +7461,18 @@ async fn execute_action_with_transaction(
                 .payload
                 .get("completed")
                 .and_then(|value| value.as_bool());
-            let priority = get_optional_str(&action.payload, "priority");
+            let priority = get_optional_str(&action.payload, "priority")
+                .map(normalize_todo_priority)
+                .transpose()?;
             if title.is_none() && completed.is_none() && priority.is_none() {
                 return Err("todo.update 缺少可更新字段".to_string());
             }
-            let mut updates: Vec<String> = Vec::new();
-            if title.is_some() {
-                updates.push("title = ?".to_string());
-            }
-            if completed.is_some() {
-                updates.push("completed = ?".to_string());
-            }
-            if priority.is_some() {
-                updates.push("priority = ?".to_string());
-            }
-            let query = format!("UPDATE todos SET {} WHERE id = ?", updates.join(", "));
-            let mut query_builder = sqlx::query(&query);
-            if let Some(value) = title {
-                query_builder = query_builder.bind(value);
-            }
-            if let Some(value) = completed {
-                query_builder = query_builder.bind(if value { 1 } else { 0 });
-            }
-            if let Some(value) = priority {
-                query_builder = query_builder.bind(value);
-            }
-            query_builder = query_builder.bind(id);
-            query_builder
-                .execute(&mut **tx)
+            let mut updates = UpdateBuilder::new();
+            updates.set_opt("title", title);
+            updates.set_completed(completed);
+            updates.set_opt("priority", priority);
+            updates
+                .execute("todos", id, &mut **tx)
                 .await
                 .map_err(|e| format!("Failed to update todo: {}", e))?;
             Ok("待办已更新".to_string())
@@ -2670,8 +7493,8 @@ async fn execute_action_with_transaction(
                 .payload
                 .get("id")
                 .and_then(|item| item.as_str())
-                .unwrap_or(&chrono::Utc::now().timestamp_millis().to_string())
-                .to_string();
+                .map(str::to_string)
+                .unwrap_or_else(new_entity_id);
             sqlx::query(
                 "INSERT INTO projects (id, title, deadline, progress, status) VALUES (?1, ?2, ?3, 0, 'active')",
             )
@@ -2709,15 +7532,17 @@ async fn execute_action_with_transaction(
         }
         "event.create" => {
             let title = get_required_str(&action.payload, "title")?;
-            let date = get_required_str(&action.payload, "date")?;
-            let color = get_optional_str(&action.payload, "color").unwrap_or("blue");
+            let date = normalize_event_date(get_required_str(&action.payload, "date")?)?;
+            let (color, color_warning) = get_optional_str(&action.payload, "color")
+                .map(normalize_event_color)
+                .unwrap_or(("blue", None));
             let note = get_optional_str(&action.payload, "note");
             let id = action
                 .payload
                 .get("id")
                 .and_then(|item| item.as_str())
-                .unwrap_or(&chrono::Utc::now().timestamp_millis().to_string())
-                .to_string();
+                .map(str::to_string)
+                .unwrap_or_else(new_entity_id);
             sqlx::query(
                 "INSERT INTO events (id, title, date, color, note) VALUES (?1, ?2, ?3, ?4, ?5)",
             )
@@ -2729,50 +7554,32 @@ async fn execute_action_with_transaction(
             .execute(&mut **tx)
             .await
             .map_err(|e| format!("Failed to create event: {}", e))?;
-            Ok("日程已创建".to_string())
+            Ok(with_color_warning("日程已创建", color_warning))
         }
         "event.update" => {
             let id = get_required_str(&action.payload, "id")?;
             let title = get_optional_str(&action.payload, "title");
-            let date = get_optional_str(&action.payload, "date");
-            let color = get_optional_str(&action.payload, "color");
+            let date = get_optional_str(&action.payload, "date")
+                .map(normalize_event_date)
+                .transpose()?;
+            let color = get_optional_str(&action.payload, "color").map(normalize_event_color);
             let note = get_optional_str(&action.payload, "note");
             if title.is_none() && date.is_none() && color.is_none() && note.is_none() {
                 return Err("event.update 缺少可更新字段".to_string());
             }
-            let mut updates: Vec<String> = Vec::new();
-            if title.is_some() {
-                updates.push("title = ?".to_string());
-            }
-            if date.is_some() {
-                updates.push("date = ?".to_string());
-            }
-            if color.is_some() {
-                updates.push("color = ?".to_string());
-            }
-            if note.is_some() {
-                updates.push("note = ?".to_string());
-            }
-            let query = format!("UPDATE events SET {} WHERE id = ?", updates.join(", "));
-            let mut query_builder = sqlx::query(&query);
-            if let Some(value) = title {
-                query_builder = query_builder.bind(value);
-            }
-            if let Some(value) = date {
-                query_builder = query_builder.bind(value);
-            }
-            if let Some(value) = color {
-                query_builder = query_builder.bind(value);
-            }
-            if let Some(value) = note {
-                query_builder = query_builder.bind(value);
-            }
-            query_builder = query_builder.bind(id);
-            query_builder
-                .execute(&mut **tx)
+            let mut updates = UpdateBuilder::new();
+            updates.set_opt("title", title);
+            updates.set_opt("date", date);
+            updates.set_opt("color", color.as_ref().map(|(color, _)| *color));
+            updates.set_opt("note", note);
+            updates
+                .execute("events", id, &mut **tx)
                 .await
                 .map_err(|e| format!("Failed to update event: {}", e))?;
-            Ok("日程已更新".to_string())
+            Ok(with_color_warning(
+                "日程已更新",
+                color.and_then(|(_, warning)| warning),
+            ))
         }
         "event.delete" => {
             let id = get_required_str(&action.payload, "id")?;
@@ -2789,8 +7596,8 @@ async fn execute_action_with_transaction(
                 .payload
                 .get("id")
                 .and_then(|item| item.as_str())
-                .unwrap_or(&chrono::Utc::now().timestamp_millis().to_string())
-                .to_string();
+                .map(str::to_string)
+                .unwrap_or_else(new_entity_id);
             let budget = action
                 .payload
                 .get("budget")
@@ -2798,8 +7605,13 @@ async fn execute_action_with_transaction(
             let date = get_optional_str(&action.payload, "date");
             let location = get_optional_str(&action.payload, "location");
             let note = get_optional_str(&action.payload, "note");
+            let completed = action
+                .payload
+                .get("completed")
+                .and_then(|value| value.as_bool())
+                .unwrap_or(false);
             sqlx::query(
-                "INSERT INTO personal_tasks (id, title, budget, date, location, note) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                "INSERT INTO personal_tasks (id, title, budget, date, location, note, completed, completed_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, CASE WHEN ?7 = 1 THEN CURRENT_TIMESTAMP END)",
             )
             .bind(&id)
             .bind(title)
@@ -2807,6 +7619,7 @@ async fn execute_action_with_transaction(
             .bind(date)
             .bind(location)
             .bind(note)
+            .bind(if completed { 1 } else { 0 })
             .execute(&mut **tx)
             .await
             .map_err(|e| format!("Failed to create personal task: {}", e))?;
@@ -2822,57 +7635,47 @@ async fn execute_action_with_transaction(
             let date = get_optional_str(&action.payload, "date");
             let location = get_optional_str(&action.payload, "location");
             let note = get_optional_str(&action.payload, "note");
+            let completed = action
+                .payload
+                .get("completed")
+                .and_then(|value| value.as_bool());
             if title.is_none()
                 && budget.is_none()
                 && date.is_none()
                 && location.is_none()
                 && note.is_none()
+                && completed.is_none()
             {
                 return Err("personal.update 缺少可更新字段".to_string());
             }
-            let mut updates: Vec<String> = Vec::new();
-            if title.is_some() {
-                updates.push("title = ?".to_string());
-            }
-            if budget.is_some() {
-                updates.push("budget = ?".to_string());
-            }
-            if date.is_some() {
-                updates.push("date = ?".to_string());
-            }
-            if location.is_some() {
-                updates.push("location = ?".to_string());
-            }
-            if note.is_some() {
-                updates.push("note = ?".to_string());
-            }
-            let query = format!(
-                "UPDATE personal_tasks SET {} WHERE id = ?",
-                updates.join(", ")
-            );
-            let mut query_builder = sqlx::query(&query);
-            if let Some(value) = title {
-                query_builder = query_builder.bind(value);
-            }
-            if let Some(value) = budget {
-                query_builder = query_builder.bind(value);
-            }
-            if let Some(value) = date {
-                query_builder = query_builder.bind(value);
-            }
-            if let Some(value) = location {
-                query_builder = query_builder.bind(value);
-            }
-            if let Some(value) = note {
-                query_builder = query_builder.bind(value);
-            }
-            query_builder = query_builder.bind(id);
-            query_builder
-                .execute(&mut **tx)
+            let mut updates = UpdateBuilder::new();
+            updates.set_opt("title", title);
+            updates.set_opt("budget", budget);
+            updates.set_opt("date", date);
+            updates.set_opt("location", location);
+            updates.set_opt("note", note);
+            updates.set_completed(completed);
+            updates
+                .execute("personal_tasks", id, &mut **tx)
                 .await
                 .map_err(|e| format!("Failed to update personal task: {}", e))?;
             Ok("个人事务已更新".to_string())
         }
+        "personal.to_event" => {
+            let id = get_required_str(&action.payload, "id")?;
+            let mark_completed = action
+                .payload
+                .get("markCompleted")
+                .and_then(|value| value.as_bool())
+                .unwrap_or(false);
+            convert_personal_task_to_event(tx, id, mark_completed).await?;
+            Ok("已将个人事务转为日程".to_string())
+        }
+        "info.update_settings" => {
+            let request = info_settings_request_from_payload(&action.payload).await?;
+            upsert_info_settings(&mut **tx, request).await?;
+            Ok("信息设置已更新".to_string())
+        }
         "personal.delete" => {
             let id = get_required_str(&action.payload, "id")?;
             sqlx::query("DELETE FROM personal_tasks WHERE id = ?1")
@@ -2889,7 +7692,7 @@ async fn execute_action_with_transaction(
 
 async fn build_context_snapshot() -> Result<Value, String> {
     let pool = get_db_pool()?;
-    let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+    let today = local_today_string();
 
     let pending_todos = sqlx::query("SELECT id, title, priority FROM todos WHERE completed = 0 ORDER BY created_at DESC LIMIT 8")
         .fetch_all(pool)
@@ -2900,14 +7703,14 @@ async fn build_context_snapshot() -> Result<Value, String> {
         .await
         .map_err(|e| format!("Failed to fetch projects snapshot: {}", e))?;
     let today_events = sqlx::query(
-        "SELECT id, title, date, color, note FROM events WHERE date = ?1 ORDER BY date LIMIT 10",
+        "SELECT id, title, date, color, note, pinned FROM events WHERE date = ?1 ORDER BY date LIMIT 10",
     )
     .bind(&today)
     .fetch_all(pool)
     .await
     .map_err(|e| format!("Failed to fetch events snapshot: {}", e))?;
     let personal_tasks =
-        sqlx::query("SELECT id, title, date, budget FROM personal_tasks ORDER BY date LIMIT 8")
+        sqlx::query("SELECT id, title, date, budget FROM personal_tasks WHERE completed = 0 ORDER BY date LIMIT 8")
             .fetch_all(pool)
             .await
             .map_err(|e| format!("Failed to fetch personal snapshot: {}", e))?;
@@ -2979,72 +7782,151 @@ fn local_fallback_response(
     AgentChatResponse {
         reply,
         actions: vec![AgentActionProposal {
-            id: format!("snapshot-{}", chrono::Utc::now().timestamp_millis()),
+            id: format!("snapshot-{}", new_entity_id()),
             r#type: "query.snapshot".to_string(),
             title: "生成当前快照".to_string(),
             reason: "用于后续进一步规划和动作确认".to_string(),
             payload: json!({}),
             requires_approval: true,
         }],
+        plan: vec![],
+    }
+}
+
+const SUPPORTED_AGENT_PROVIDERS: &[&str] = &["openai", "anthropic", "minimax", "codex_local"];
+
+/// Applies a per-request provider/model override on a copy of the settings. The
+/// provider itself is validated later in `call_provider`.
+fn apply_provider_override(
+    mut settings: AgentSettings,
+    provider_override: Option<&str>,
+    model_override: Option<&str>,
+) -> AgentSettings {
+    if let Some(provider) = provider_override.map(str::trim).filter(|p| !p.is_empty()) {
+        settings.provider = provider.to_string();
+    }
+    let Some(model) = model_override.map(str::trim).filter(|m| !m.is_empty()) else {
+        return settings;
+    };
+    match settings.provider.as_str() {
+        "openai" => settings.openai.model = model.to_string(),
+        "anthropic" => settings.anthropic.model = model.to_string(),
+        "minimax" => settings.minimax.model = model.to_string(),
+        "codex_local" => {
+            // Codex has no model field in its config; pass it through the CLI instead.
+            if settings.codex.exec_args.is_empty() {
+                settings.codex.exec_args = default_codex_exec_args();
+            }
+            settings
+                .codex
+                .exec_args
+                .extend(["--model".to_string(), model.to_string()]);
+        }
+        _ => {}
     }
+    settings
 }
 
 async fn call_provider(
     app: &AppHandle,
     request_id: &str,
-    request: &AgentChatRequest,
+    settings: &AgentSettings,
+    messages: &[AgentMessage],
     snapshot: &Value,
 ) -> Result<AgentChatResponse, String> {
-    let provider = request.settings.provider.as_str();
+    let provider = settings.provider.as_str();
+    if !SUPPORTED_AGENT_PROVIDERS.contains(&provider) {
+        return Err(format!(
+            "Unsupported provider: {} (expected one of: {})",
+            provider,
+            SUPPORTED_AGENT_PROVIDERS.join(", ")
+        ));
+    }
+    let requests_per_minute = match provider {
+        "openai" => settings.openai.requests_per_minute,
+        "anthropic" => settings.anthropic.requests_per_minute,
+        "minimax" => settings.minimax.requests_per_minute,
+        _ => None,
+    };
+    if let Some(requests_per_minute) = requests_per_minute {
+        acquire_provider_slot(provider, requests_per_minute)?;
+    }
     match provider {
-        "openai" => call_openai(request, snapshot).await,
-        "anthropic" => call_anthropic(request, snapshot).await,
-        "minimax" => call_minimax(request, snapshot).await,
-        "codex_local" => call_codex_local(app, request_id, request, snapshot).await,
+        "openai" => call_openai(request_id, settings, messages, snapshot).await,
+        "anthropic" => call_anthropic(request_id, settings, messages, snapshot).await,
+        "minimax" => call_minimax(request_id, settings, messages, snapshot).await,
+        "codex_local" => call_codex_local(app, request_id, settings, messages, snapshot).await,
         _ => Err(format!("Unsupported provider: {}", provider)),
     }
 }
 
+struct ProviderRateBucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+/// Token bucket holding up to `requests_per_minute` requests, refilled continuously.
+/// Rejects instead of queuing so a burst surfaces immediately in the chat.
+fn acquire_provider_slot(provider: &str, requests_per_minute: u32) -> Result<(), String> {
+    if requests_per_minute == 0 {
+        return Ok(());
+    }
+    let capacity = requests_per_minute as f64;
+    let per_second = capacity / 60.0;
+    let mut buckets = PROVIDER_RATE_LIMITS
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .map_err(|_| "Provider rate limiter poisoned".to_string())?;
+    let now = Instant::now();
+    let bucket = buckets
+        .entry(provider.to_string())
+        .or_insert(ProviderRateBucket {
+            tokens: capacity,
+            refilled_at: now,
+        });
+    let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+    bucket.tokens = (bucket.tokens + elapsed * per_second).min(capacity);
+    bucket.refilled_at = now;
+
+    if bucket.tokens < 1.0 {
+        let wait_secs = ((1.0 - bucket.tokens) / per_second).ceil();
+        return Err(format!(
+            "Rate limited locally: {} allows {} requests per minute, retry in {}s",
+            provider, requests_per_minute, wait_secs
+        ));
+    }
+    bucket.tokens -= 1.0;
+    Ok(())
+}
+
 async fn call_openai(
-    request: &AgentChatRequest,
+    request_id: &str,
+    settings: &AgentSettings,
+    messages: &[AgentMessage],
     snapshot: &Value,
 ) -> Result<AgentChatResponse, String> {
-    let config = &request.settings.openai;
-    if config.api_key.trim().is_empty() {
+    let config = &settings.openai;
+    let api_key = resolve_provider_api_key(config)?;
+    if api_key.is_empty() {
         return Err("OpenAI API key is empty".to_string());
     }
 
     let endpoint = format!("{}/chat/completions", config.base_url.trim_end_matches('/'));
+    let request_messages = build_chat_messages(messages, snapshot);
 
-    let messages = request
-        .messages
-        .iter()
-        .map(|message| {
-            json!({
-                "role": if message.role == "assistant" { "assistant" } else { "user" },
-                "content": message.content,
-            })
-        })
-        .collect::<Vec<Value>>();
-
-    let mut request_messages = vec![json!({
-        "role": "system",
-        "content": build_system_prompt(snapshot),
-    })];
-    request_messages.extend(messages);
-
-    let client = reqwest::Client::new();
+    let client = http_client()?;
     let response = client
         .post(endpoint)
-        .bearer_auth(config.api_key.trim())
+        .bearer_auth(&api_key)
         .json(&json!({
             "model": config.model,
             "temperature": 0.2,
             "messages": request_messages,
         }))
+        .timeout(provider_timeout(config))
         .send()
         .await
-        .map_err(|e| format!("OpenAI request failed: {}", e))?;
+        .map_err(|e| provider_request_error("OpenAI", config, e))?;
 
     if !response.status().is_success() {
         let status = response.status();
@@ -3052,6 +7934,7 @@ async fn call_openai(
             .text()
             .await
             .unwrap_or_else(|_| "no body".to_string());
+        persist_provider_error(request_id, "openai", Some(status.as_u16()), &body).await;
         return Err(format!("OpenAI error {}: {}", status, body));
     }
 
@@ -3071,12 +7954,57 @@ async fn call_openai(
     parse_llm_response(content)
 }
 
+/// The provider's API key, read from the OS keychain when `apiKey` holds a
+/// `keychain:service/account` reference and used literally otherwise.
+fn resolve_provider_api_key(config: &AgentProviderConfig) -> Result<String, String> {
+    let value = config.api_key.trim();
+    let Some(reference) = value.strip_prefix(KEYCHAIN_KEY_PREFIX) else {
+        return Ok(value.to_string());
+    };
+    let (service, account) = reference
+        .split_once('/')
+        .filter(|(service, account)| !service.is_empty() && !account.is_empty())
+        .ok_or_else(|| format!("Invalid keychain reference: {}", value))?;
+    keyring::Entry::new(service, account)
+        .and_then(|entry| entry.get_password())
+        .map(|key| key.trim().to_string())
+        .map_err(|e| {
+            format!(
+                "Failed to read API key from keychain ({}): {}",
+                reference, e
+            )
+        })
+}
+
+fn provider_timeout(config: &AgentProviderConfig) -> Duration {
+    Duration::from_millis(config.request_timeout_ms.max(1000))
+}
+
+fn provider_request_error(
+    provider: &str,
+    config: &AgentProviderConfig,
+    error: reqwest::Error,
+) -> String {
+    if error.is_timeout() {
+        format!(
+            "{} request timed out after {} ms",
+            provider,
+            provider_timeout(config).as_millis()
+        )
+    } else {
+        format!("{} request failed: {}", provider, error)
+    }
+}
+
 async fn call_anthropic(
-    request: &AgentChatRequest,
+    request_id: &str,
+    settings: &AgentSettings,
+    messages: &[AgentMessage],
     snapshot: &Value,
 ) -> Result<AgentChatResponse, String> {
-    let config = &request.settings.anthropic;
-    if config.api_key.trim().is_empty() {
+    let config = &settings.anthropic;
+    let api_key = resolve_provider_api_key(config)?;
+    if api_key.is_empty() {
         return Err("Anthropic API key is empty".to_string());
     }
 
@@ -3086,39 +8014,25 @@ async fn call_anthropic(
         .clone()
         .unwrap_or_else(|| "2023-06-01".to_string());
 
-    let messages = request
-        .messages
-        .iter()
-        .filter_map(|message| {
-            let role = if message.role == "assistant" {
-                Some("assistant")
-            } else if message.role == "user" || message.role == "system" {
-                Some("user")
-            } else {
-                None
-            }?;
-            Some(json!({
-                "role": role,
-                "content": message.content,
-            }))
-        })
-        .collect::<Vec<Value>>();
+    let system = build_anthropic_system(messages, snapshot);
+    let messages = build_anthropic_messages(messages);
 
-    let client = reqwest::Client::new();
+    let client = http_client()?;
     let response = client
         .post(endpoint)
-        .header("x-api-key", config.api_key.trim())
+        .header("x-api-key", &api_key)
         .header("anthropic-version", anthropic_version)
         .json(&json!({
             "model": config.model,
             "max_tokens": 1200,
             "temperature": 0.2,
-            "system": build_system_prompt(snapshot),
+            "system": system,
             "messages": messages,
         }))
+        .timeout(provider_timeout(config))
         .send()
         .await
-        .map_err(|e| format!("Anthropic request failed: {}", e))?;
+        .map_err(|e| provider_request_error("Anthropic", config, e))?;
 
     if !response.status().is_success() {
         let status = response.status();
@@ -3126,6 +8040,7 @@ async fn call_anthropic(
             .text()
             .await
             .unwrap_or_else(|_| "no body".to_string());
+        persist_provider_error(request_id, "anthropic", Some(status.as_u16()), &body).await;
         return Err(format!("Anthropic error {}: {}", status, body));
     }
 
@@ -3145,11 +8060,14 @@ async fn call_anthropic(
 }
 
 async fn call_minimax(
-    request: &AgentChatRequest,
+    request_id: &str,
+    settings: &AgentSettings,
+    messages: &[AgentMessage],
     snapshot: &Value,
 ) -> Result<AgentChatResponse, String> {
-    let config = &request.settings.minimax;
-    if config.api_key.trim().is_empty() {
+    let config = &settings.minimax;
+    let api_key = resolve_provider_api_key(config)?;
+    if api_key.is_empty() {
         return Err("MiniMax API key is empty".to_string());
     }
 
@@ -3157,28 +8075,12 @@ async fn call_minimax(
         "{}/text/chatcompletion_v2",
         config.base_url.trim_end_matches('/')
     );
+    let request_messages = build_chat_messages(messages, snapshot);
 
-    let mut request_messages = vec![json!({
-        "role": "system",
-        "content": build_system_prompt(snapshot),
-    })];
-    request_messages.extend(
-        request
-            .messages
-            .iter()
-            .map(|message| {
-                json!({
-                    "role": if message.role == "assistant" { "assistant" } else { "user" },
-                    "content": message.content,
-                })
-            })
-            .collect::<Vec<Value>>(),
-    );
-
-    let client = reqwest::Client::new();
+    let client = http_client()?;
     let response = client
         .post(endpoint)
-        .header("Authorization", format!("Bearer {}", config.api_key.trim()))
+        .header("Authorization", format!("Bearer {}", api_key))
         .header("Content-Type", "application/json")
         .json(&json!({
             "model": config.model,
@@ -3187,9 +8089,10 @@ async fn call_minimax(
             "temperature": 0.2,
             "max_tokens": 1200,
         }))
+        .timeout(provider_timeout(config))
         .send()
         .await
-        .map_err(|e| format!("MiniMax request failed: {}", e))?;
+        .map_err(|e| provider_request_error("MiniMax", config, e))?;
 
     if !response.status().is_success() {
         let status = response.status();
@@ -3197,6 +8100,7 @@ async fn call_minimax(
             .text()
             .await
             .unwrap_or_else(|_| "no body".to_string());
+        persist_provider_error(request_id, "minimax", Some(status.as_u16()), &body).await;
         return Err(format!("MiniMax error {}: {}", status, body));
     }
 
@@ -3216,17 +8120,60 @@ async fn call_minimax(
     parse_llm_response(content)
 }
 
+/// Chat-completions style message list (OpenAI, MiniMax): system prompt first, then turns.
+fn build_chat_messages(messages: &[AgentMessage], snapshot: &Value) -> Vec<Value> {
+    let mut request_messages = vec![json!({
+        "role": "system",
+        "content": build_system_prompt(snapshot),
+    })];
+    request_messages.extend(messages.iter().map(|message| {
+        json!({
+            "role": if message.role == "assistant" { "assistant" } else { "user" },
+            "content": message.content,
+        })
+    }));
+    request_messages
+}
+
+/// Anthropic top-level `system` text: the built prompt plus any caller-supplied system turns.
+fn build_anthropic_system(messages: &[AgentMessage], snapshot: &Value) -> String {
+    let mut system = build_system_prompt(snapshot);
+    for message in messages.iter().filter(|message| message.role == "system") {
+        let content = message.content.trim();
+        if !content.is_empty() {
+            system.push_str("\n\n");
+            system.push_str(content);
+        }
+    }
+    system
+}
+
+/// Anthropic message list; only user/assistant turns, system content goes to `system`.
+fn build_anthropic_messages(messages: &[AgentMessage]) -> Vec<Value> {
+    messages
+        .iter()
+        .filter(|message| message.role == "user" || message.role == "assistant")
+        .map(|message| {
+            json!({
+                "role": message.role,
+                "content": message.content,
+            })
+        })
+        .collect::<Vec<Value>>()
+}
+
 async fn call_codex_local(
     app: &AppHandle,
     request_id: &str,
-    request: &AgentChatRequest,
+    settings: &AgentSettings,
+    messages: &[AgentMessage],
     snapshot: &Value,
 ) -> Result<AgentChatResponse, String> {
-    if !request.settings.codex.enabled {
+    if !settings.codex.enabled {
         return Err("Codex local runtime is disabled".to_string());
     }
 
-    let binary = resolve_codex_binary(request.settings.codex.binary_path.as_deref())?;
+    let binary = resolve_codex_binary(settings.codex.binary_path.as_deref())?;
     emit_agent_event(
         app,
         request_id,
@@ -3235,7 +8182,7 @@ async fn call_codex_local(
         Some(json!({ "binary": binary })),
     );
 
-    if request.settings.codex.prefer_mcp {
+    if settings.codex.prefer_mcp {
         emit_agent_event(
             app,
             request_id,
@@ -3243,7 +8190,7 @@ async fn call_codex_local(
             "尝试连接 Codex MCP 通道",
             None,
         );
-        if let Err(error) = probe_codex_mcp(&binary, &request.settings.codex).await {
+        if let Err(error) = probe_codex_mcp(&binary, &settings.codex).await {
             emit_agent_event(
                 app,
                 request_id,
@@ -3256,8 +8203,8 @@ async fn call_codex_local(
 
     emit_agent_event(app, request_id, "planning", "通过 Codex 生成执行计划", None);
 
-    let prompt = build_codex_prompt(request, snapshot);
-    let content = run_codex_exec(&binary, &request.settings.codex, &prompt).await?;
+    let prompt = build_codex_prompt(messages, snapshot);
+    let content = run_codex_exec(&binary, &settings.codex, &prompt).await?;
     let mut parsed = parse_llm_response(&content)?;
 
     if is_generic_identity_reply(&parsed.reply) && parsed.actions.is_empty() {
@@ -3265,23 +8212,21 @@ async fn call_codex_local(
             "{}\n\n请注意：不要做身份介绍，也不要回复固定模板。请直接回答用户最后一个问题，并给出可执行动作（如果需要）。",
             prompt
         );
-        let retry_content = run_codex_exec(&binary, &request.settings.codex, &retry_prompt).await?;
+        let retry_content = run_codex_exec(&binary, &settings.codex, &retry_prompt).await?;
         parsed = parse_llm_response(&retry_content)?;
     }
 
     Ok(parsed)
 }
 
-fn build_codex_prompt(request: &AgentChatRequest, snapshot: &Value) -> String {
-    let latest_user = request
-        .messages
+fn build_codex_prompt(messages: &[AgentMessage], snapshot: &Value) -> String {
+    let latest_user = messages
         .iter()
         .rev()
         .find(|item| item.role == "user")
         .map(|item| item.content.clone())
         .unwrap_or_else(|| "请根据当前工作台快照给出建议".to_string());
-    let conversation = request
-        .messages
+    let conversation = messages
         .iter()
         .map(|item| format!("{}: {}", item.role, item.content))
         .collect::<Vec<String>>()
@@ -3314,10 +8259,10 @@ async fn run_codex_exec(
         .stdin(Stdio::null());
 
     let duration = Duration::from_millis(config.request_timeout_ms.max(1000));
-    let output = timeout(duration, cmd.output())
+    let output = output_with_timeout(&mut cmd, duration)
         .await
-        .map_err(|_| "Codex exec timed out".to_string())?
-        .map_err(|e| format!("Failed to run codex exec: {}", e))?;
+        .map_err(|e| format!("Failed to run codex exec: {}", e))?
+        .ok_or_else(|| "Codex exec timed out".to_string())?;
 
     if output.status.success() {
         let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
@@ -3339,6 +8284,40 @@ async fn run_codex_exec(
     }
 }
 
+/// Spawns `cmd` and collects its output, waiting at most `duration`. On timeout the
+/// child is killed and reaped so no orphan process survives, and `None` is returned.
+async fn output_with_timeout(
+    cmd: &mut Command,
+    duration: Duration,
+) -> std::io::Result<Option<std::process::Output>> {
+    // Also covers the caller's future being dropped mid-run
+    cmd.kill_on_drop(true);
+    let mut child = cmd.spawn()?;
+    let stdout_task = tokio::spawn(read_child_pipe(child.stdout.take()));
+    let stderr_task = tokio::spawn(read_child_pipe(child.stderr.take()));
+
+    let status = match timeout(duration, child.wait()).await {
+        Ok(status) => status?,
+        Err(_) => {
+            let _ = child.kill().await;
+            return Ok(None);
+        }
+    };
+    Ok(Some(std::process::Output {
+        status,
+        stdout: stdout_task.await.unwrap_or_default(),
+        stderr: stderr_task.await.unwrap_or_default(),
+    }))
+}
+
+async fn read_child_pipe<R: AsyncRead + Unpin>(pipe: Option<R>) -> Vec<u8> {
+    let mut buffer = Vec::new();
+    if let Some(mut pipe) = pipe {
+        let _ = pipe.read_to_end(&mut buffer).await;
+    }
+    buffer
+}
+
 fn extract_codex_last_message(stdout: &str) -> String {
     let mut candidate: Option<String> = None;
     for line in stdout.lines() {
@@ -3418,57 +8397,355 @@ fn resolve_codex_binary(path_override: Option<&str>) -> Result<String, String> {
         }
     }
 
-    Err("codex was not found in PATH".to_string())
+    Err("codex was not found in PATH".to_string())
+}
+
+async fn probe_codex_mcp(binary: &str, config: &AgentCodexConfig) -> Result<(), String> {
+    let mut args = if config.mcp_args.is_empty() {
+        default_codex_mcp_args()
+    } else {
+        config.mcp_args.clone()
+    };
+    args.push("--help".to_string());
+    run_codex_probe(binary, args, config.request_timeout_ms).await
+}
+
+async fn probe_codex_exec(binary: &str, config: &AgentCodexConfig) -> Result<(), String> {
+    let mut args = if config.exec_args.is_empty() {
+        default_codex_exec_args()
+    } else {
+        config.exec_args.clone()
+    };
+    args.push("--help".to_string());
+    run_codex_probe(binary, args, config.request_timeout_ms).await
+}
+
+async fn run_codex_probe(binary: &str, args: Vec<String>, timeout_ms: u64) -> Result<(), String> {
+    let mut cmd = Command::new(binary);
+    cmd.args(args)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .stdin(Stdio::null());
+    output_with_timeout(&mut cmd, Duration::from_millis(timeout_ms.max(1000)))
+        .await
+        .map_err(|e| format!("Codex probe failed: {}", e))?
+        .ok_or_else(|| "Codex probe timed out".to_string())?;
+    Ok(())
+}
+
+const MCP_PROBE_TIMEOUT_SECS: u64 = 15;
+const MCP_TOOLS_CACHE_TTL_SECS: u64 = 600;
+const MCP_PROTOCOL_VERSION: &str = "2024-11-05";
+
+struct CachedMcpTools {
+    fingerprint: String,
+    probed_at: Instant,
+    tools: Vec<McpToolInfo>,
+}
+
+static MCP_TOOLS_CACHE: OnceLock<Mutex<HashMap<String, CachedMcpTools>>> = OnceLock::new();
+
+fn mcp_tools_cache() -> &'static Mutex<HashMap<String, CachedMcpTools>> {
+    MCP_TOOLS_CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn mcp_server_fingerprint(server: &McpServerConfig) -> String {
+    let mut parts = vec![server.command.clone()];
+    parts.extend(server.args.iter().cloned());
+    parts.push(server.cwd.clone().unwrap_or_default());
+    parts.join("\0")
+}
+
+/// Tools of the given stdio servers, probing any server whose cache entry is
+/// missing, stale or was made for a different command line. Servers that are
+/// no longer passed in are dropped from the cache.
+async fn list_mcp_tools(servers: &[McpServerConfig]) -> Vec<McpToolInfo> {
+    let ttl = Duration::from_secs(MCP_TOOLS_CACHE_TTL_SECS);
+    let stale: Vec<&McpServerConfig> = {
+        let mut cache = mcp_tools_cache().lock().unwrap_or_else(|e| e.into_inner());
+        cache.retain(|name, _| servers.iter().any(|server| &server.name == name));
+        servers
+            .iter()
+            .filter(|server| server.transport == "stdio")
+            .filter(|server| {
+                cache.get(&server.name).is_none_or(|entry| {
+                    entry.fingerprint != mcp_server_fingerprint(server)
+                        || entry.probed_at.elapsed() > ttl
+                })
+            })
+            .collect()
+    };
+
+    let probed: Vec<(&McpServerConfig, Vec<McpToolInfo>)> = stream::iter(stale)
+        .map(|server| async move {
+            let tools = timeout(
+                Duration::from_secs(MCP_PROBE_TIMEOUT_SECS),
+                probe_mcp_tools(server),
+            )
+            .await
+            .unwrap_or_else(|_| Err("timed out".to_string()))
+            // A server that cannot list its tools just contributes none
+            .unwrap_or_default();
+            (server, tools)
+        })
+        .buffer_unordered(4)
+        .collect()
+        .await;
+
+    let mut cache = mcp_tools_cache().lock().unwrap_or_else(|e| e.into_inner());
+    for (server, tools) in probed {
+        cache.insert(
+            server.name.clone(),
+            CachedMcpTools {
+                fingerprint: mcp_server_fingerprint(server),
+                probed_at: Instant::now(),
+                tools,
+            },
+        );
+    }
+    servers
+        .iter()
+        .filter_map(|server| cache.get(&server.name))
+        .flat_map(|entry| entry.tools.iter().cloned())
+        .collect()
+}
+
+/// Whatever `list_mcp_tools` last found; never probes, so prompts stay fast.
+fn cached_mcp_tools() -> Vec<McpToolInfo> {
+    let cache = mcp_tools_cache().lock().unwrap_or_else(|e| e.into_inner());
+    let mut tools: Vec<McpToolInfo> = cache
+        .values()
+        .flat_map(|entry| entry.tools.iter().cloned())
+        .collect();
+    tools.sort_by(|a, b| (&a.server, &a.tool).cmp(&(&b.server, &b.tool)));
+    tools
+}
+
+/// A started stdio MCP server that has completed the `initialize` handshake. The
+/// child is killed when the session is dropped.
+struct McpSession {
+    _child: tokio::process::Child,
+    stdin: tokio::process::ChildStdin,
+    lines: tokio::io::Lines<BufReader<tokio::process::ChildStdout>>,
 }
 
-async fn probe_codex_mcp(binary: &str, config: &AgentCodexConfig) -> Result<(), String> {
-    let mut args = if config.mcp_args.is_empty() {
-        default_codex_mcp_args()
-    } else {
-        config.mcp_args.clone()
-    };
-    args.push("--help".to_string());
-    run_codex_probe(binary, args, config.request_timeout_ms).await
+async fn start_mcp_session(server: &McpServerConfig) -> Result<McpSession, String> {
+    // Starting with ciphertext in place of a secret would only fail less clearly
+    if !server.locked_env.is_empty() {
+        return Err(locked_env_message(server));
+    }
+    let mut cmd = Command::new(&server.command);
+    cmd.args(&server.args)
+        .envs(&server.env)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true);
+    if let Some(cwd) = server.cwd.as_deref().filter(|cwd| !cwd.trim().is_empty()) {
+        cmd.current_dir(cwd);
+    }
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| format!("Failed to start MCP server: {}", e))?;
+    let mut stdin = child
+        .stdin
+        .take()
+        .ok_or_else(|| "MCP server stdin unavailable".to_string())?;
+    let stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| "MCP server stdout unavailable".to_string())?;
+    let mut lines = BufReader::new(stdout).lines();
+
+    send_mcp_message(
+        &mut stdin,
+        &json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "initialize",
+            "params": {
+                "protocolVersion": MCP_PROTOCOL_VERSION,
+                "capabilities": {},
+                "clientInfo": { "name": "zhaoxi", "version": env!("CARGO_PKG_VERSION") }
+            }
+        }),
+    )
+    .await?;
+    read_mcp_response(&mut lines, 1).await?;
+    send_mcp_message(
+        &mut stdin,
+        &json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }),
+    )
+    .await?;
+    Ok(McpSession {
+        _child: child,
+        stdin,
+        lines,
+    })
 }
 
-async fn probe_codex_exec(binary: &str, config: &AgentCodexConfig) -> Result<(), String> {
-    let mut args = if config.exec_args.is_empty() {
-        default_codex_exec_args()
-    } else {
-        config.exec_args.clone()
-    };
-    args.push("--help".to_string());
-    run_codex_probe(binary, args, config.request_timeout_ms).await
+/// Starts the server and pages through `tools/list`.
+async fn probe_mcp_tools(server: &McpServerConfig) -> Result<Vec<McpToolInfo>, String> {
+    let McpSession {
+        _child,
+        mut stdin,
+        mut lines,
+    } = start_mcp_session(server).await?;
+
+    let mut tools = Vec::new();
+    let mut cursor: Option<String> = None;
+    for request_id in 2..12 {
+        let params = match &cursor {
+            Some(cursor) => json!({ "cursor": cursor }),
+            None => json!({}),
+        };
+        send_mcp_message(
+            &mut stdin,
+            &json!({ "jsonrpc": "2.0", "id": request_id, "method": "tools/list", "params": params }),
+        )
+        .await?;
+        let result = read_mcp_response(&mut lines, request_id).await?;
+        for tool in result
+            .get("tools")
+            .and_then(|value| value.as_array())
+            .into_iter()
+            .flatten()
+        {
+            let Some(name) = tool.get("name").and_then(|value| value.as_str()) else {
+                continue;
+            };
+            tools.push(McpToolInfo {
+                server: server.name.clone(),
+                tool: name.to_string(),
+                description: tool
+                    .get("description")
+                    .and_then(|value| value.as_str())
+                    .map(|value| value.trim().to_string())
+                    .filter(|value| !value.is_empty()),
+            });
+        }
+        cursor = result
+            .get("nextCursor")
+            .and_then(|value| value.as_str())
+            .map(str::to_string);
+        if cursor.is_none() {
+            break;
+        }
+    }
+    Ok(tools)
+}
+
+/// Starts the server and sends a single `tools/call`, returning its `result`.
+async fn call_mcp_tool(
+    server: &McpServerConfig,
+    tool: &str,
+    arguments: Value,
+) -> Result<Value, String> {
+    let mut session = start_mcp_session(server).await?;
+    send_mcp_message(
+        &mut session.stdin,
+        &json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "method": "tools/call",
+            "params": { "name": tool, "arguments": arguments }
+        }),
+    )
+    .await?;
+    read_mcp_response(&mut session.lines, 2).await
 }
 
-async fn run_codex_probe(binary: &str, args: Vec<String>, timeout_ms: u64) -> Result<(), String> {
-    let mut cmd = Command::new(binary);
-    cmd.args(args)
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .stdin(Stdio::null());
-    timeout(Duration::from_millis(timeout_ms.max(1000)), cmd.output())
+async fn send_mcp_message(
+    stdin: &mut tokio::process::ChildStdin,
+    message: &Value,
+) -> Result<(), String> {
+    let mut line = message.to_string();
+    line.push('\n');
+    stdin
+        .write_all(line.as_bytes())
         .await
-        .map_err(|_| "Codex probe timed out".to_string())?
-        .map_err(|e| format!("Codex probe failed: {}", e))?;
-    Ok(())
+        .map_err(|e| format!("Failed to write to MCP server: {}", e))
+}
+
+/// Reads lines until the response for `id`, skipping notifications and logs.
+async fn read_mcp_response(
+    lines: &mut tokio::io::Lines<BufReader<tokio::process::ChildStdout>>,
+    id: i64,
+) -> Result<Value, String> {
+    while let Some(line) = lines
+        .next_line()
+        .await
+        .map_err(|e| format!("Failed to read from MCP server: {}", e))?
+    {
+        let Ok(message) = serde_json::from_str::<Value>(line.trim()) else {
+            continue;
+        };
+        if message.get("id").and_then(|value| value.as_i64()) != Some(id) {
+            continue;
+        }
+        if let Some(error) = message.get("error") {
+            return Err(format!("MCP error: {}", error));
+        }
+        return Ok(message.get("result").cloned().unwrap_or(Value::Null));
+    }
+    Err("MCP server closed its output".to_string())
 }
 
 fn build_system_prompt(snapshot: &Value) -> String {
     format!(
-        "你是 ZhaoXi Workbench Agent。你必须基于上下文数据给出清晰建议，并且仅输出 JSON，结构为: {{\"reply\":\"string\",\"actions\":[{{\"id\":\"string\",\"type\":\"string\",\"title\":\"string\",\"reason\":\"string\",\"payload\":{{}},\"requiresApproval\":false}}]}}。\
-        action type 只能使用: todo.create,todo.update,todo.delete,project.create,project.update_progress,project.delete,event.create,event.update,event.delete,personal.create,personal.update,personal.delete,query.snapshot。\
+        "你是 ZhaoXi Workbench Agent。你必须基于上下文数据给出清晰建议，并且仅输出 JSON，结构为: {{\"reply\":\"string\",\"actions\":[{{\"id\":\"string\",\"type\":\"string\",\"title\":\"string\",\"reason\":\"string\",\"payload\":{{}},\"requiresApproval\":false}}],\"plan\":[{{\"step\":1,\"description\":\"string\",\"actionRef\":\"action id\"}}]}}。\
+        多步骤任务需在 plan 中按顺序列出步骤，actionRef 指向对应 action 的 id；简单任务 plan 返回空数组。\
+        action type 只能使用: {}。\
+        todo.list(payload 可选 completed,priority,limit)、project.list(payload 可选 status)、event.list_by_date(payload 必填 date) 是只读查询，结果会在下一轮提供给你。\
+        personal.to_event 把有日期的个人事务转为日程，payload 必填 id，可选 markCompleted。\
+        修改信息中心的关注关键词、推送时间等设置时使用 info.update_settings，payload 只填需要修改的字段（includeKeywords/excludeKeywords 为字符串数组），该动作需用户确认后执行。\
+        一次新增多条待办时使用 todo.bulk_create，payload 为 {{\"titles\":[\"string\"],\"priority\":\"normal\"}}，不要拆成多个 todo.create。\
+        todo 的 priority 只能使用: low,normal,high,urgent。\
+        event 的 color 只能使用: {}。\
         你必须直接回答用户问题，禁止固定自我介绍或与问题无关的模板句。\
         如果不需要动作，actions 返回空数组。\
-        当前上下文: {}",
+        {}当前上下文: {}",
+        AGENT_ACTION_SPECS
+            .iter()
+            .map(|spec| spec.action_type)
+            .collect::<Vec<_>>()
+            .join(","),
+        EVENT_COLOR_PALETTE
+            .iter()
+            .map(|(name, _)| *name)
+            .collect::<Vec<_>>()
+            .join(","),
+        describe_mcp_tools(&cached_mcp_tools()),
         snapshot
     )
 }
 
+/// One prompt sentence naming the MCP tools available to the runtime, or an
+/// empty string when none have been discovered.
+fn describe_mcp_tools(tools: &[McpToolInfo]) -> String {
+    if tools.is_empty() {
+        return String::new();
+    }
+    let entries = tools
+        .iter()
+        .map(|tool| match &tool.description {
+            Some(description) => format!(
+                "{}/{}({})",
+                tool.server,
+                tool.tool,
+                description.chars().take(80).collect::<String>()
+            ),
+            None => format!("{}/{}", tool.server, tool.tool),
+        })
+        .collect::<Vec<_>>()
+        .join("；");
+    format!("已接入的 MCP 工具（server/tool）: {}。", entries)
+}
+
 fn parse_llm_response(content: &str) -> Result<AgentChatResponse, String> {
     let normalized = extract_json_block(content);
     if let Ok(value) = serde_json::from_str::<Value>(&normalized) {
-        let reply = value
+        let mut reply = value
             .get("reply")
             .and_then(|item| item.as_str())
             .unwrap_or("已生成建议。")
@@ -3480,10 +8757,32 @@ fn parse_llm_response(content: &str) -> Result<AgentChatResponse, String> {
             .unwrap_or_else(|| Value::Array(vec![]));
         let parsed_actions: Vec<AgentActionProposal> = serde_json::from_value(actions)
             .map_err(|e| format!("LLM actions parse failed: {}", e))?;
+        // Drop action types the model made up instead of failing the whole batch later
+        let (parsed_actions, dropped): (Vec<_>, Vec<_>) =
+            parsed_actions.into_iter().partition(|action| {
+                AGENT_ACTION_SPECS
+                    .iter()
+                    .any(|spec| spec.action_type == action.r#type)
+            });
+        if !dropped.is_empty() {
+            let names = dropped
+                .iter()
+                .map(|action| action.r#type.as_str())
+                .collect::<Vec<_>>()
+                .join(", ");
+            reply.push_str(&format!("\n\n（已忽略不支持的操作: {}）", names));
+        }
+        // The plan is advisory, so a malformed one is dropped rather than failing the reply
+        let plan = value
+            .get("plan")
+            .cloned()
+            .and_then(|item| serde_json::from_value::<Vec<AgentPlanStep>>(item).ok())
+            .unwrap_or_default();
 
         return Ok(AgentChatResponse {
             reply,
             actions: parsed_actions,
+            plan,
         });
     }
 
@@ -3495,6 +8794,7 @@ fn parse_llm_response(content: &str) -> Result<AgentChatResponse, String> {
     Ok(AgentChatResponse {
         reply: plain_reply.to_string(),
         actions: vec![],
+        plan: vec![],
     })
 }
 
@@ -3542,7 +8842,7 @@ async fn persist_agent_event(request_id: &str, stage: &str, message: &str, meta:
     let _ = sqlx::query(
         "INSERT INTO agent_events (id, request_id, stage, message, meta_json) VALUES (?1, ?2, ?3, ?4, ?5)",
     )
-    .bind(format!("evt-{}", chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0)))
+    .bind(format!("evt-{}", new_entity_id()))
     .bind(request_id)
     .bind(stage)
     .bind(message)
@@ -3568,7 +8868,7 @@ async fn persist_agent_session(
     let _ = sqlx::query(
         "INSERT INTO agent_sessions (id, request_id, provider, user_message, reply) VALUES (?1, ?2, ?3, ?4, ?5)",
     )
-    .bind(format!("sess-{}", chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0)))
+    .bind(format!("sess-{}", new_entity_id()))
     .bind(request_id)
     .bind(provider)
     .bind(latest_user)
@@ -3577,13 +8877,33 @@ async fn persist_agent_session(
     .await;
 }
 
+async fn persist_provider_error(request_id: &str, provider: &str, status: Option<u16>, body: &str) {
+    let Ok(pool) = get_db_pool() else {
+        return;
+    };
+    let truncated = body
+        .chars()
+        .take(PROVIDER_ERROR_BODY_LIMIT)
+        .collect::<String>();
+    let _ = sqlx::query(
+        "INSERT INTO agent_provider_errors (id, request_id, provider, status, body) VALUES (?1, ?2, ?3, ?4, ?5)",
+    )
+    .bind(format!("perr-{}", new_entity_id()))
+    .bind(request_id)
+    .bind(provider)
+    .bind(status.map(i64::from))
+    .bind(truncated)
+    .execute(pool)
+    .await;
+}
+
 async fn persist_audit_records(records: &[AgentExecutionAuditRecord]) {
     let Ok(pool) = get_db_pool() else {
         return;
     };
     for record in records {
         let _ = sqlx::query(
-            "INSERT INTO agent_action_audits (id, batch_id, action_id, action_type, payload_json, before_state_json, after_state_json, success, error_message) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            "INSERT INTO agent_action_audits (id, batch_id, action_id, action_type, payload_json, before_state_json, after_state_json, success, error_message, stop_on_error) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
         )
         .bind(&record.id)
         .bind(&record.batch_id)
@@ -3594,6 +8914,7 @@ async fn persist_audit_records(records: &[AgentExecutionAuditRecord]) {
         .bind(record.after_state.as_ref().map(|item| item.to_string()))
         .bind(if record.success { 1 } else { 0 })
         .bind(record.error.clone())
+        .bind(record.stop_on_error)
         .execute(pool)
         .await;
     }
@@ -3602,7 +8923,7 @@ async fn persist_audit_records(records: &[AgentExecutionAuditRecord]) {
 async fn load_info_settings() -> Result<InfoSettings, String> {
     let pool = get_db_pool()?;
     let row = sqlx::query(
-        "SELECT push_time, include_keywords_json, exclude_keywords_json, max_items_per_day
+        "SELECT push_time, include_keywords_json, exclude_keywords_json, max_items_per_day, max_concurrent_fetches, retention_days, quiet_hours_start, quiet_hours_end, next_refresh_at
          FROM info_settings
          WHERE id = 'default'
          LIMIT 1",
@@ -3612,13 +8933,22 @@ async fn load_info_settings() -> Result<InfoSettings, String> {
     .map_err(|e| format!("Failed to query info settings: {}", e))?;
 
     if let Some(row) = row {
-        let include_keywords = parse_keywords_json(row.get("include_keywords_json"))?;
-        let exclude_keywords = parse_keywords_json(row.get("exclude_keywords_json"))?;
+        let include_keywords =
+            keywords_or_reset("include_keywords_json", row.get("include_keywords_json")).await;
+        let exclude_keywords =
+            keywords_or_reset("exclude_keywords_json", row.get("exclude_keywords_json")).await;
         return Ok(InfoSettings {
             push_time: normalize_push_time(&row.get::<String, _>("push_time")),
             include_keywords,
             exclude_keywords,
             max_items_per_day: row.get::<i32, _>("max_items_per_day").clamp(1, 100),
+            max_concurrent_fetches: row.get::<i32, _>("max_concurrent_fetches").clamp(1, 20),
+            retention_days: row
+                .get::<i32, _>("retention_days")
+                .clamp(1, MAX_INFO_RETENTION_DAYS),
+            quiet_hours_start: row.get("quiet_hours_start"),
+            quiet_hours_end: row.get("quiet_hours_end"),
+            next_refresh_at: row.get("next_refresh_at"),
         });
     }
 
@@ -3627,10 +8957,248 @@ async fn load_info_settings() -> Result<InfoSettings, String> {
         include_keywords: vec![],
         exclude_keywords: vec![],
         max_items_per_day: 20,
+        max_concurrent_fetches: DEFAULT_MAX_CONCURRENT_FETCHES,
+        retention_days: 1,
+        quiet_hours_start: None,
+        quiet_hours_end: None,
+        next_refresh_at: None,
     })
 }
 
+/// A corrupt keyword column should not take the whole settings load down with it:
+/// reset just that column to an empty list and keep going.
+async fn keywords_or_reset(column: &str, raw: String) -> Vec<String> {
+    match parse_keywords_json(raw) {
+        Ok(keywords) => keywords,
+        Err(_) => {
+            let sql = format!(
+                "UPDATE info_settings SET {} = '[]', updated_at = CURRENT_TIMESTAMP WHERE id = 'default'",
+                column
+            );
+            // A failed reset just means the column is reset again on the next load
+            if let Ok(pool) = get_db_pool() {
+                let _ = sqlx::query(&sql).execute(pool).await;
+            }
+            vec![]
+        }
+    }
+}
+
+/// Stores which sources surfaced which links today, keeping at most
+/// `MAX_INFO_RETENTION_DAYS` of history for `get_duplicate_info_links`.
+async fn record_link_sightings(
+    today: &str,
+    sightings: &[(String, String, String)],
+) -> Result<(), String> {
+    let pool = get_db_pool()?;
+    let cutoff = (app_now().date_naive() - chrono::Duration::days(MAX_INFO_RETENTION_DAYS.into()))
+        .format("%Y-%m-%d")
+        .to_string();
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+    sqlx::query("DELETE FROM info_link_sightings WHERE date <= ?1")
+        .bind(&cutoff)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to prune link sightings: {}", e))?;
+    for (link, source_id, title) in sightings {
+        sqlx::query(
+            "INSERT OR REPLACE INTO info_link_sightings (link, source_id, date, title) VALUES (?1, ?2, ?3, ?4)",
+        )
+        .bind(link)
+        .bind(source_id)
+        .bind(today)
+        .bind(title)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to record link sighting: {}", e))?;
+    }
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit transaction: {}", e))
+}
+
+/// Stores each source's latest fetch outcome so the manage UI can tell an empty
+/// feed from a broken one.
+async fn record_source_health(
+    health: &[(String, &'static str, Option<String>)],
+) -> Result<(), String> {
+    let pool = get_db_pool()?;
+    let fetched_at = local_now_rfc3339();
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+    for (source_id, status, error) in health {
+        sqlx::query(
+            "UPDATE info_sources SET last_fetch_status = ?1, last_fetch_error = ?2, last_fetched_at = ?3, updated_at = CURRENT_TIMESTAMP WHERE id = ?4",
+        )
+        .bind(status)
+        .bind(error)
+        .bind(&fetched_at)
+        .bind(source_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to update source health: {}", e))?;
+    }
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit transaction: {}", e))
+}
+
+async fn load_feed_validators() -> Result<HashMap<String, FeedValidators>, String> {
+    let pool = get_db_pool()?;
+    let rows = sqlx::query("SELECT id, etag, last_modified FROM info_sources")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Failed to load feed validators: {}", e))?;
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            (
+                row.get("id"),
+                FeedValidators {
+                    etag: row.get("etag"),
+                    last_modified: row.get("last_modified"),
+                },
+            )
+        })
+        .collect())
+}
+
+async fn record_feed_validators(validators: &[(String, FeedValidators)]) -> Result<(), String> {
+    let pool = get_db_pool()?;
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+    for (source_id, validators) in validators {
+        sqlx::query(
+            "UPDATE info_sources SET etag = ?1, last_modified = ?2, updated_at = CURRENT_TIMESTAMP WHERE id = ?3",
+        )
+        .bind(&validators.etag)
+        .bind(&validators.last_modified)
+        .bind(source_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to update feed validators: {}", e))?;
+    }
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit transaction: {}", e))
+}
+
+struct InfoRefreshGuard;
+
+impl Drop for InfoRefreshGuard {
+    fn drop(&mut self) {
+        if let Ok(mut running) = INFO_REFRESH_RUNNING.lock() {
+            *running = None;
+        }
+    }
+}
+
+/// Claims the refresh slot, or returns the trigger type already holding it.
+fn try_begin_info_refresh(trigger_type: &str) -> Result<InfoRefreshGuard, String> {
+    let mut running = INFO_REFRESH_RUNNING
+        .lock()
+        .map_err(|_| "unknown".to_string())?;
+    if let Some(current) = running.as_ref() {
+        return Err(current.clone());
+    }
+    *running = Some(trigger_type.to_string());
+    Ok(InfoRefreshGuard)
+}
+
+const INFO_VALIDATE_TIMEOUT_SECS: u64 = 20;
+
+/// Fetches every enabled source without touching stored items, for a feed health
+/// check. Each source gets its own timeout; the outcome is kept as its fetch health.
+#[command]
+pub async fn validate_all_info_sources() -> Result<Vec<InfoSourceValidation>, String> {
+    let settings = load_info_settings().await?;
+    let sources: Vec<InfoSource> = get_info_sources()
+        .await?
+        .into_iter()
+        .filter(|source| source.enabled)
+        .collect();
+
+    let concurrency = settings.max_concurrent_fetches.max(1) as usize;
+    let filter = InfoKeywordFilter::from_settings(&settings);
+    let mut outcomes: Vec<(usize, &'static str, InfoSourceValidation)> =
+        stream::iter(sources.iter().enumerate())
+            .map(|(index, source)| {
+                let filter = &filter;
+                async move {
+                    let fetched = timeout(
+                        Duration::from_secs(INFO_VALIDATE_TIMEOUT_SECS),
+                        fetch_source_items(source, filter),
+                    )
+                    .await
+                    .unwrap_or_else(|_| Err(format!("{} 秒内未响应", INFO_VALIDATE_TIMEOUT_SECS)));
+                    let (status, title, item_count, error) = match fetched {
+                        Ok(feed) if feed.empty => ("empty", feed.title, 0, None),
+                        Ok(feed) => ("ok", feed.title, feed.entry_count, None),
+                        Err(error) => ("error", None, 0, Some(error)),
+                    };
+                    let validation = InfoSourceValidation {
+                        source_id: source.id.clone(),
+                        name: source.name.clone(),
+                        status: status.to_string(),
+                        title,
+                        item_count,
+                        error,
+                    };
+                    (index, status, validation)
+                }
+            })
+            .buffer_unordered(concurrency)
+            .collect()
+            .await;
+    outcomes.sort_by_key(|(index, _, _)| *index);
+
+    let health: Vec<(String, &'static str, Option<String>)> = outcomes
+        .iter()
+        .map(|(_, status, validation)| {
+            (
+                validation.source_id.clone(),
+                *status,
+                validation.error.clone(),
+            )
+        })
+        .collect();
+    record_source_health(&health).await?;
+
+    Ok(outcomes
+        .into_iter()
+        .map(|(_, _, validation)| validation)
+        .collect())
+}
+
+fn info_refresh_busy_response(running_trigger: String) -> InfoRefreshResponse {
+    InfoRefreshResponse {
+        success: false,
+        skipped: true,
+        fetched_count: 0,
+        kept_count: 0,
+        message: format!("已有刷新正在进行（{}）", running_trigger),
+        refreshed_at: local_now_rfc3339(),
+        trigger_type: running_trigger,
+    }
+}
+
 async fn refresh_info_with_trigger(trigger_type: &str) -> Result<InfoRefreshResponse, String> {
+    let _guard = match try_begin_info_refresh(trigger_type) {
+        Ok(guard) => guard,
+        Err(running_trigger) => return Ok(info_refresh_busy_response(running_trigger)),
+    };
+    run_info_refresh(trigger_type).await
+}
+
+/// The full refresh; the caller must hold the refresh slot.
+async fn run_info_refresh(trigger_type: &str) -> Result<InfoRefreshResponse, String> {
     let pool = get_db_pool()?;
     let settings = load_info_settings().await?;
     let sources = get_info_sources().await?;
@@ -3638,11 +9206,16 @@ async fn refresh_info_with_trigger(trigger_type: &str) -> Result<InfoRefreshResp
         .into_iter()
         .filter(|source| source.enabled)
         .collect();
-    let refreshed_at = chrono::Local::now().to_rfc3339();
+    let refreshed_at = local_now_rfc3339();
     let today = local_today_string();
 
-    sqlx::query("DELETE FROM info_items_daily WHERE date != ?1")
-        .bind(&today)
+    // Drop only days that have fallen out of the retention window
+    let retention_cutoff = (app_now().date_naive()
+        - chrono::Duration::days(settings.retention_days.into()))
+    .format("%Y-%m-%d")
+    .to_string();
+    sqlx::query("DELETE FROM info_items_daily WHERE date <= ?1")
+        .bind(&retention_cutoff)
         .execute(pool)
         .await
         .map_err(|e| format!("Failed to cleanup stale info items: {}", e))?;
@@ -3652,10 +9225,12 @@ async fn refresh_info_with_trigger(trigger_type: &str) -> Result<InfoRefreshResp
         insert_info_refresh_log(trigger_type, true, &message, 0, 0).await;
         return Ok(InfoRefreshResponse {
             success: true,
+            skipped: false,
             fetched_count: 0,
             kept_count: 0,
             message,
             refreshed_at,
+            trigger_type: trigger_type.to_string(),
         });
     }
 
@@ -3664,11 +9239,48 @@ async fn refresh_info_with_trigger(trigger_type: &str) -> Result<InfoRefreshResp
     let mut aggregate: HashMap<String, InfoItem> = HashMap::new();
     let mut errors = Vec::new();
 
-    for source in enabled_sources {
-        match fetch_source_items(&source, &settings).await {
-            Ok(items) => {
+    let concurrency = settings.max_concurrent_fetches.max(1) as usize;
+    let filter = InfoKeywordFilter::from_settings(&settings);
+    // Owned sources keep this future spawnable by the one-shot timer
+    let results: Vec<(InfoSource, Result<FetchedFeed, String>)> =
+        stream::iter(enabled_sources.iter().cloned())
+            .map(|source| {
+                let filter = &filter;
+                async move {
+                    let result = fetch_source_items(&source, filter).await;
+                    (source, result)
+                }
+            })
+            .buffer_unordered(concurrency)
+            .collect()
+            .await;
+
+    let mut sightings = Vec::new();
+    let mut empty_sources = Vec::new();
+    let mut health = Vec::with_capacity(results.len());
+    let mut validators = Vec::new();
+    for (source, result) in results {
+        match result {
+            Ok(FetchedFeed {
+                items,
+                empty,
+                validators: feed_validators,
+                ..
+            }) => {
+                validators.push((source.id.clone(), feed_validators));
+                if empty {
+                    empty_sources.push(source.name.clone());
+                    health.push((source.id.clone(), "empty", None));
+                } else {
+                    health.push((source.id.clone(), "ok", None));
+                }
                 fetched_count += items.len() as i32;
                 for item in items {
+                    sightings.push((
+                        item.link.clone(),
+                        item.source_id.clone(),
+                        item.title.clone(),
+                    ));
                     if !link_seen.insert(item.link.clone()) {
                         if let Some(existing) = aggregate.get_mut(&item.link) {
                             if item.score > existing.score {
@@ -3681,11 +9293,16 @@ async fn refresh_info_with_trigger(trigger_type: &str) -> Result<InfoRefreshResp
                 }
             }
             Err(error) => {
+                health.push((source.id.clone(), "error", Some(error.clone())));
                 errors.push(format!("{}: {}", source.name, error));
             }
         }
     }
 
+    record_link_sightings(&today, &sightings).await?;
+    record_source_health(&health).await?;
+    record_feed_validators(&validators).await?;
+
     let mut final_items: Vec<InfoItem> = aggregate.into_values().collect();
     final_items.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal));
     final_items.truncate(settings.max_items_per_day as usize);
@@ -3696,49 +9313,40 @@ async fn refresh_info_with_trigger(trigger_type: &str) -> Result<InfoRefreshResp
         .await
         .map_err(|e| format!("Failed to clear current day info items: {}", e))?;
 
-    for (index, item) in final_items.iter().enumerate() {
-        let matched_keywords_json = serde_json::to_string(&item.matched_keywords)
-            .map_err(|e| format!("Failed to serialize matched keywords: {}", e))?;
-        sqlx::query(
-            "INSERT INTO info_items_daily
-             (id, date, source_id, title, link, summary, published_at, score, matched_keywords_json, fetched_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
-        )
-        .bind(format!("info-{}-{}", chrono::Utc::now().timestamp_millis(), index))
-        .bind(&today)
-        .bind(&item.source_id)
-        .bind(&item.title)
-        .bind(&item.link)
-        .bind(&item.summary)
-        .bind(&item.published_at)
-        .bind(item.score)
-        .bind(matched_keywords_json)
-        .bind(&item.fetched_at)
-        .execute(pool)
-        .await
-        .map_err(|e| format!("Failed to store info item: {}", e))?;
+    for item in &final_items {
+        store_info_item(pool, &today, item).await?;
     }
 
     let success = errors.is_empty();
-    let message = if success {
+    let mut message = if success {
         format!("已更新 {} 条信息", final_items.len())
     } else {
         format!(
-            "已更新 {} 条信息，{} 个信息源失败",
+            "已更新 {} 条信息，{} 个信息源失败（并发抓取 {} 个，遇到限流可在设置中调低）",
             final_items.len(),
-            errors.len()
+            errors.len(),
+            concurrency
         )
     };
+    if !empty_sources.is_empty() {
+        message.push_str(&format!("，{} 个信息源内容为空", empty_sources.len()));
+    }
+    let mut details = errors.clone();
+    details.extend(
+        empty_sources
+            .iter()
+            .map(|name| format!("{}: 订阅源没有条目", name)),
+    );
     insert_info_refresh_log(
         trigger_type,
         success,
         &format!(
             "{}{}",
             message,
-            if errors.is_empty() {
+            if details.is_empty() {
                 String::new()
             } else {
-                format!("（{}）", errors.join("; "))
+                format!("（{}）", details.join("; "))
             }
         ),
         fetched_count,
@@ -3746,39 +9354,436 @@ async fn refresh_info_with_trigger(trigger_type: &str) -> Result<InfoRefreshResp
     )
     .await;
 
-    Ok(InfoRefreshResponse {
-        success,
-        fetched_count,
-        kept_count: final_items.len() as i32,
-        message,
-        refreshed_at,
-    })
+    Ok(InfoRefreshResponse {
+        success,
+        skipped: false,
+        fetched_count,
+        kept_count: final_items.len() as i32,
+        message,
+        refreshed_at,
+        trigger_type: trigger_type.to_string(),
+    })
+}
+
+async fn store_info_item<'c, E>(executor: E, date: &str, item: &InfoItem) -> Result<(), String>
+where
+    E: sqlx::Executor<'c, Database = sqlx::Sqlite>,
+{
+    let matched_keywords_json = serde_json::to_string(&item.matched_keywords)
+        .map_err(|e| format!("Failed to serialize matched keywords: {}", e))?;
+    sqlx::query(
+        "INSERT INTO info_items_daily
+         (id, date, source_id, title, link, summary, published_at, score, matched_keywords_json, fetched_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+    )
+    .bind(format!("info-{}", new_entity_id()))
+    .bind(date)
+    .bind(&item.source_id)
+    .bind(&item.title)
+    .bind(&item.link)
+    .bind(&item.summary)
+    .bind(&item.published_at)
+    .bind(item.score)
+    .bind(matched_keywords_json)
+    .bind(&item.fetched_at)
+    .execute(executor)
+    .await
+    .map_err(|e| format!("Failed to store info item: {}", e))?;
+    Ok(())
+}
+
+static HTTP_CLIENT: Mutex<Option<reqwest::Client>> = Mutex::new(None);
+static FETCH_HTTP_CLIENT: Mutex<Option<reqwest::Client>> = Mutex::new(None);
+
+/// Hosts that never go through a proxy, so local Ollama/codex endpoints keep working.
+const PROXY_ALWAYS_BYPASS: &str = "localhost,127.0.0.1,::1";
+
+/// Shared client for weather and providers. The `proxy` app setting (or, when it is
+/// unset, `ZHAOXI_PROXY`) overrides the proxy, `off` disabling it; otherwise
+/// `HTTPS_PROXY`/`HTTP_PROXY` are applied explicitly. `NO_PROXY` and local hosts
+/// are always bypassed.
+fn http_client() -> Result<reqwest::Client, String> {
+    cached_http_client(&HTTP_CLIENT, |builder| {
+        builder.redirect(reqwest::redirect::Policy::default())
+    })
+}
+
+/// Client for fetching user-supplied URLs. Redirects are not followed automatically
+/// so `guarded_get` can vet every hop, and names resolve through
+/// `PublicOnlyResolver` so the connection uses the addresses that were checked.
+fn fetch_http_client() -> Result<reqwest::Client, String> {
+    cached_http_client(&FETCH_HTTP_CLIENT, |builder| {
+        builder
+            .redirect(reqwest::redirect::Policy::none())
+            .dns_resolver(Arc::new(PublicOnlyResolver))
+    })
+}
+
+fn cached_http_client(
+    slot: &Mutex<Option<reqwest::Client>>,
+    configure: fn(reqwest::ClientBuilder) -> reqwest::ClientBuilder,
+) -> Result<reqwest::Client, String> {
+    let mut slot = slot.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(client) = slot.as_ref() {
+        return Ok(client.clone());
+    }
+    let client = build_http_client(configure)?;
+    *slot = Some(client.clone());
+    Ok(client)
+}
+
+/// Drops the shared clients so the next request picks up a changed proxy setting.
+fn reset_http_clients() {
+    for slot in [&HTTP_CLIENT, &FETCH_HTTP_CLIENT] {
+        *slot.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+}
+
+fn build_http_client(
+    configure: fn(reqwest::ClientBuilder) -> reqwest::ClientBuilder,
+) -> Result<reqwest::Client, String> {
+    let env_value = |keys: &[&str]| {
+        keys.iter()
+            .filter_map(|key| env::var(key).ok())
+            .map(|value| value.trim().to_string())
+            .find(|value| !value.is_empty())
+    };
+    let bypass = match env_value(&["NO_PROXY", "no_proxy"]) {
+        Some(extra) => format!("{},{}", PROXY_ALWAYS_BYPASS, extra),
+        None => PROXY_ALWAYS_BYPASS.to_string(),
+    };
+    let proxy_error = |e: reqwest::Error| format!("代理地址无效: {}", e);
+
+    let mut builder = configure(reqwest::Client::builder());
+    let explicit_proxy = cached_app_settings()
+        .proxy
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .or_else(|| env_value(&["ZHAOXI_PROXY"]));
+    match explicit_proxy {
+        Some(value) if value.eq_ignore_ascii_case("off") => builder = builder.no_proxy(),
+        Some(url) => {
+            let proxy = reqwest::Proxy::all(&url).map_err(proxy_error)?;
+            builder = builder.proxy(proxy.no_proxy(reqwest::NoProxy::from_string(&bypass)));
+        }
+        None => {
+            if let Some(url) = env_value(&["HTTPS_PROXY", "https_proxy"]) {
+                let proxy = reqwest::Proxy::https(&url).map_err(proxy_error)?;
+                builder = builder.proxy(proxy.no_proxy(reqwest::NoProxy::from_string(&bypass)));
+            }
+            if let Some(url) = env_value(&["HTTP_PROXY", "http_proxy"]) {
+                let proxy = reqwest::Proxy::http(&url).map_err(proxy_error)?;
+                builder = builder.proxy(proxy.no_proxy(reqwest::NoProxy::from_string(&bypass)));
+            }
+        }
+    }
+    builder
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))
+}
+
+const MAX_FETCH_REDIRECTS: usize = 5;
+
+/// GET for user-supplied URLs (feeds, article pages). Each hop, including
+/// redirects, must resolve to a public address so a feed cannot be used to probe
+/// local services or the LAN.
+async fn guarded_get(url: &str) -> Result<reqwest::Response, String> {
+    guarded_get_with_auth(url, None).await
+}
+
+/// `guarded_get` that sends a source's credentials. They are only attached while
+/// the request stays on the original host, so a redirect cannot leak them.
+async fn guarded_get_with_auth(
+    url: &str,
+    auth: Option<&InfoSource>,
+) -> Result<reqwest::Response, String> {
+    guarded_get_with_headers(url, auth, reqwest::header::HeaderMap::new()).await
+}
+
+/// `guarded_get_with_auth` with extra request headers sent on every hop.
+async fn guarded_get_with_headers(
+    url: &str,
+    auth: Option<&InfoSource>,
+    headers: reqwest::header::HeaderMap,
+) -> Result<reqwest::Response, String> {
+    let client = fetch_http_client()?;
+    let mut current = ensure_public_url(url).await?;
+    let origin_host = current.host_str().map(str::to_string);
+    for _ in 0..=MAX_FETCH_REDIRECTS {
+        let mut request = client.get(current.clone()).headers(headers.clone());
+        if let Some(source) = auth.filter(|_| current.host_str() == origin_host.as_deref()) {
+            request = apply_info_source_auth(request, source);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| format!("请求失败: {}", e))?;
+        if !response.status().is_redirection() {
+            return Ok(response);
+        }
+        let location = response
+            .headers()
+            .get(reqwest::header::LOCATION)
+            .and_then(|value| value.to_str().ok())
+            .ok_or_else(|| format!("HTTP {} 缺少跳转地址", response.status()))?;
+        let next = current
+            .join(location)
+            .map_err(|e| format!("跳转地址无效: {}", e))?;
+        current = ensure_public_url(next.as_str()).await?;
+    }
+    Err("跳转次数过多".to_string())
+}
+
+fn apply_info_source_auth(
+    request: reqwest::RequestBuilder,
+    source: &InfoSource,
+) -> reqwest::RequestBuilder {
+    let Some(value) = source
+        .auth_value
+        .as_deref()
+        .filter(|value| !value.is_empty())
+    else {
+        return request;
+    };
+    match source.auth_type.as_str() {
+        "basic" => {
+            let (user, password) = value.split_once(':').unwrap_or((value, ""));
+            request.basic_auth(user, Some(password))
+        }
+        "bearer" => request.bearer_auth(value),
+        _ => request,
+    }
+}
+
+/// Resolves names for `fetch_http_client`, failing when any address is not public.
+/// Checking in the resolver itself means a name cannot pass `ensure_public_url`
+/// and then rebind to a private address for the actual connection. Requests sent
+/// through a proxy are resolved by the proxy, so only `ensure_public_url` applies.
+struct PublicOnlyResolver;
+
+impl reqwest::dns::Resolve for PublicOnlyResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        Box::pin(async move {
+            // The connector fills in the port from the URL
+            let addrs = resolve_public_addrs(name.as_str(), 0).await?;
+            Ok(Box::new(addrs.into_iter()) as reqwest::dns::Addrs)
+        })
+    }
+}
+
+async fn resolve_public_addrs(host: &str, port: u16) -> Result<Vec<std::net::SocketAddr>, String> {
+    let addrs: Vec<std::net::SocketAddr> = tokio::net::lookup_host((host, port))
+        .await
+        .map_err(|e| format!("无法解析主机 {}: {}", host, e))?
+        .collect();
+    if addrs.is_empty() {
+        return Err(format!("无法解析主机 {}", host));
+    }
+    if let Some(addr) = addrs.iter().find(|addr| !is_public_ip(addr.ip())) {
+        return Err(format!("拒绝访问内网或本机地址: {} ({})", host, addr.ip()));
+    }
+    Ok(addrs)
+}
+
+/// Rejects non-http(s) schemes and hosts that resolve to loopback, private,
+/// link-local or otherwise non-public addresses.
+async fn ensure_public_url(url: &str) -> Result<reqwest::Url, String> {
+    let parsed = reqwest::Url::parse(url.trim()).map_err(|e| format!("链接无效: {}", e))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err("Only http/https links are allowed".to_string());
+    }
+    let host = parsed
+        .host_str()
+        .map(|host| host.trim_start_matches('[').trim_end_matches(']'))
+        .filter(|host| !host.is_empty())
+        .ok_or_else(|| "链接缺少主机名".to_string())?;
+    let port = parsed.port_or_known_default().unwrap_or(80);
+    resolve_public_addrs(host, port).await?;
+    Ok(parsed)
+}
+
+fn is_public_ip(ip: std::net::IpAddr) -> bool {
+    match ip {
+        std::net::IpAddr::V4(v4) => {
+            let [a, b, ..] = v4.octets();
+            !(v4.is_private()
+                || v4.is_loopback()
+                || v4.is_link_local()
+                || v4.is_unspecified()
+                || v4.is_broadcast()
+                || v4.is_multicast()
+                || v4.is_documentation()
+                // 100.64.0.0/10 carrier-grade NAT
+                || (a == 100 && (b & 0xc0) == 64)
+                || a == 0)
+        }
+        std::net::IpAddr::V6(v6) => {
+            if let Some(v4) = v6.to_ipv4_mapped() {
+                return is_public_ip(std::net::IpAddr::V4(v4));
+            }
+            let segments = v6.segments();
+            let first = segments[0];
+            !(v6.is_loopback()
+                || v6.is_unspecified()
+                || v6.is_multicast()
+                // fc00::/7 unique local, fe80::/10 link-local
+                || (first & 0xfe00) == 0xfc00
+                || (first & 0xffc0) == 0xfe80
+                // 64:ff9b::/96 NAT64, which can reach any IPv4 address
+                || segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0]
+                // 2001:db8::/32 documentation
+                || (first == 0x2001 && segments[1] == 0x0db8))
+        }
+    }
+}
+
+fn max_feed_bytes() -> usize {
+    env::var("ZHAOXI_MAX_FEED_BYTES")
+        .ok()
+        .and_then(|value| value.trim().parse::<usize>().ok())
+        .filter(|value| *value > 0)
+        .unwrap_or(DEFAULT_MAX_FEED_BYTES)
+}
+
+/// Streams a response body, aborting as soon as it grows past `limit` bytes.
+async fn read_body_with_limit(
+    mut response: reqwest::Response,
+    limit: usize,
+) -> Result<Vec<u8>, String> {
+    let too_large = || format!("订阅内容过大（超过 {} 字节）", limit);
+    if response
+        .content_length()
+        .is_some_and(|length| length > limit as u64)
+    {
+        return Err(too_large());
+    }
+
+    let mut body = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| format!("读取响应失败: {}", e))?
+    {
+        if body.len() + chunk.len() > limit {
+            return Err(too_large());
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body)
+}
+
+/// Items kept from one feed. `empty` marks a feed that parsed fine but had no
+/// entries at all, as opposed to entries that were all filtered out.
+struct FetchedFeed {
+    items: Vec<InfoItem>,
+    empty: bool,
+    title: Option<String>,
+    entry_count: usize,
+    validators: FeedValidators,
+}
+
+/// `ETag`/`Last-Modified` of a feed response, replayed as `If-None-Match`/
+/// `If-Modified-Since` so an unchanged feed answers 304 without a body.
+#[derive(Debug, Clone, Default)]
+struct FeedValidators {
+    etag: Option<String>,
+    last_modified: Option<String>,
 }
 
 async fn fetch_source_items(
     source: &InfoSource,
-    settings: &InfoSettings,
-) -> Result<Vec<InfoItem>, String> {
-    let client = reqwest::Client::new();
-    let response = client
-        .get(&source.url)
-        .send()
-        .await
-        .map_err(|e| format!("请求失败: {}", e))?;
+    filter: &InfoKeywordFilter,
+) -> Result<FetchedFeed, String> {
+    fetch_source_items_if_changed(source, filter, &FeedValidators::default())
+        .await?
+        .ok_or_else(|| format!("HTTP {}", reqwest::StatusCode::NOT_MODIFIED))
+}
+
+/// Conditional `fetch_source_items`; `None` when the feed is unchanged since
+/// `validators` were recorded.
+async fn fetch_source_items_if_changed(
+    source: &InfoSource,
+    filter: &InfoKeywordFilter,
+    validators: &FeedValidators,
+) -> Result<Option<FetchedFeed>, String> {
+    use reqwest::header::{
+        HeaderMap, HeaderName, HeaderValue, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED,
+    };
+
+    let mut headers = HeaderMap::new();
+    let conditions = [
+        (IF_NONE_MATCH, validators.etag.as_deref()),
+        (IF_MODIFIED_SINCE, validators.last_modified.as_deref()),
+    ];
+    for (name, value) in conditions {
+        if let Some(value) = value.and_then(|value| HeaderValue::from_str(value).ok()) {
+            headers.insert(name, value);
+        }
+    }
+    let response = guarded_get_with_headers(&source.url, Some(source), headers).await?;
+    if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+        return Ok(None);
+    }
     if !response.status().is_success() {
         return Err(format!("HTTP {}", response.status()));
     }
-    let bytes = response
-        .bytes()
+    let header_value = |name: HeaderName| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+    };
+    let validators = FeedValidators {
+        etag: header_value(ETAG),
+        last_modified: header_value(LAST_MODIFIED),
+    };
+    let bytes = read_body_with_limit(response, max_feed_bytes()).await?;
+    process_feed_body(source, filter, &bytes, validators)
         .await
-        .map_err(|e| format!("读取响应失败: {}", e))?;
-    let feed =
-        feed_rs::parser::parse(bytes.as_ref()).map_err(|e| format!("解析 RSS/Atom 失败: {}", e))?;
+        .map(Some)
+}
+
+/// Turns a downloaded feed body into scored items. A body that still leads with
+/// the entry recorded on the source reuses that fetch's items without parsing.
+async fn process_feed_body(
+    source: &InfoSource,
+    filter: &InfoKeywordFilter,
+    bytes: &[u8],
+    validators: FeedValidators,
+) -> Result<FetchedFeed, String> {
+    let top_link = sniff_top_link(bytes);
+    let today = local_today_string();
+    if let Some(top_link) = &top_link {
+        if let Some(cached) = load_cached_feed_items(source, top_link, &filter.key, &today).await {
+            return Ok(FetchedFeed {
+                items: cached.items,
+                empty: false,
+                title: cached.title,
+                entry_count: cached.entry_count,
+                validators,
+            });
+        }
+    }
+
+    // The Content-Type is not trusted either way: feeds are often served as text/html
+    let feed = feed_rs::parser::parse(bytes).map_err(|e| {
+        if looks_like_html(bytes) {
+            "URL returned HTML, not a feed".to_string()
+        } else {
+            format!("解析 RSS/Atom 失败: {}", e)
+        }
+    })?;
+    let empty = feed.entries.is_empty();
+    let entry_count = feed.entries.len();
+    let feed_title = feed
+        .title
+        .as_ref()
+        .map(|text| text.content.trim().to_string())
+        .filter(|text| !text.is_empty());
 
-    let include = normalize_keywords(settings.include_keywords.clone());
-    let exclude = normalize_keywords(settings.exclude_keywords.clone());
     let now = chrono::Utc::now();
-    let fetched_at = chrono::Local::now().to_rfc3339();
+    let fetched_at = local_now_rfc3339();
     let mut items = Vec::new();
 
     for (index, entry) in feed.entries.into_iter().enumerate() {
@@ -3803,44 +9808,13 @@ async fn fetch_source_items(
             continue;
         }
 
-        let haystack = format!(
-            "{} {}",
-            title.to_lowercase(),
-            summary.clone().unwrap_or_default().to_lowercase()
-        );
-        if exclude.iter().any(|keyword| haystack.contains(keyword)) {
+        let published = entry.published.or(entry.updated);
+        let Some((score, matched_keywords)) =
+            score_info_entry(&title, summary.as_deref(), published, filter, now)
+        else {
             continue;
-        }
-
-        let matched_keywords = if include.is_empty() {
-            Vec::new()
-        } else {
-            include
-                .iter()
-                .filter(|keyword| haystack.contains(keyword.as_str()))
-                .cloned()
-                .collect::<Vec<String>>()
         };
-        if !include.is_empty() && matched_keywords.is_empty() {
-            continue;
-        }
-
-        let published_at = entry
-            .published
-            .or(entry.updated)
-            .map(|item| item.to_rfc3339());
-        let mut score = matched_keywords.len() as f64;
-        if let Some(published) = entry.published.or(entry.updated) {
-            let hours = (now - published.with_timezone(&chrono::Utc)).num_hours();
-            if hours <= 24 {
-                score += 1.0;
-            } else if hours <= 72 {
-                score += 0.5;
-            }
-        }
-        if include.is_empty() {
-            score += 0.1;
-        }
+        let published_at = published.map(|item| item.to_rfc3339());
 
         items.push(InfoItem {
             id: format!("temp-{}-{}", source.id, index),
@@ -3855,7 +9829,245 @@ async fn fetch_source_items(
         });
     }
 
-    Ok(items)
+    if let Some(top_link) = top_link.filter(|_| !empty) {
+        let cached = CachedFeedItems {
+            url: source.url.clone(),
+            filter_key: filter.key.clone(),
+            date: today,
+            title: feed_title.clone(),
+            entry_count,
+            items: items.clone(),
+        };
+        // A failed write only means the next fetch parses the feed again
+        let _ = store_cached_feed_items(&source.id, &top_link, &cached).await;
+    }
+
+    Ok(FetchedFeed {
+        items,
+        empty,
+        title: feed_title,
+        entry_count,
+        validators,
+    })
+}
+
+/// Scored items from a source's previous fetch, persisted on `info_sources`
+/// (`feed_items_json`) next to the top link they were scored for. Entries are
+/// only reused on the same day with the same keyword settings.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CachedFeedItems {
+    url: String,
+    filter_key: String,
+    date: String,
+    title: Option<String>,
+    entry_count: usize,
+    items: Vec<InfoItem>,
+}
+
+async fn load_cached_feed_items(
+    source: &InfoSource,
+    top_link: &str,
+    filter_key: &str,
+    date: &str,
+) -> Option<CachedFeedItems> {
+    let pool = get_db_pool().ok()?;
+    let cached_json: Option<String> = sqlx::query_scalar(
+        "SELECT feed_items_json FROM info_sources WHERE id = ?1 AND last_top_link = ?2",
+    )
+    .bind(&source.id)
+    .bind(top_link)
+    .fetch_optional(pool)
+    .await
+    .ok()??;
+    serde_json::from_str::<CachedFeedItems>(&cached_json?)
+        .ok()
+        .filter(|cached| {
+            cached.url == source.url && cached.filter_key == filter_key && cached.date == date
+        })
+}
+
+async fn store_cached_feed_items(
+    source_id: &str,
+    top_link: &str,
+    cached: &CachedFeedItems,
+) -> Result<(), String> {
+    let pool = get_db_pool()?;
+    let cached_json = serde_json::to_string(cached)
+        .map_err(|e| format!("Failed to serialize feed items: {}", e))?;
+    sqlx::query(
+        "UPDATE info_sources SET last_top_link = ?1, feed_items_json = ?2, updated_at = CURRENT_TIMESTAMP WHERE id = ?3",
+    )
+    .bind(top_link)
+    .bind(cached_json)
+    .bind(source_id)
+    .execute(pool)
+    .await
+    .map_err(|e| format!("Failed to store feed items: {}", e))?;
+    Ok(())
+}
+
+/// Link of the first `<item>`/`<entry>`, read straight from the raw XML so an
+/// unchanged feed is recognized without a full parse. The value is only compared
+/// against an earlier sniff, so it is kept as written (entities undecoded).
+/// JSON Feeds and entries without a link yield `None`.
+fn sniff_top_link(bytes: &[u8]) -> Option<String> {
+    let text = std::str::from_utf8(bytes).ok()?;
+    let (start, tag) = ["item", "entry"]
+        .into_iter()
+        .filter_map(|tag| find_element_start(text, tag).map(|start| (start, tag)))
+        .min()?;
+    let end = start + text[start..].find(&format!("</{}>", tag))?;
+    let entry = &text[start..end];
+    let link = &entry[find_element_start(entry, "link")?..];
+    let open_tag = &link[..link.find('>')?];
+    let href = match open_tag.find("href=") {
+        Some(position) => {
+            let value = &open_tag[position + "href=".len()..];
+            let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+            let value = &value[1..];
+            &value[..value.find(quote)?]
+        }
+        None => {
+            let body = &link[open_tag.len() + 1..];
+            let body = &body[..body.find("</link>")?];
+            body.trim()
+                .trim_start_matches("<![CDATA[")
+                .trim_end_matches("]]>")
+        }
+    };
+    let href = href.trim();
+    (!href.is_empty()).then(|| href.to_string())
+}
+
+/// Byte offset of the first `<tag` that opens that exact element (not `<tagname`).
+fn find_element_start(text: &str, tag: &str) -> Option<usize> {
+    let pattern = format!("<{}", tag);
+    let mut offset = 0;
+    while let Some(position) = text[offset..].find(&pattern) {
+        let start = offset + position;
+        let next = text[start + pattern.len()..].chars().next();
+        if next.is_some_and(|c| c == '>' || c == '/' || c.is_whitespace()) {
+            return Some(start);
+        }
+        offset = start + pattern.len();
+    }
+    None
+}
+
+/// Sniffs the start of a body for an HTML document, for servers that send pages
+/// with a feed-ish content type.
+fn looks_like_html(bytes: &[u8]) -> bool {
+    let head = String::from_utf8_lossy(&bytes[..bytes.len().min(512)]).to_ascii_lowercase();
+    let head = head.trim_start_matches('\u{feff}').trim_start();
+    head.starts_with("<!doctype html") || head.starts_with("<html")
+}
+
+/// A normalized keyword: plain keywords match as lowercase substrings, `re:`
+/// keywords as case-insensitive regexes against the title or the summary.
+enum KeywordMatcher {
+    Substring(String),
+    Pattern { keyword: String, regex: Regex },
+}
+
+impl KeywordMatcher {
+    fn keyword(&self) -> &str {
+        match self {
+            KeywordMatcher::Substring(keyword) => keyword,
+            KeywordMatcher::Pattern { keyword, .. } => keyword,
+        }
+    }
+
+    fn is_match(&self, haystack: &str, title: &str, summary: &str) -> bool {
+        match self {
+            KeywordMatcher::Substring(keyword) => haystack.contains(keyword.as_str()),
+            KeywordMatcher::Pattern { regex, .. } => {
+                regex.is_match(title) || regex.is_match(summary)
+            }
+        }
+    }
+}
+
+/// Include/exclude keywords compiled once per refresh. Patterns are checked when
+/// saved, so an invalid one (e.g. from an old backup) is skipped instead of
+/// failing the fetch.
+struct InfoKeywordFilter {
+    include: Vec<KeywordMatcher>,
+    exclude: Vec<KeywordMatcher>,
+    /// Identifies the keyword settings, so cached scores are not reused after they change.
+    key: String,
+}
+
+impl InfoKeywordFilter {
+    fn from_settings(settings: &InfoSettings) -> Self {
+        let include = normalize_keywords(settings.include_keywords.clone());
+        let exclude = normalize_keywords(settings.exclude_keywords.clone());
+        Self {
+            key: format!("{:?}|{:?}", include, exclude),
+            include: compile_keywords(include),
+            exclude: compile_keywords(exclude),
+        }
+    }
+}
+
+fn compile_keywords(keywords: Vec<String>) -> Vec<KeywordMatcher> {
+    keywords
+        .into_iter()
+        .filter_map(|keyword| {
+            let Some(pattern) = keyword.strip_prefix(KEYWORD_REGEX_PREFIX) else {
+                return Some(KeywordMatcher::Substring(keyword));
+            };
+            RegexBuilder::new(pattern)
+                .case_insensitive(true)
+                .build()
+                .ok()
+                .map(|regex| KeywordMatcher::Pattern { keyword, regex })
+        })
+        .collect()
+}
+
+/// Applies the keyword filter and scoring to one entry. `None` means the entry is
+/// filtered out (hits an exclude keyword, or misses every include keyword).
+fn score_info_entry(
+    title: &str,
+    summary: Option<&str>,
+    published: Option<chrono::DateTime<chrono::Utc>>,
+    filter: &InfoKeywordFilter,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Option<(f64, Vec<String>)> {
+    let summary = summary.unwrap_or_default();
+    let haystack = format!("{} {}", title.to_lowercase(), summary.to_lowercase());
+    if filter
+        .exclude
+        .iter()
+        .any(|keyword| keyword.is_match(&haystack, title, summary))
+    {
+        return None;
+    }
+
+    let matched_keywords = filter
+        .include
+        .iter()
+        .filter(|keyword| keyword.is_match(&haystack, title, summary))
+        .map(|keyword| keyword.keyword().to_string())
+        .collect::<Vec<String>>();
+    if !filter.include.is_empty() && matched_keywords.is_empty() {
+        return None;
+    }
+
+    let mut score = matched_keywords.len() as f64;
+    if let Some(published) = published {
+        let hours = (now - published).num_hours();
+        if hours <= 24 {
+            score += 1.0;
+        } else if hours <= 72 {
+            score += 0.5;
+        }
+    }
+    if filter.include.is_empty() {
+        score += 0.1;
+    }
+    Some((score, matched_keywords))
 }
 
 fn row_to_info_item(row: sqlx::sqlite::SqliteRow) -> Result<InfoItem, String> {
@@ -3879,11 +10091,34 @@ fn parse_keywords_json(raw: String) -> Result<Vec<String>, String> {
         .map_err(|e| format!("Failed to parse keywords json: {}", e))
 }
 
+/// Keywords starting with this prefix are treated as regular expressions.
+const KEYWORD_REGEX_PREFIX: &str = "re:";
+
+/// Rejects a `re:` keyword whose pattern does not compile, so a typo surfaces
+/// when the settings are saved rather than as a keyword that never matches.
+fn validate_keyword_pattern(keyword: &str) -> Result<(), String> {
+    let keyword = keyword.trim();
+    if let Some(pattern) = keyword.strip_prefix(KEYWORD_REGEX_PREFIX) {
+        RegexBuilder::new(pattern)
+            .build()
+            .map_err(|e| format!("关键词正则无效 {}: {}", keyword, e))?;
+    }
+    Ok(())
+}
+
 fn normalize_keywords(keywords: Vec<String>) -> Vec<String> {
     let mut seen = HashSet::new();
     keywords
         .into_iter()
-        .map(|item| item.trim().to_lowercase())
+        .map(|item| {
+            let trimmed = item.trim();
+            // Patterns keep their case: `\D` and `\d` mean different things
+            if trimmed.starts_with(KEYWORD_REGEX_PREFIX) {
+                trimmed.to_string()
+            } else {
+                trimmed.to_lowercase()
+            }
+        })
         .filter(|item| !item.is_empty())
         .filter(|item| seen.insert(item.clone()))
         .collect()
@@ -3899,6 +10134,46 @@ fn normalize_push_time(input: &str) -> String {
     "09:00".to_string()
 }
 
+/// `None` when the request leaves quiet hours alone, `Some(None)` to clear them and
+/// `Some(Some((start, end)))` for a new `HH:MM` window.
+fn normalize_quiet_hours(
+    start: Option<&str>,
+    end: Option<&str>,
+) -> Result<Option<Option<(String, String)>>, String> {
+    let parse = |value: &str| {
+        chrono::NaiveTime::parse_from_str(value.trim(), "%H:%M")
+            .map(|time| time.format("%H:%M").to_string())
+            .map_err(|_| format!("静默时段时间格式应为 HH:MM: {}", value))
+    };
+    match (start.map(str::trim), end.map(str::trim)) {
+        (None, None) => Ok(None),
+        (Some(""), Some("")) => Ok(Some(None)),
+        (Some(start), Some(end)) if !start.is_empty() && !end.is_empty() => {
+            Ok(Some(Some((parse(start)?, parse(end)?))))
+        }
+        _ => Err("静默时段需同时设置开始和结束时间".to_string()),
+    }
+}
+
+/// Whether `now` falls in the configured quiet hours. A window whose start is
+/// after its end wraps past midnight (23:00–07:00); equal ends mean no window.
+fn in_quiet_hours(settings: &InfoSettings, now: chrono::NaiveTime) -> bool {
+    let (Some(start), Some(end)) = (&settings.quiet_hours_start, &settings.quiet_hours_end) else {
+        return false;
+    };
+    let (Ok(start), Ok(end)) = (
+        chrono::NaiveTime::parse_from_str(start, "%H:%M"),
+        chrono::NaiveTime::parse_from_str(end, "%H:%M"),
+    ) else {
+        return false;
+    };
+    if start <= end {
+        start <= now && now < end
+    } else {
+        now >= start || now < end
+    }
+}
+
 fn weather_code_to_condition(code: i32) -> &'static str {
     match code {
         0 => "clear",
@@ -3942,14 +10217,79 @@ fn wind_speed_to_level(speed_ms: f64) -> String {
     format!("{}级", level)
 }
 
+/// Parses a fixed UTC offset such as `+08:00`, `-0530`, `UTC` or `Z`.
+fn parse_utc_offset(input: &str) -> Option<chrono::FixedOffset> {
+    let trimmed = input.trim();
+    if trimmed.eq_ignore_ascii_case("utc") || trimmed.eq_ignore_ascii_case("z") {
+        return chrono::FixedOffset::east_opt(0);
+    }
+    let (sign, rest) = match trimmed.chars().next()? {
+        '+' => (1, &trimmed[1..]),
+        '-' => (-1, &trimmed[1..]),
+        _ => return None,
+    };
+    let digits = rest.replace(':', "");
+    if digits.len() != 4 || !digits.chars().all(|ch| ch.is_ascii_digit()) {
+        return None;
+    }
+    let hours: i32 = digits[..2].parse().ok()?;
+    let minutes: i32 = digits[2..].parse().ok()?;
+    if hours > 14 || minutes > 59 {
+        return None;
+    }
+    chrono::FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+/// Current time in the app timezone: the `utcOffset` app setting, else
+/// `ZHAOXI_UTC_OFFSET` (e.g. `+08:00`), else the system local offset. All "today"
+/// keys go through this. Stored timestamps such as `created_at` stay in UTC.
+fn app_now() -> chrono::DateTime<chrono::FixedOffset> {
+    let offset = cached_app_settings()
+        .utc_offset
+        .or_else(|| env::var("ZHAOXI_UTC_OFFSET").ok())
+        .and_then(|value| parse_utc_offset(&value));
+    to_app_time(chrono::Utc::now(), offset)
+}
+
+/// `now` in `offset`, or in the system local offset when there is none.
+fn to_app_time(
+    now: chrono::DateTime<chrono::Utc>,
+    offset: Option<chrono::FixedOffset>,
+) -> chrono::DateTime<chrono::FixedOffset> {
+    match offset {
+        Some(offset) => now.with_timezone(&offset),
+        None => now.with_timezone(&chrono::Local).fixed_offset(),
+    }
+}
+
+/// Start of `date` in `offset`, as UTC, for comparing app-local days against
+/// stored UTC timestamps.
+fn local_midnight_utc(
+    date: chrono::NaiveDate,
+    offset: chrono::FixedOffset,
+) -> Option<chrono::DateTime<chrono::Utc>> {
+    date.and_hms_opt(0, 0, 0)?
+        .and_local_timezone(offset)
+        .single()
+        .map(|midnight| midnight.with_timezone(&chrono::Utc))
+}
+
 fn local_today_string() -> String {
-    chrono::Local::now().format("%Y-%m-%d").to_string()
+    app_now().format("%Y-%m-%d").to_string()
+}
+
+fn local_now_rfc3339() -> String {
+    app_now().to_rfc3339()
 }
 
 fn default_info_source_type() -> String {
     "rss".to_string()
 }
 
+fn default_info_source_auth_type() -> String {
+    "none".to_string()
+}
+
 async fn insert_info_refresh_log(
     trigger_type: &str,
     success: bool,
@@ -3964,10 +10304,7 @@ async fn insert_info_refresh_log(
         "INSERT INTO info_refresh_logs (id, trigger_type, success, message, fetched_count, kept_count)
          VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
     )
-    .bind(format!(
-        "info-log-{}",
-        chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
-    ))
+    .bind(format!("info-log-{}", new_entity_id()))
     .bind(trigger_type)
     .bind(if success { 1 } else { 0 })
     .bind(message)
@@ -3978,9 +10315,7 @@ async fn insert_info_refresh_log(
 }
 
 fn app_data_root(app: &AppHandle) -> Result<PathBuf, String> {
-    app.path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to resolve app data dir: {}", e))
+    resolve_data_dir(app)
 }
 
 fn backup_work_dir(app: &AppHandle) -> Result<PathBuf, String> {
@@ -3990,14 +10325,51 @@ fn backup_work_dir(app: &AppHandle) -> Result<PathBuf, String> {
     Ok(dir)
 }
 
+const BACKUP_PROGRESS_EVERY_ROWS: usize = 200;
+
+/// Emits `backup_progress` events while tables are read or restored, so a long
+/// export or import does not look frozen.
+struct BackupProgress<'a> {
+    app: &'a AppHandle,
+    /// `export`, `rollback` (the safety copy taken before an import) or `import`.
+    phase: &'static str,
+}
+
+impl BackupProgress<'_> {
+    fn emit(&self, table: &str, rows_done: usize, rows_total: usize) {
+        let event = BackupProgressEvent {
+            phase: self.phase.to_string(),
+            table: table.to_string(),
+            table_index: SQLITE_BACKUP_TABLES
+                .iter()
+                .position(|name| *name == table)
+                .unwrap_or_default(),
+            table_count: SQLITE_BACKUP_TABLES.len(),
+            rows_done,
+            rows_total,
+        };
+        let _ = self.app.emit("backup_progress", &event);
+    }
+}
+
 async fn build_backup_envelope(
     app: &AppHandle,
     local_state: Option<BackupLocalState>,
     include_secrets: bool,
+    delta_since: Option<String>,
+    excluded_tables: Vec<String>,
+    phase: &'static str,
 ) -> Result<(BackupEnvelope, Vec<String>, HashMap<String, usize>), String> {
-    let sqlite = collect_sqlite_backup().await?;
-    let table_counts = sqlite_table_counts_from_backup(&sqlite);
     let mut warnings = Vec::new();
+    let progress = BackupProgress { app, phase };
+    let sqlite = collect_sqlite_backup(
+        delta_since.as_deref(),
+        &excluded_tables,
+        &mut warnings,
+        &progress,
+    )
+    .await?;
+    let table_counts = sqlite_table_counts_from_backup(&sqlite);
     let agent_files = collect_agent_files(app, &mut warnings)?;
     let payload = BackupPayload {
         sqlite,
@@ -4011,32 +10383,137 @@ async fn build_backup_envelope(
             exported_at: chrono::Utc::now().to_rfc3339(),
             platform: env::consts::OS.to_string(),
             include_secrets,
+            delta_since,
+            excluded_tables,
         },
         payload,
     };
     Ok((envelope, warnings, table_counts))
 }
 
-async fn collect_sqlite_backup() -> Result<BackupSqliteData, String> {
-    Ok(BackupSqliteData {
-        todos: query_table_rows("todos").await?,
-        projects: query_table_rows("projects").await?,
-        events: query_table_rows("events").await?,
-        personal_tasks: query_table_rows("personal_tasks").await?,
-        inspirations: query_table_rows("inspirations").await?,
-        info_sources: query_table_rows("info_sources").await?,
-        info_settings: query_table_rows("info_settings").await?,
-        info_items_daily: query_table_rows("info_items_daily").await?,
-        info_refresh_logs: query_table_rows("info_refresh_logs").await?,
-        agent_sessions: query_table_rows("agent_sessions").await?,
-        agent_events: query_table_rows("agent_events").await?,
-        agent_action_audits: query_table_rows("agent_action_audits").await?,
-    })
+/// Reads every table inside one transaction so the backup is a single point-in-time view.
+/// With `since`, only rows changed after it are read (see `query_table_rows`).
+async fn collect_sqlite_backup(
+    since: Option<&str>,
+    excluded: &[String],
+    warnings: &mut Vec<String>,
+    progress: &BackupProgress<'_>,
+) -> Result<BackupSqliteData, String> {
+    let pool = get_db_pool()?;
+    let _maintenance = DB_MAINTENANCE_LOCK.lock().await;
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to start backup read transaction: {}", e))?;
+    let data = BackupSqliteData {
+        todos: query_table_rows(&mut tx, "todos", since, excluded, warnings, progress).await?,
+        recurring_todo_templates: query_table_rows(
+            &mut tx,
+            "recurring_todo_templates",
+            since,
+            excluded,
+            warnings,
+            progress,
+        )
+        .await?,
+        projects: query_table_rows(&mut tx, "projects", since, excluded, warnings, progress)
+            .await?,
+        events: query_table_rows(&mut tx, "events", since, excluded, warnings, progress).await?,
+        personal_tasks: query_table_rows(
+            &mut tx,
+            "personal_tasks",
+            since,
+            excluded,
+            warnings,
+            progress,
+        )
+        .await?,
+        inspirations: query_table_rows(
+            &mut tx,
+            "inspirations",
+            since,
+            excluded,
+            warnings,
+            progress,
+        )
+        .await?,
+        info_sources: query_table_rows(
+            &mut tx,
+            "info_sources",
+            since,
+            excluded,
+            warnings,
+            progress,
+        )
+        .await?,
+        info_settings: query_table_rows(
+            &mut tx,
+            "info_settings",
+            since,
+            excluded,
+            warnings,
+            progress,
+        )
+        .await?,
+        info_items_daily: query_table_rows(
+            &mut tx,
+            "info_items_daily",
+            since,
+            excluded,
+            warnings,
+            progress,
+        )
+        .await?,
+        info_refresh_logs: query_table_rows(
+            &mut tx,
+            "info_refresh_logs",
+            since,
+            excluded,
+            warnings,
+            progress,
+        )
+        .await?,
+        agent_sessions: query_table_rows(
+            &mut tx,
+            "agent_sessions",
+            since,
+            excluded,
+            warnings,
+            progress,
+        )
+        .await?,
+        agent_events: query_table_rows(
+            &mut tx,
+            "agent_events",
+            since,
+            excluded,
+            warnings,
+            progress,
+        )
+        .await?,
+        agent_action_audits: query_table_rows(
+            &mut tx,
+            "agent_action_audits",
+            since,
+            excluded,
+            warnings,
+            progress,
+        )
+        .await?,
+    };
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to finish backup read transaction: {}", e))?;
+    Ok(data)
 }
 
 fn sqlite_table_counts_from_backup(sqlite: &BackupSqliteData) -> HashMap<String, usize> {
     let mut counts = HashMap::new();
     counts.insert("todos".to_string(), sqlite.todos.len());
+    counts.insert(
+        "recurring_todo_templates".to_string(),
+        sqlite.recurring_todo_templates.len(),
+    );
     counts.insert("projects".to_string(), sqlite.projects.len());
     counts.insert("events".to_string(), sqlite.events.len());
     counts.insert("personal_tasks".to_string(), sqlite.personal_tasks.len());
@@ -4054,16 +10531,83 @@ fn sqlite_table_counts_from_backup(sqlite: &BackupSqliteData) -> HashMap<String,
     counts
 }
 
-async fn query_table_rows(table: &str) -> Result<Vec<Value>, String> {
-    let pool = get_db_pool()?;
-    let sql = format!("SELECT * FROM {}", quote_ident(table));
-    let rows = sqlx::query(&sql)
-        .fetch_all(pool)
+/// Tables whose rows are never modified after insert, so `created_at` is a safe
+/// delta cutoff for them.
+const APPEND_ONLY_BACKUP_TABLES: [&str; 4] = [
+    "info_refresh_logs",
+    "agent_sessions",
+    "agent_events",
+    "agent_action_audits",
+];
+
+/// Reads a table for backup. With `since`, filters on `updated_at`, or on `created_at`
+/// for append-only tables; any other table cannot tell which rows were edited, so it
+/// is exported in full and a warning is recorded.
+async fn query_table_rows(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    table: &str,
+    since: Option<&str>,
+    excluded: &[String],
+    warnings: &mut Vec<String>,
+    progress: &BackupProgress<'_>,
+) -> Result<Vec<Value>, String> {
+    if excluded.iter().any(|name| name == table) {
+        progress.emit(table, 0, 0);
+        return Ok(Vec::new());
+    }
+    let mut sql = format!("SELECT * FROM {}", quote_ident(table));
+    let mut delta = false;
+    if let Some(since) = since {
+        let columns = get_table_columns(tx, table).await?;
+        let timestamp_column = if columns.contains("updated_at") {
+            Some("updated_at")
+        } else if APPEND_ONLY_BACKUP_TABLES.contains(&table) && columns.contains("created_at") {
+            Some("created_at")
+        } else {
+            None
+        };
+        match timestamp_column {
+            Some(column) => {
+                // datetime() copes with both CURRENT_TIMESTAMP and RFC3339 values
+                sql.push_str(&format!(" WHERE datetime({}) > datetime(?1)", column));
+                delta = true;
+            }
+            None => warnings.push(format!(
+                "表 {} 不记录修改时间，已完整导出（{} 之后的增量不适用）",
+                table, since
+            )),
+        }
+    }
+    let mut query = sqlx::query(&sql);
+    if delta {
+        query = query.bind(since);
+    }
+    let rows = query
+        .fetch_all(&mut **tx)
         .await
         .map_err(|e| format!("Failed to query table {}: {}", table, e))?;
+    progress.emit(table, rows.len(), rows.len());
     Ok(rows.into_iter().map(sqlite_row_to_json).collect())
 }
 
+/// Normalizes a delta export cutoff to SQLite's `YYYY-MM-DD HH:MM:SS` (UTC).
+fn normalize_since_timestamp(input: &str) -> Result<String, String> {
+    let format = "%Y-%m-%d %H:%M:%S";
+    if let Ok(value) = chrono::DateTime::parse_from_rfc3339(input) {
+        return Ok(value.with_timezone(&chrono::Utc).format(format).to_string());
+    }
+    if let Ok(value) = chrono::NaiveDateTime::parse_from_str(input, format) {
+        return Ok(value.format(format).to_string());
+    }
+    // A bare date means the start of that day in the app timezone
+    if let Ok(date) = chrono::NaiveDate::parse_from_str(input, "%Y-%m-%d") {
+        if let Some(value) = local_midnight_utc(date, *app_now().offset()) {
+            return Ok(value.format(format).to_string());
+        }
+    }
+    Err(format!("无法解析起始时间: {}", input))
+}
+
 fn sqlite_row_to_json(row: sqlx::sqlite::SqliteRow) -> Value {
     let mut map = serde_json::Map::new();
     for column in row.columns() {
@@ -4214,7 +10758,20 @@ fn sanitize_backup_envelope(envelope: &mut BackupEnvelope) {
     envelope.meta.include_secrets = false;
     sanitize_json_value(&mut envelope.payload.local_state.workbench_storage);
     sanitize_json_value(&mut envelope.payload.local_state.workbench_agent_storage);
-    for server in &mut envelope.payload.agent_files.mcp_servers {
+    sanitize_mcp_server_env(&mut envelope.payload.agent_files.mcp_servers);
+    sanitize_info_source_auth(&mut envelope.payload.sqlite.info_sources);
+}
+
+fn sanitize_info_source_auth(rows: &mut [Value]) {
+    for row in rows {
+        if let Some(value) = row.get_mut("auth_value") {
+            *value = Value::Null;
+        }
+    }
+}
+
+fn sanitize_mcp_server_env(servers: &mut [McpServerConfig]) {
+    for server in servers {
         for (key, value) in &mut server.env {
             if is_sensitive_key(key) {
                 *value = String::new();
@@ -4227,7 +10784,11 @@ fn sanitize_json_value(value: &mut Value) {
     match value {
         Value::Object(map) => {
             for (key, entry) in map {
-                if is_sensitive_key(key) {
+                // Keychain references carry no secret, so they survive redaction
+                let is_reference = entry
+                    .as_str()
+                    .is_some_and(|value| value.trim().starts_with(KEYCHAIN_KEY_PREFIX));
+                if is_sensitive_key(key) && !is_reference {
                     *entry = Value::String(String::new());
                 } else {
                     sanitize_json_value(entry);
@@ -4254,7 +10815,8 @@ fn is_sensitive_key(key: &str) -> bool {
 
 async fn create_rollback_backup(app: &AppHandle) -> Result<(String, Vec<String>), String> {
     let mut warnings = Vec::new();
-    let (mut envelope, mut collect_warnings, _) = build_backup_envelope(app, None, false).await?;
+    let (mut envelope, mut collect_warnings, _) =
+        build_backup_envelope(app, None, false, None, Vec::new(), "rollback").await?;
     warnings.append(&mut collect_warnings);
     sanitize_backup_envelope(&mut envelope);
 
@@ -4272,14 +10834,26 @@ async fn create_rollback_backup(app: &AppHandle) -> Result<(String, Vec<String>)
     Ok((rollback_path.to_string_lossy().to_string(), warnings))
 }
 
-async fn restore_sqlite_data(sqlite: &BackupSqliteData) -> Result<(), String> {
+/// Replaces every table with the backup rows, or for a delta backup upserts the
+/// rows into the existing data.
+async fn restore_sqlite_data(
+    sqlite: &BackupSqliteData,
+    delta: bool,
+    excluded: &[String],
+    progress: &BackupProgress<'_>,
+) -> Result<(), String> {
     let pool = get_db_pool()?;
+    let _maintenance = DB_MAINTENANCE_LOCK.lock().await;
     let mut tx = pool
         .begin()
         .await
         .map_err(|e| format!("Failed to start import transaction: {}", e))?;
 
-    for table in SQLITE_BACKUP_TABLES {
+    // Tables the backup left out are not in it at all, so keep what is there now
+    let cleared = SQLITE_BACKUP_TABLES
+        .iter()
+        .filter(|table| !delta && !excluded.iter().any(|name| name == *table));
+    for table in cleared {
         let delete_sql = format!("DELETE FROM {}", quote_ident(table));
         sqlx::query(&delete_sql)
             .execute(&mut *tx)
@@ -4287,18 +10861,99 @@ async fn restore_sqlite_data(sqlite: &BackupSqliteData) -> Result<(), String> {
             .map_err(|e| format!("Failed to clear table {}: {}", table, e))?;
     }
 
-    insert_json_rows(&mut tx, "todos", &sqlite.todos).await?;
-    insert_json_rows(&mut tx, "projects", &sqlite.projects).await?;
-    insert_json_rows(&mut tx, "events", &sqlite.events).await?;
-    insert_json_rows(&mut tx, "personal_tasks", &sqlite.personal_tasks).await?;
-    insert_json_rows(&mut tx, "inspirations", &sqlite.inspirations).await?;
-    insert_json_rows(&mut tx, "info_sources", &sqlite.info_sources).await?;
-    insert_json_rows(&mut tx, "info_settings", &sqlite.info_settings).await?;
-    insert_json_rows(&mut tx, "info_items_daily", &sqlite.info_items_daily).await?;
-    insert_json_rows(&mut tx, "info_refresh_logs", &sqlite.info_refresh_logs).await?;
-    insert_json_rows(&mut tx, "agent_sessions", &sqlite.agent_sessions).await?;
-    insert_json_rows(&mut tx, "agent_events", &sqlite.agent_events).await?;
-    insert_json_rows(&mut tx, "agent_action_audits", &sqlite.agent_action_audits).await?;
+    insert_json_rows(&mut tx, "todos", &sqlite.todos, delta, progress).await?;
+    insert_json_rows(
+        &mut tx,
+        "recurring_todo_templates",
+        &sqlite.recurring_todo_templates,
+        delta,
+        progress,
+    )
+    .await?;
+    insert_json_rows(&mut tx, "projects", &sqlite.projects, delta, progress).await?;
+    insert_json_rows(&mut tx, "events", &sqlite.events, delta, progress).await?;
+    insert_json_rows(
+        &mut tx,
+        "personal_tasks",
+        &sqlite.personal_tasks,
+        delta,
+        progress,
+    )
+    .await?;
+    insert_json_rows(
+        &mut tx,
+        "inspirations",
+        &sqlite.inspirations,
+        delta,
+        progress,
+    )
+    .await?;
+    // Backups older than the status column only carry is_archived
+    sqlx::query(
+        r#"
+        UPDATE inspirations SET status = 'archived'
+        WHERE is_archived = 1 AND status <> 'archived'
+        "#,
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to backfill inspiration status: {}", e))?;
+    insert_json_rows(
+        &mut tx,
+        "info_sources",
+        &sqlite.info_sources,
+        delta,
+        progress,
+    )
+    .await?;
+    insert_json_rows(
+        &mut tx,
+        "info_settings",
+        &sqlite.info_settings,
+        delta,
+        progress,
+    )
+    .await?;
+    insert_json_rows(
+        &mut tx,
+        "info_items_daily",
+        &sqlite.info_items_daily,
+        delta,
+        progress,
+    )
+    .await?;
+    insert_json_rows(
+        &mut tx,
+        "info_refresh_logs",
+        &sqlite.info_refresh_logs,
+        delta,
+        progress,
+    )
+    .await?;
+    insert_json_rows(
+        &mut tx,
+        "agent_sessions",
+        &sqlite.agent_sessions,
+        delta,
+        progress,
+    )
+    .await?;
+    insert_json_rows(
+        &mut tx,
+        "agent_events",
+        &sqlite.agent_events,
+        delta,
+        progress,
+    )
+    .await?;
+    insert_json_rows(
+        &mut tx,
+        "agent_action_audits",
+        &sqlite.agent_action_audits,
+        delta,
+        progress,
+    )
+    .await?;
 
     tx.commit()
         .await
@@ -4310,13 +10965,18 @@ async fn insert_json_rows(
     tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
     table: &str,
     rows: &[Value],
+    upsert: bool,
+    progress: &BackupProgress<'_>,
 ) -> Result<(), String> {
     if rows.is_empty() {
         return Ok(());
     }
 
     let allowed_columns = get_table_columns(tx, table).await?;
-    for row in rows {
+    for (index, row) in rows.iter().enumerate() {
+        if index % BACKUP_PROGRESS_EVERY_ROWS == 0 {
+            progress.emit(table, index, rows.len());
+        }
         let Some(map) = row.as_object() else {
             continue;
         };
@@ -4336,8 +10996,14 @@ async fn insert_json_rows(
             .collect::<Vec<String>>()
             .join(", ");
         let placeholders = vec!["?"; keys.len()].join(", ");
+        let verb = if upsert {
+            "INSERT OR REPLACE"
+        } else {
+            "INSERT"
+        };
         let sql = format!(
-            "INSERT INTO {} ({}) VALUES ({})",
+            "{} INTO {} ({}) VALUES ({})",
+            verb,
             quote_ident(table),
             columns,
             placeholders
@@ -4354,6 +11020,7 @@ async fn insert_json_rows(
             .map_err(|e| format!("Failed to insert row into {}: {}", table, e))?;
     }
 
+    progress.emit(table, rows.len(), rows.len());
     Ok(())
 }
 
@@ -4469,6 +11136,181 @@ fn restore_user_skills(app: &AppHandle, skills: &[BackupSkillDir]) -> Result<(),
     Ok(())
 }
 
+fn import_agent_bundle_from_dir(
+    app: &AppHandle,
+    staging_dir: &Path,
+    input_path: &Path,
+) -> Result<AgentBundleSummary, String> {
+    let manifest_content = fs::read_to_string(staging_dir.join("manifest.json"))
+        .map_err(|_| "Bundle is missing manifest.json".to_string())?;
+    let manifest: Value = serde_json::from_str(&manifest_content)
+        .map_err(|e| format!("Failed to parse bundle manifest: {}", e))?;
+    let schema_version = manifest
+        .get("schemaVersion")
+        .and_then(|value| value.as_str())
+        .unwrap_or_default();
+    if schema_version != AGENT_BUNDLE_SCHEMA_VERSION {
+        return Err(format!(
+            "不支持的 bundle 版本: {} (期望 {})",
+            schema_version, AGENT_BUNDLE_SCHEMA_VERSION
+        ));
+    }
+
+    let existing = load_tooling_config(app)?;
+    let mut warnings = Vec::new();
+
+    let mut mcp_count = 0;
+    let mcp_path = staging_dir.join("mcp").join("servers.json");
+    if mcp_path.exists() {
+        let mut servers = load_user_mcp_servers(app)?;
+        let mut known: HashSet<String> = existing
+            .mcp_servers
+            .iter()
+            .map(|server| server.name.to_lowercase())
+            .collect();
+        for server in read_mcp_servers_from_path(&mcp_path)? {
+            if let Err(error) = validate_mcp_server(&server) {
+                warnings.push(format!("跳过 MCP 服务 {}: {}", server.name, error));
+                continue;
+            }
+            if !known.insert(server.name.to_lowercase()) {
+                warnings.push(format!("MCP 服务 {} 已存在，已跳过", server.name));
+                continue;
+            }
+            servers.push(server);
+            mcp_count += 1;
+        }
+        if mcp_count > 0 {
+            write_user_mcp_servers(app, &servers)?;
+        }
+    }
+
+    let mut command_count = 0;
+    let commands_dir = staging_dir.join("commands");
+    if let Ok(entries) = fs::read_dir(&commands_dir) {
+        let user_commands_root = ensure_user_commands_dir(app)?;
+        let mut known: HashSet<String> = existing
+            .commands
+            .iter()
+            .map(|command| command.slug.clone())
+            .collect();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("md") {
+                continue;
+            }
+            let command = match parse_command_markdown(&path, "user") {
+                Ok(command) => command,
+                Err(error) => {
+                    warnings.push(format!("跳过命令文件 {}: {}", path.display(), error));
+                    continue;
+                }
+            };
+            if !known.insert(command.slug.clone()) {
+                warnings.push(format!("命令 /{} 已存在，已跳过", command.slug));
+                continue;
+            }
+            let command_file = user_commands_root.join(format!("{}.md", command.slug));
+            fs::write(command_file, build_command_markdown(&command))
+                .map_err(|e| format!("Failed to write imported command file: {}", e))?;
+            command_count += 1;
+        }
+    }
+
+    let mut skill_count = 0;
+    let skills_dir = staging_dir.join("skills");
+    if let Ok(entries) = fs::read_dir(&skills_dir) {
+        let user_skills_root = ensure_user_skills_dir(app)?;
+        let mut known: HashSet<String> = existing
+            .skills
+            .iter()
+            .map(|skill| skill.id.clone())
+            .collect();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !path.is_dir() {
+                continue;
+            }
+            let skill = match read_skill_manifest(&path, "user") {
+                Ok(skill) => skill,
+                Err(error) => {
+                    warnings.push(format!("跳过技能 {}: {}", path.display(), error));
+                    continue;
+                }
+            };
+            if !is_safe_relative_path(&skill.id) {
+                warnings.push(format!("跳过技能 {}: id 不合法", skill.id));
+                continue;
+            }
+            if !known.insert(skill.id.clone()) {
+                warnings.push(format!("技能 {} 已存在，已跳过", skill.id));
+                continue;
+            }
+            copy_dir_recursive(&path, &user_skills_root.join(&skill.id))?;
+            skill_count += 1;
+        }
+    }
+
+    Ok(AgentBundleSummary {
+        path: input_path.to_string_lossy().to_string(),
+        mcp_servers: mcp_count,
+        commands: command_count,
+        skills: skill_count,
+        warnings,
+    })
+}
+
+fn write_zip_entries(path: &Path, entries: &[(String, String)]) -> Result<(), String> {
+    use std::io::Write;
+
+    let file = fs::File::create(path)
+        .map_err(|e| format!("Failed to create bundle ({}): {}", path.display(), e))?;
+    let mut writer = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default();
+    for (name, content) in entries {
+        writer
+            .start_file(name.as_str(), options)
+            .map_err(|e| format!("Failed to add {} to bundle: {}", name, e))?;
+        writer
+            .write_all(content.as_bytes())
+            .map_err(|e| format!("Failed to write {} to bundle: {}", name, e))?;
+    }
+    writer
+        .finish()
+        .map_err(|e| format!("Failed to finish bundle: {}", e))?;
+    Ok(())
+}
+
+fn extract_zip_to_dir(path: &Path, target: &Path) -> Result<(), String> {
+    let file = fs::File::open(path)
+        .map_err(|e| format!("读取 bundle 失败 ({}): {}", path.display(), e))?;
+    let mut archive =
+        zip::ZipArchive::new(file).map_err(|e| format!("Bundle 不是有效的 zip 文件: {}", e))?;
+    for index in 0..archive.len() {
+        let mut entry = archive
+            .by_index(index)
+            .map_err(|e| format!("Failed to read bundle entry: {}", e))?;
+        let Some(relative) = entry.enclosed_name() else {
+            return Err(format!("Unsafe path in bundle: {}", entry.name()));
+        };
+        let out_path = target.join(relative);
+        if entry.is_dir() {
+            fs::create_dir_all(&out_path)
+                .map_err(|e| format!("Failed to create {}: {}", out_path.display(), e))?;
+            continue;
+        }
+        if let Some(parent) = out_path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        let mut out_file = fs::File::create(&out_path)
+            .map_err(|e| format!("Failed to create {}: {}", out_path.display(), e))?;
+        std::io::copy(&mut entry, &mut out_file)
+            .map_err(|e| format!("Failed to extract {}: {}", out_path.display(), e))?;
+    }
+    Ok(())
+}
+
 fn is_safe_relative_path(path: &str) -> bool {
     let candidate = Path::new(path);
     if candidate.is_absolute() {
@@ -4499,12 +11341,18 @@ fn default_codex_timeout_ms() -> u64 {
     120_000
 }
 
+fn default_provider_timeout_ms() -> u64 {
+    60_000
+}
+
 fn default_openai_provider() -> AgentProviderConfig {
     AgentProviderConfig {
         base_url: "https://api.openai.com/v1".to_string(),
         api_key: String::new(),
         model: "gpt-4o-mini".to_string(),
         api_version: None,
+        requests_per_minute: None,
+        request_timeout_ms: default_provider_timeout_ms(),
     }
 }
 
@@ -4514,6 +11362,8 @@ fn default_anthropic_provider() -> AgentProviderConfig {
         api_key: String::new(),
         model: "claude-3-5-sonnet-latest".to_string(),
         api_version: Some("2023-06-01".to_string()),
+        requests_per_minute: None,
+        request_timeout_ms: default_provider_timeout_ms(),
     }
 }
 
@@ -4523,6 +11373,8 @@ fn default_minimax_provider() -> AgentProviderConfig {
         api_key: String::new(),
         model: "MiniMax-M2.1".to_string(),
         api_version: None,
+        requests_per_minute: None,
+        request_timeout_ms: default_provider_timeout_ms(),
     }
 }
 
@@ -4530,16 +11382,20 @@ fn default_stdio_transport() -> String {
     "stdio".to_string()
 }
 
+fn default_mcp_tool_timeout_secs() -> u64 {
+    60
+}
+
+fn mcp_tool_timeout(server: &McpServerConfig) -> Duration {
+    Duration::from_secs(server.tool_timeout_secs.max(1))
+}
+
 fn default_insert_mode() -> String {
     "insert".to_string()
 }
 
 fn get_user_agent_root(app: &AppHandle) -> Result<PathBuf, String> {
-    let root = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to resolve app data dir: {}", e))?
-        .join("agent");
+    let root = app_data_root(app)?.join("agent");
     fs::create_dir_all(&root).map_err(|e| format!("Failed to create agent app data dir: {}", e))?;
     Ok(root)
 }
@@ -4562,29 +11418,322 @@ fn ensure_user_commands_dir(app: &AppHandle) -> Result<PathBuf, String> {
     Ok(path)
 }
 
+const AGENT_SETTINGS_FILE: &str = "settings.enc";
+const AGENT_PROFILES_FILE: &str = "profiles.enc";
+const DEFAULT_AGENT_PROFILE: &str = "default";
+const MAX_AGENT_PROFILE_NAME_CHARS: usize = 40;
+const AGENT_SETTINGS_KEY_FILE: &str = "settings.key";
+const KEYCHAIN_SERVICE: &str = "com.explore-os.desktop";
+const KEYCHAIN_SECRETS_ACCOUNT: &str = "agent-secrets-key";
+const ENCRYPTED_SECRET_PREFIX: &str = "enc:v1:";
+const KEYCHAIN_KEY_PREFIX: &str = "keychain:";
+
+static KEYCHAIN_SECRETS_KEY: OnceLock<Result<Key<Aes256Gcm>, String>> = OnceLock::new();
+
+fn resolve_agent_settings(
+    app: &AppHandle,
+    provided: Option<AgentSettings>,
+) -> Result<AgentSettings, String> {
+    if let Some(settings) = provided {
+        return Ok(settings);
+    }
+    load_agent_settings(app)?
+        .ok_or_else(|| "Agent settings are not configured; save them first".to_string())
+}
+
+/// Settings for a chat: explicit settings win, then the named profile, then the
+/// default saved settings.
+fn resolve_agent_profile_settings(
+    app: &AppHandle,
+    provided: Option<AgentSettings>,
+    profile: Option<&str>,
+) -> Result<AgentSettings, String> {
+    let profile = profile
+        .map(str::trim)
+        .filter(|name| !name.is_empty() && *name != DEFAULT_AGENT_PROFILE);
+    match (provided, profile) {
+        (None, Some(name)) => load_agent_profiles(app)?
+            .remove(name)
+            .ok_or_else(|| format!("Agent profile not found: {}", name)),
+        (provided, _) => resolve_agent_settings(app, provided),
+    }
+}
+
+fn load_agent_settings(app: &AppHandle) -> Result<Option<AgentSettings>, String> {
+    let Some((plaintext, migrate_to_keychain)) =
+        read_agent_secret_file(app, AGENT_SETTINGS_FILE, "agent settings")?
+    else {
+        return Ok(None);
+    };
+    let settings = serde_json::from_slice::<AgentSettings>(&plaintext)
+        .map_err(|e| format!("Failed to parse agent settings: {}", e))?;
+    if migrate_to_keychain {
+        write_agent_settings(app, &settings)?;
+        // Profiles share the key file; loading them re-encrypts them under the
+        // keychain key before the file goes away
+        load_agent_profiles(app)?;
+        let _ = fs::remove_file(get_user_agent_root(app)?.join(AGENT_SETTINGS_KEY_FILE));
+    }
+    Ok(Some(settings))
+}
+
+fn write_agent_settings(app: &AppHandle, settings: &AgentSettings) -> Result<(), String> {
+    let plaintext = serde_json::to_vec(settings)
+        .map_err(|e| format!("Failed to serialize agent settings: {}", e))?;
+    write_agent_secret_file(app, AGENT_SETTINGS_FILE, "agent settings", &plaintext)
+}
+
+fn load_agent_profiles(app: &AppHandle) -> Result<BTreeMap<String, AgentSettings>, String> {
+    let Some((plaintext, migrate_to_keychain)) =
+        read_agent_secret_file(app, AGENT_PROFILES_FILE, "agent profiles")?
+    else {
+        return Ok(BTreeMap::new());
+    };
+    let profiles = serde_json::from_slice::<BTreeMap<String, AgentSettings>>(&plaintext)
+        .map_err(|e| format!("Failed to parse agent profiles: {}", e))?;
+    if migrate_to_keychain {
+        write_agent_profiles(app, &profiles)?;
+    }
+    Ok(profiles)
+}
+
+fn write_agent_profiles(
+    app: &AppHandle,
+    profiles: &BTreeMap<String, AgentSettings>,
+) -> Result<(), String> {
+    let plaintext = serde_json::to_vec(profiles)
+        .map_err(|e| format!("Failed to serialize agent profiles: {}", e))?;
+    write_agent_secret_file(app, AGENT_PROFILES_FILE, "agent profiles", &plaintext)
+}
+
+/// Decrypts a file under the agent root. The flag is set when it was opened with
+/// the legacy key file although a keychain key exists, i.e. it should be rewritten.
+fn read_agent_secret_file(
+    app: &AppHandle,
+    file_name: &str,
+    label: &str,
+) -> Result<Option<(Vec<u8>, bool)>, String> {
+    let path = get_user_agent_root(app)?.join(file_name);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let data = fs::read(&path)
+        .map_err(|e| format!("Failed to read {} ({}): {}", label, path.display(), e))?;
+    if data.len() < 12 {
+        return Err(format!("{} file is corrupted", label));
+    }
+    let (nonce, ciphertext) = data.split_at(12);
+    let decrypt = |key: &Key<Aes256Gcm>| {
+        Aes256Gcm::new(key)
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| format!("Failed to decrypt {}", label))
+    };
+    let key_file = get_user_agent_root(app)?.join(AGENT_SETTINGS_KEY_FILE);
+    match keychain_secrets_key() {
+        Some(key) => match decrypt(&key) {
+            Ok(plaintext) => Ok(Some((plaintext, false))),
+            // Saved before the keychain was reachable: open it with the key file,
+            // then re-encrypt under the keychain key and drop the file
+            Err(_) if key_file.exists() => {
+                Ok(Some((decrypt(&load_or_create_settings_key(app)?)?, true)))
+            }
+            Err(error) => Err(error),
+        },
+        None => Ok(Some((decrypt(&load_or_create_settings_key(app)?)?, false))),
+    }
+}
+
+fn write_agent_secret_file(
+    app: &AppHandle,
+    file_name: &str,
+    label: &str,
+    plaintext: &[u8],
+) -> Result<(), String> {
+    let key = match keychain_secrets_key() {
+        Some(key) => key,
+        None => load_or_create_settings_key(app)?,
+    };
+    let cipher = Aes256Gcm::new(&key);
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| format!("Failed to encrypt {}", label))?;
+
+    let mut data = nonce.to_vec();
+    data.extend_from_slice(&ciphertext);
+    let path = get_user_agent_root(app)?.join(file_name);
+    fs::write(&path, data)
+        .map_err(|e| format!("Failed to write {} ({}): {}", label, path.display(), e))?;
+    Ok(())
+}
+
+/// Fallback AES key for when no OS keychain is reachable. It is stored in plaintext
+/// (0600 on Unix) next to the files it encrypts, so it only guards against casual
+/// reads: anyone who can read the agent directory can decrypt the secrets. On such
+/// systems API keys are better kept as `keychain:` references.
+fn load_or_create_settings_key(app: &AppHandle) -> Result<Key<Aes256Gcm>, String> {
+    let path = get_user_agent_root(app)?.join(AGENT_SETTINGS_KEY_FILE);
+    if path.exists() {
+        let bytes =
+            fs::read(&path).map_err(|e| format!("Failed to read agent settings key: {}", e))?;
+        if bytes.len() != 32 {
+            return Err("Agent settings key is corrupted".to_string());
+        }
+        return Ok(*Key::<Aes256Gcm>::from_slice(&bytes));
+    }
+
+    let key = Aes256Gcm::generate_key(OsRng);
+    fs::write(&path, key.as_slice())
+        .map_err(|e| format!("Failed to write agent settings key: {}", e))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = fs::set_permissions(&path, fs::Permissions::from_mode(0o600));
+    }
+    Ok(key)
+}
+
+/// AES key for secrets at rest, held in the OS keychain and created on first use.
+/// `None` when no keychain is reachable (e.g. headless Linux without a secret
+/// service); callers then fall back to the key file or plaintext, and
+/// `load_tooling_config` reports it.
+fn keychain_secrets_key() -> Option<Key<Aes256Gcm>> {
+    KEYCHAIN_SECRETS_KEY
+        .get_or_init(load_or_create_keychain_key)
+        .as_ref()
+        .ok()
+        .copied()
+}
+
+fn keychain_unavailable_reason() -> Option<String> {
+    KEYCHAIN_SECRETS_KEY
+        .get_or_init(load_or_create_keychain_key)
+        .as_ref()
+        .err()
+        .cloned()
+}
+
+fn load_or_create_keychain_key() -> Result<Key<Aes256Gcm>, String> {
+    let entry = keyring::Entry::new(KEYCHAIN_SERVICE, KEYCHAIN_SECRETS_ACCOUNT)
+        .map_err(|e| format!("Failed to open keychain entry: {}", e))?;
+    match entry.get_secret() {
+        Ok(bytes) if bytes.len() == 32 => Ok(*Key::<Aes256Gcm>::from_slice(&bytes)),
+        Ok(_) => Err("Keychain secrets key is corrupted".to_string()),
+        Err(keyring::Error::NoEntry) => {
+            let key = Aes256Gcm::generate_key(OsRng);
+            entry
+                .set_secret(key.as_slice())
+                .map_err(|e| format!("Failed to store keychain secrets key: {}", e))?;
+            Ok(key)
+        }
+        Err(error) => Err(format!("Failed to read keychain secrets key: {}", error)),
+    }
+}
+
+fn encrypt_secret_value(key: &Key<Aes256Gcm>, value: &str) -> Result<String, String> {
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = Aes256Gcm::new(key)
+        .encrypt(&nonce, value.as_bytes())
+        .map_err(|_| "Failed to encrypt secret".to_string())?;
+    let encoded: String = nonce
+        .iter()
+        .chain(ciphertext.iter())
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    Ok(format!("{}{}", ENCRYPTED_SECRET_PREFIX, encoded))
+}
+
+fn decrypt_secret_value(key: &Key<Aes256Gcm>, value: &str) -> Result<String, String> {
+    let encoded = value
+        .strip_prefix(ENCRYPTED_SECRET_PREFIX)
+        .ok_or_else(|| "Secret is not encrypted".to_string())?;
+    let bytes = (0..encoded.len())
+        .step_by(2)
+        .map(|index| {
+            encoded
+                .get(index..index + 2)
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+        })
+        .collect::<Option<Vec<u8>>>()
+        .filter(|bytes| bytes.len() > 12)
+        .ok_or_else(|| "Encrypted secret is corrupted".to_string())?;
+    let (nonce, ciphertext) = bytes.split_at(12);
+    let plaintext = Aes256Gcm::new(key)
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| "Failed to decrypt secret".to_string())?;
+    String::from_utf8(plaintext).map_err(|_| "Decrypted secret is not UTF-8".to_string())
+}
+
+/// Copy of the settings with every provider API key blanked. Keychain references
+/// carry no secret, so they are kept.
+fn redact_agent_settings(settings: &AgentSettings) -> AgentSettings {
+    let mut redacted = settings.clone();
+    for config in [
+        &mut redacted.openai,
+        &mut redacted.anthropic,
+        &mut redacted.minimax,
+    ] {
+        if !config.api_key.trim().starts_with(KEYCHAIN_KEY_PREFIX) {
+            config.api_key.clear();
+        }
+    }
+    redacted
+}
+
+fn keep_existing_api_key(incoming: &mut AgentProviderConfig, existing: &AgentProviderConfig) {
+    if incoming.api_key.trim().is_empty() {
+        incoming.api_key = existing.api_key.clone();
+    }
+}
+
+fn prefer_existing_api_key(incoming: &mut AgentProviderConfig, existing: &AgentProviderConfig) {
+    if !existing.api_key.trim().is_empty() {
+        incoming.api_key = existing.api_key.clone();
+    }
+}
+
 fn load_tooling_config(app: &AppHandle) -> Result<AgentToolingConfig, String> {
     let mut mcp_map: HashMap<String, McpServerConfig> = HashMap::new();
     for item in load_builtin_mcp_servers() {
         mcp_map.insert(item.name.to_lowercase(), item);
     }
+    let mut warnings = Vec::new();
+    if let Some(error) = keychain_unavailable_reason() {
+        warnings.push(format!(
+            "系统钥匙串不可用（{}）：Agent 设置改用数据目录中的 {} 加密，能读取该目录者即可解密；MCP 敏感环境变量以明文保存",
+            error, AGENT_SETTINGS_KEY_FILE
+        ));
+    }
     for item in load_user_mcp_servers(app)? {
-        mcp_map.insert(item.name.to_lowercase(), item);
+        if !item.locked_env.is_empty() {
+            warnings.push(locked_env_message(&item));
+        }
+        let name = item.name.clone();
+        if mcp_map.insert(name.to_lowercase(), item).is_some() {
+            warnings.push(format!("User MCP server \"{}\" overrides a builtin", name));
+        }
     }
 
     let mut skill_map: HashMap<String, SkillConfig> = HashMap::new();
-    for item in load_builtin_skills() {
+    for item in load_builtin_skills(&mut warnings) {
         skill_map.insert(item.id.clone(), item);
     }
-    for item in load_user_skills(app)? {
-        skill_map.insert(item.id.clone(), item);
+    for item in load_user_skills(app, &mut warnings)? {
+        let id = item.id.clone();
+        if skill_map.insert(id.clone(), item).is_some() {
+            warnings.push(format!("User skill \"{}\" overrides a builtin", id));
+        }
     }
 
     let mut command_map: HashMap<String, AgentCommandConfig> = HashMap::new();
-    for item in load_builtin_commands() {
+    for item in load_builtin_commands(&mut warnings) {
         command_map.insert(item.slug.clone(), item);
     }
-    for item in load_user_commands(app)? {
-        command_map.insert(item.slug.clone(), item);
+    for item in load_user_commands(app, &mut warnings)? {
+        let slug = item.slug.clone();
+        if command_map.insert(slug.clone(), item).is_some() {
+            warnings.push(format!("User command \"/{}\" overrides a builtin", slug));
+        }
     }
 
     let mut mcp_servers = mcp_map.into_values().collect::<Vec<McpServerConfig>>();
@@ -4602,37 +11751,85 @@ fn load_tooling_config(app: &AppHandle) -> Result<AgentToolingConfig, String> {
         mcp_servers,
         skills,
         commands,
+        warnings,
     })
 }
 
-fn load_builtin_skills() -> Vec<SkillConfig> {
-    let Some(skill_root) = resolve_first_existing_path(&[
+fn load_builtin_skills(warnings: &mut Vec<String>) -> Vec<SkillConfig> {
+    let Some(skill_root) = builtin_skills_root() else {
+        return vec![];
+    };
+    load_skills_from_dir(&skill_root, "builtin", warnings)
+}
+
+fn builtin_skills_root() -> Option<PathBuf> {
+    resolve_first_existing_path(&[
         "agent/skills",
         "../agent/skills",
         "../../agent/skills",
         "app/agent/skills",
-    ]) else {
-        return vec![];
-    };
-    load_skills_from_dir(&skill_root, "builtin")
+    ])
 }
 
-fn load_user_skills(app: &AppHandle) -> Result<Vec<SkillConfig>, String> {
+fn load_user_skills(
+    app: &AppHandle,
+    warnings: &mut Vec<String>,
+) -> Result<Vec<SkillConfig>, String> {
     let root = ensure_user_skills_dir(app)?;
-    Ok(load_skills_from_dir(&root, "user"))
+    Ok(load_skills_from_dir(&root, "user", warnings))
 }
 
-fn load_skills_from_dir(root: &Path, source: &str) -> Vec<SkillConfig> {
+fn load_skills_from_dir(root: &Path, source: &str, warnings: &mut Vec<String>) -> Vec<SkillConfig> {
     let Ok(entries) = fs::read_dir(root) else {
         return vec![];
     };
     entries
         .filter_map(Result::ok)
-        .filter(|entry| entry.path().is_dir())
-        .filter_map(|entry| read_skill_manifest(&entry.path(), source).ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .filter_map(|path| match read_skill_manifest(&path, source) {
+            Ok(skill) => Some(skill),
+            Err(error) => {
+                warnings.push(format!(
+                    "Skipped {} skill {}: {}",
+                    source,
+                    path.display(),
+                    error
+                ));
+                None
+            }
+        })
         .collect::<Vec<SkillConfig>>()
 }
 
+fn validate_skills_in_dir(root: &Path, source: &str) -> Vec<SkillValidationResult> {
+    let Ok(entries) = fs::read_dir(root) else {
+        return vec![];
+    };
+    let mut paths = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect::<Vec<PathBuf>>();
+    paths.sort();
+    paths
+        .into_iter()
+        .map(|path| {
+            let (id, error) = match read_skill_manifest(&path, source) {
+                Ok(skill) => (Some(skill.id), None),
+                Err(error) => (None, Some(error)),
+            };
+            SkillValidationResult {
+                path: path.to_string_lossy().to_string(),
+                source: source.to_string(),
+                ok: error.is_none(),
+                id,
+                error,
+            }
+        })
+        .collect()
+}
+
 fn read_skill_manifest(path: &Path, source: &str) -> Result<SkillConfig, String> {
     let manifest_path = path.join("manifest.json");
     let content = fs::read_to_string(&manifest_path).map_err(|e| {
@@ -4692,12 +11889,39 @@ fn load_builtin_mcp_servers() -> Vec<McpServerConfig> {
     read_mcp_servers_from_path(&config_path).unwrap_or_default()
 }
 
+/// Sensitive env values are stored encrypted (see `write_user_mcp_servers`) and
+/// come back decrypted here. A value that cannot be decrypted is kept as stored
+/// and its key listed in `locked_env`, so saving the server again does not lose it.
 fn load_user_mcp_servers(app: &AppHandle) -> Result<Vec<McpServerConfig>, String> {
     let config_path = ensure_user_mcp_dir(app)?.join("servers.json");
     if !config_path.exists() {
         return Ok(vec![]);
     }
-    read_mcp_servers_from_path(&config_path)
+    let mut servers = read_mcp_servers_from_path(&config_path)?;
+    for server in &mut servers {
+        server.locked_env.clear();
+        for (key, value) in &mut server.env {
+            if !value.starts_with(ENCRYPTED_SECRET_PREFIX) {
+                continue;
+            }
+            let decrypted = keychain_secrets_key()
+                .ok_or_else(|| "OS keychain unavailable".to_string())
+                .and_then(|secrets_key| decrypt_secret_value(&secrets_key, value));
+            match decrypted {
+                Ok(decrypted) => *value = decrypted,
+                Err(_) => server.locked_env.push(key.clone()),
+            }
+        }
+    }
+    Ok(servers)
+}
+
+fn locked_env_message(server: &McpServerConfig) -> String {
+    format!(
+        "MCP 服务 {} 的环境变量 {} 无法解密，请重新填写",
+        server.name,
+        server.locked_env.join(", ")
+    )
 }
 
 fn read_mcp_servers_from_path(path: &Path) -> Result<Vec<McpServerConfig>, String> {
@@ -4708,11 +11932,37 @@ fn read_mcp_servers_from_path(path: &Path) -> Result<Vec<McpServerConfig>, Strin
     Ok(parsed.servers)
 }
 
+/// Encrypts sensitive env values with the keychain key. Without a keychain they
+/// are written as plaintext (`load_tooling_config` warns about it). Values still
+/// encrypted, e.g. `locked_env` ones, are written back unchanged.
 fn write_user_mcp_servers(app: &AppHandle, servers: &[McpServerConfig]) -> Result<(), String> {
     let config_path = ensure_user_mcp_dir(app)?.join("servers.json");
-    let data = McpServerFile {
-        servers: servers.to_vec(),
+    let mut servers = servers.to_vec();
+    let Some(secrets_key) = keychain_secrets_key() else {
+        return write_mcp_server_file(&config_path, servers);
     };
+    for server in &mut servers {
+        for (key, value) in &mut server.env {
+            if !is_sensitive_key(key)
+                || value.is_empty()
+                || value.starts_with(ENCRYPTED_SECRET_PREFIX)
+            {
+                continue;
+            }
+            *value = encrypt_secret_value(&secrets_key, value)?;
+        }
+    }
+    write_mcp_server_file(&config_path, servers)
+}
+
+fn write_mcp_server_file(
+    config_path: &Path,
+    mut servers: Vec<McpServerConfig>,
+) -> Result<(), String> {
+    for server in &mut servers {
+        server.locked_env.clear();
+    }
+    let data = McpServerFile { servers };
     fs::write(
         config_path,
         serde_json::to_string_pretty(&data)
@@ -4722,7 +11972,7 @@ fn write_user_mcp_servers(app: &AppHandle, servers: &[McpServerConfig]) -> Resul
     Ok(())
 }
 
-fn load_builtin_commands() -> Vec<AgentCommandConfig> {
+fn load_builtin_commands(warnings: &mut Vec<String>) -> Vec<AgentCommandConfig> {
     let Some(commands_root) = resolve_first_existing_path(&[
         "agent/commands",
         "../agent/commands",
@@ -4731,15 +11981,22 @@ fn load_builtin_commands() -> Vec<AgentCommandConfig> {
     ]) else {
         return vec![];
     };
-    load_commands_from_dir(&commands_root, "builtin")
+    load_commands_from_dir(&commands_root, "builtin", warnings)
 }
 
-fn load_user_commands(app: &AppHandle) -> Result<Vec<AgentCommandConfig>, String> {
+fn load_user_commands(
+    app: &AppHandle,
+    warnings: &mut Vec<String>,
+) -> Result<Vec<AgentCommandConfig>, String> {
     let root = ensure_user_commands_dir(app)?;
-    Ok(load_commands_from_dir(&root, "user"))
+    Ok(load_commands_from_dir(&root, "user", warnings))
 }
 
-fn load_commands_from_dir(root: &Path, source: &str) -> Vec<AgentCommandConfig> {
+fn load_commands_from_dir(
+    root: &Path,
+    source: &str,
+    warnings: &mut Vec<String>,
+) -> Vec<AgentCommandConfig> {
     let Ok(entries) = fs::read_dir(root) else {
         return vec![];
     };
@@ -4747,7 +12004,18 @@ fn load_commands_from_dir(root: &Path, source: &str) -> Vec<AgentCommandConfig>
         .filter_map(Result::ok)
         .map(|entry| entry.path())
         .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("md"))
-        .filter_map(|path| parse_command_markdown(&path, source).ok())
+        .filter_map(|path| match parse_command_markdown(&path, source) {
+            Ok(command) => Some(command),
+            Err(error) => {
+                warnings.push(format!(
+                    "Skipped {} command {}: {}",
+                    source,
+                    path.display(),
+                    error
+                ));
+                None
+            }
+        })
         .collect::<Vec<AgentCommandConfig>>()
 }
 
@@ -4793,29 +12061,52 @@ fn parse_command_markdown(path: &Path, source: &str) -> Result<AgentCommandConfi
     Ok(command)
 }
 
+/// Splits `---` delimited frontmatter from the body. Frontmatter that is never closed or
+/// has malformed lines is an error (with its line number) instead of silently becoming body.
 fn split_frontmatter(content: &str) -> Result<(HashMap<String, String>, String), String> {
     let trimmed = content.trim_start();
     if !trimmed.starts_with("---\n") {
         return Ok((HashMap::new(), trimmed.to_string()));
     }
+    let leading = &content[..content.len() - trimmed.len()];
+    let opening_line = leading.matches('\n').count() + 1;
     let rest = &trimmed[4..];
-    let Some(end_idx) = rest.find("\n---\n") else {
-        return Ok((HashMap::new(), trimmed.to_string()));
-    };
-    let frontmatter_block = &rest[..end_idx];
-    let body = &rest[end_idx + 5..];
     let mut map = HashMap::new();
-    for line in frontmatter_block.lines() {
-        let line = line.trim();
+    let mut offset = 0;
+    let mut body_start = None;
+    for (index, raw_line) in rest.split_inclusive('\n').enumerate() {
+        let line_number = opening_line + index + 1;
+        offset += raw_line.len();
+        let line = raw_line.trim();
+        if line == "---" {
+            body_start = Some(offset);
+            break;
+        }
         if line.is_empty() || line.starts_with('#') {
             continue;
         }
         let Some((k, v)) = line.split_once(':') else {
-            continue;
+            return Err(format!(
+                "Frontmatter line {}: expected `key: value`, got `{}`",
+                line_number, line
+            ));
         };
-        map.insert(k.trim().to_string(), v.trim().trim_matches('"').to_string());
+        let (key, value) = (k.trim(), v.trim().trim_matches('"'));
+        if key == "mode" && value != "insert" && value != "execute" {
+            return Err(format!(
+                "Frontmatter line {}: mode must be insert or execute, got `{}`",
+                line_number, value
+            ));
+        }
+        map.insert(key.to_string(), value.to_string());
     }
-    Ok((map, body.to_string()))
+    let Some(body_start) = body_start else {
+        return Err(format!(
+            "Frontmatter opened on line {} has no closing ---",
+            opening_line
+        ));
+    };
+    Ok((map, rest[body_start..].to_string()))
 }
 
 fn parse_frontmatter_list(input: Option<&String>) -> Vec<String> {
@@ -4928,3 +12219,342 @@ fn resolve_first_existing_path(candidates: &[&str]) -> Option<PathBuf> {
     }
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs a test against the shared test database. Every such test uses one
+    /// runtime, since the pool's connections must outlive any single test.
+    fn with_test_db<F: std::future::Future>(test: F) -> F::Output {
+        static RUNTIME: OnceLock<tokio::runtime::Runtime> = OnceLock::new();
+        RUNTIME
+            .get_or_init(|| tokio::runtime::Runtime::new().unwrap())
+            .block_on(async {
+                crate::database::init_test_database().await.unwrap();
+                test.await
+            })
+    }
+
+    fn test_info_source(id: &str) -> InfoSource {
+        InfoSource {
+            id: id.to_string(),
+            name: "Test".to_string(),
+            r#type: "rss".to_string(),
+            url: "https://example.com/feed.xml".to_string(),
+            enabled: true,
+            is_preset: false,
+            created_at: None,
+            updated_at: None,
+            last_fetch_status: None,
+            last_fetch_error: None,
+            last_fetched_at: None,
+            auth_type: default_info_source_auth_type(),
+            auth_value: None,
+        }
+    }
+
+    fn keyword_filter(include: &[&str]) -> InfoKeywordFilter {
+        let settings = InfoSettings {
+            push_time: "09:00".to_string(),
+            include_keywords: include.iter().map(|keyword| keyword.to_string()).collect(),
+            exclude_keywords: Vec::new(),
+            max_items_per_day: 20,
+            max_concurrent_fetches: DEFAULT_MAX_CONCURRENT_FETCHES,
+            retention_days: 1,
+            quiet_hours_start: None,
+            quiet_hours_end: None,
+            next_refresh_at: None,
+        };
+        InfoKeywordFilter::from_settings(&settings)
+    }
+
+    fn keyword_filter_key(include: &[&str]) -> String {
+        keyword_filter(include).key
+    }
+
+    #[test]
+    fn normalize_event_date_zero_pads_month_and_day() {
+        assert_eq!(normalize_event_date("2025-1-5").unwrap(), "2025-01-05");
+        assert_eq!(normalize_event_date("2025/12/31").unwrap(), "2025-12-31");
+        assert!(normalize_event_date("2025-2-30").is_err());
+    }
+
+    #[test]
+    fn update_builder_skips_unset_fields_and_keeps_bind_order() {
+        let mut update = UpdateBuilder::new();
+        update.set_opt("title", Some("Write report"));
+        update.set_opt::<String>("description", None);
+        update.set_completed(Some(true));
+        update.set_opt::<i32>("sort_order", None);
+        update.set_opt("priority", Some("high".to_string()));
+
+        assert_eq!(
+            update.sql("todos"),
+            format!(
+                "UPDATE todos SET title = ?, completed = ?, {}, priority = ? WHERE id = ?",
+                completed_at_assignment(true)
+            )
+        );
+        assert_eq!(
+            update.binds,
+            vec![
+                UpdateValue::Text("Write report".to_string()),
+                UpdateValue::Integer(1),
+                UpdateValue::Text("high".to_string()),
+            ]
+        );
+        assert!(UpdateBuilder::new().is_empty());
+    }
+
+    #[test]
+    fn new_entity_id_is_unique_and_increasing_in_a_tight_loop() {
+        let ids: Vec<i64> = (0..10_000)
+            .map(|_| new_entity_id().parse().unwrap())
+            .collect();
+        assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn app_time_uses_a_fixed_offset_for_today() {
+        let now = "2025-01-05T17:30:00Z"
+            .parse::<chrono::DateTime<chrono::Utc>>()
+            .unwrap();
+        let shanghai = parse_utc_offset("+08:00").unwrap();
+
+        let local = to_app_time(now, Some(shanghai));
+        assert_eq!(local.to_rfc3339(), "2025-01-06T01:30:00+08:00");
+        assert_eq!(local.date_naive().to_string(), "2025-01-06");
+        let new_york = to_app_time(now, parse_utc_offset("-05:00"));
+        assert_eq!(new_york.date_naive().to_string(), "2025-01-05");
+
+        let midnight = local_midnight_utc(local.date_naive(), shanghai).unwrap();
+        assert_eq!(midnight.to_rfc3339(), "2025-01-05T16:00:00+00:00");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn output_with_timeout_kills_a_child_that_overruns() {
+        let pid_file = env::temp_dir().join(format!("zhaoxi-timeout-{}.pid", new_entity_id()));
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c")
+            .arg(format!("echo $$ > '{}'; exec sleep 30", pid_file.display()))
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
+        let started = Instant::now();
+        let output = output_with_timeout(&mut cmd, Duration::from_millis(500))
+            .await
+            .unwrap();
+        assert!(output.is_none());
+        assert!(started.elapsed() < Duration::from_secs(10));
+
+        let pid = fs::read_to_string(&pid_file).unwrap().trim().to_string();
+        let _ = fs::remove_file(&pid_file);
+        let alive = std::process::Command::new("kill")
+            .args(["-0", &pid])
+            .stderr(Stdio::null())
+            .status()
+            .unwrap()
+            .success();
+        assert!(!alive, "sleep (pid {}) survived the timeout", pid);
+    }
+
+    #[test]
+    fn one_shot_refresh_runs_exactly_once() {
+        with_test_db(async {
+            let pool = get_db_pool().unwrap();
+            // No enabled sources, so the refresh itself never touches the network
+            sqlx::query("UPDATE info_sources SET enabled = 0")
+                .execute(pool)
+                .await
+                .unwrap();
+            ensure_info_settings().await.unwrap();
+            let due = (app_now() - chrono::Duration::minutes(1)).to_rfc3339();
+            sqlx::query("UPDATE info_settings SET next_refresh_at = ?1 WHERE id = 'default'")
+                .bind(&due)
+                .execute(pool)
+                .await
+                .unwrap();
+            let scheduled_runs = || async {
+                sqlx::query_scalar::<_, i64>(
+                    "SELECT COUNT(*) FROM info_refresh_logs WHERE trigger_type = 'scheduled'",
+                )
+                .fetch_one(pool)
+                .await
+                .unwrap()
+            };
+            let before = scheduled_runs().await;
+
+            // Two timers for the same schedule, e.g. the startup one and a re-arm
+            let retry = Duration::from_millis(10);
+            let (first, second) = tokio::join!(
+                run_one_shot_refresh(&due, retry),
+                run_one_shot_refresh(&due, retry)
+            );
+            let ran = [first.unwrap(), second.unwrap()]
+                .iter()
+                .filter(|response| response.is_some())
+                .count();
+            assert_eq!(ran, 1);
+            assert!(run_one_shot_refresh(&due, retry).await.unwrap().is_none());
+            assert_eq!(scheduled_runs().await - before, 1);
+            assert_eq!(load_info_settings().await.unwrap().next_refresh_at, None);
+        });
+    }
+
+    /// Inserts `source` into the test database with the items of an earlier fetch
+    /// cached under `top_link`.
+    async fn store_test_feed_cache(source: &InfoSource, top_link: &str, filter_key: &str) {
+        sqlx::query(
+            "INSERT OR REPLACE INTO info_sources (id, name, type, url) VALUES (?1, ?2, ?3, ?4)",
+        )
+        .bind(&source.id)
+        .bind(&source.name)
+        .bind(&source.r#type)
+        .bind(&source.url)
+        .execute(get_db_pool().unwrap())
+        .await
+        .unwrap();
+        let item = InfoItem {
+            id: format!("temp-{}-0", source.id),
+            source_id: source.id.clone(),
+            title: "Rust 2.0".to_string(),
+            link: top_link.to_string(),
+            summary: None,
+            published_at: None,
+            score: 1.0,
+            matched_keywords: vec!["rust".to_string()],
+            fetched_at: "2025-01-05T09:00:00+08:00".to_string(),
+        };
+        let cached = CachedFeedItems {
+            url: source.url.clone(),
+            filter_key: filter_key.to_string(),
+            date: local_today_string(),
+            title: Some("Example".to_string()),
+            entry_count: 1,
+            items: vec![item],
+        };
+        store_cached_feed_items(&source.id, top_link, &cached)
+            .await
+            .unwrap();
+    }
+
+    #[test]
+    fn cached_feed_items_hit_only_for_same_top_link_filter_and_date() {
+        with_test_db(async {
+            let source = test_info_source("cache-test-source");
+            let filter_key = keyword_filter_key(&["rust"]);
+            let today = local_today_string();
+            store_test_feed_cache(&source, "https://example.com/a", &filter_key).await;
+
+            let hit =
+                load_cached_feed_items(&source, "https://example.com/a", &filter_key, &today).await;
+            assert_eq!(hit.map(|cached| cached.items.len()), Some(1));
+
+            let changed_link =
+                load_cached_feed_items(&source, "https://example.com/b", &filter_key, &today).await;
+            assert!(changed_link.is_none());
+            let changed_keywords = load_cached_feed_items(
+                &source,
+                "https://example.com/a",
+                &keyword_filter_key(&["rust", "tauri"]),
+                &today,
+            )
+            .await;
+            assert!(changed_keywords.is_none());
+            let changed_date =
+                load_cached_feed_items(&source, "https://example.com/a", &filter_key, "2000-01-01")
+                    .await;
+            assert!(changed_date.is_none());
+        });
+    }
+
+    #[test]
+    fn unchanged_feed_skips_parse_and_scoring() {
+        with_test_db(async {
+            let source = test_info_source("unchanged-feed-source");
+            let filter = keyword_filter(&["rust"]);
+            store_test_feed_cache(&source, "https://example.com/a", &filter.key).await;
+
+            // Truncated after the first item, so only a skipped parse can succeed
+            let unchanged = b"<rss><channel><link>https://example.com/</link>\
+                <item><title>Rust 2.0</title><link> https://example.com/a </link></item>";
+            let feed = process_feed_body(&source, &filter, unchanged, FeedValidators::default())
+                .await
+                .unwrap();
+            assert_eq!(feed.items.len(), 1);
+            assert_eq!(feed.title.as_deref(), Some("Example"));
+
+            let changed = b"<rss><channel><item><link>https://example.com/b</link></item>";
+            assert!(
+                process_feed_body(&source, &filter, changed, FeedValidators::default())
+                    .await
+                    .is_err()
+            );
+        });
+    }
+
+    #[test]
+    fn sniff_top_link_reads_first_rss_or_atom_entry() {
+        let rss = b"<rss><channel><link>https://example.com/</link><items/>\
+            <item><link><![CDATA[https://example.com/a]]></link></item>\
+            <item><link>https://example.com/b</link></item></channel></rss>";
+        assert_eq!(
+            sniff_top_link(rss).as_deref(),
+            Some("https://example.com/a")
+        );
+        let atom = b"<feed><link href=\"https://example.com/\"/><entry><title>A</title>\
+            <link rel=\"alternate\" href='https://example.com/a'/></entry></feed>";
+        assert_eq!(
+            sniff_top_link(atom).as_deref(),
+            Some("https://example.com/a")
+        );
+        assert_eq!(
+            sniff_top_link(b"<rss><channel><item><title>A</title></item>"),
+            None
+        );
+        assert_eq!(
+            sniff_top_link(br#"{"items":[{"url":"https://example.com/a"}]}"#),
+            None
+        );
+    }
+
+    #[test]
+    fn is_public_ip_rejects_internal_and_reserved_ranges() {
+        let ip = |text: &str| text.parse::<std::net::IpAddr>().unwrap();
+        for public in [
+            "93.184.216.34",
+            "1.1.1.1",
+            "2606:4700:4700::1111",
+            "::ffff:8.8.8.8",
+        ] {
+            assert!(is_public_ip(ip(public)), "{} should be public", public);
+        }
+        for internal in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.1.2.3",
+            "192.0.2.1",
+            "255.255.255.255",
+            "::1",
+            "::",
+            "fc00::1",
+            "fe80::1",
+            "ff02::1",
+            "::ffff:127.0.0.1",
+            "64:ff9b::a9fe:a9fe",
+            "2001:db8::1",
+        ] {
+            assert!(
+                !is_public_ip(ip(internal)),
+                "{} should not be public",
+                internal
+            );
+        }
+    }
+}
@@ -4,18 +4,68 @@ use std::sync::OnceLock;
 use tauri::{AppHandle, Manager};
 
 static DB_POOL: OnceLock<SqlitePool> = OnceLock::new();
+static DB_PATH: OnceLock<PathBuf> = OnceLock::new();
+static DB_INIT_ERROR: OnceLock<String> = OnceLock::new();
+static DATA_DIR: OnceLock<PathBuf> = OnceLock::new();
+static DATA_DIR_WARNING: OnceLock<String> = OnceLock::new();
+
+/// Data root for the database and agent files. `ZHAOXI_DATA_DIR` overrides the
+/// default app data dir when it points at a writable directory.
+pub fn resolve_data_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    if let Some(dir) = DATA_DIR.get() {
+        return Ok(dir.clone());
+    }
 
-pub async fn init_database_async(app_handle: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
-    // Get the app data directory
-    let app_dir: PathBuf = app_handle
+    let default_dir = app_handle
         .path()
         .app_data_dir()
-        .expect("Failed to get app data directory");
+        .map_err(|e| format!("Failed to resolve app data dir: {}", e))?;
+    let dir = match std::env::var("ZHAOXI_DATA_DIR") {
+        Ok(value) if !value.trim().is_empty() => {
+            let candidate = PathBuf::from(value.trim());
+            match ensure_writable_dir(&candidate) {
+                Ok(()) => candidate,
+                Err(error) => {
+                    let warning = format!(
+                        "ZHAOXI_DATA_DIR ({}) 不可用，已回退到默认数据目录: {}",
+                        candidate.display(),
+                        error
+                    );
+                    let _ = DATA_DIR_WARNING.set(warning);
+                    default_dir
+                }
+            }
+        }
+        _ => default_dir,
+    };
+    Ok(DATA_DIR.get_or_init(|| dir).clone())
+}
+
+pub fn get_data_dir_warning() -> Option<&'static String> {
+    DATA_DIR_WARNING.get()
+}
+
+fn ensure_writable_dir(dir: &PathBuf) -> Result<(), String> {
+    if !dir.is_absolute() {
+        return Err("must be an absolute path".to_string());
+    }
+    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let probe = dir.join(".zhaoxi-write-probe");
+    std::fs::write(&probe, b"ok").map_err(|e| e.to_string())?;
+    let _ = std::fs::remove_file(&probe);
+    Ok(())
+}
+
+pub async fn init_database_async(app_handle: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
+    // Get the data directory (app data dir unless overridden)
+    let app_dir: PathBuf = resolve_data_dir(app_handle)?;
+
+    let db_path = app_dir.join("workbench.db");
+    let _ = DB_PATH.set(db_path.clone());
 
     // Create the directory if it doesn't exist
     std::fs::create_dir_all(&app_dir)?;
 
-    let db_path = app_dir.join("workbench.db");
     println!("Database path: {:?}", db_path);
 
     let options = SqliteConnectOptions::new()
@@ -27,6 +77,9 @@ pub async fn init_database_async(app_handle: &AppHandle) -> Result<(), Box<dyn s
     // Initialize tables
     init_tables(&pool).await?;
 
+    // Bring tables created by older versions up to date
+    migrate_tables(&pool).await?;
+
     // Insert default data if empty
     insert_default_data(&pool).await?;
 
@@ -41,16 +94,58 @@ pub async fn init_database_async(app_handle: &AppHandle) -> Result<(), Box<dyn s
 pub fn init_database(app_handle: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
     // We need to block on the async function since setup is sync
     let runtime = tokio::runtime::Runtime::new()?;
-    runtime.block_on(init_database_async(app_handle))?;
+    if let Err(e) = runtime.block_on(init_database_async(app_handle)) {
+        // Kept so db_health_check can explain why the pool is missing
+        let _ = DB_INIT_ERROR.set(e.to_string());
+        return Err(e);
+    }
     Ok(())
 }
 
+/// Test-only: the full schema and default data in a fresh temp file, installed as
+/// the global pool. All tests share it, so they must not assume a table is theirs.
+#[cfg(test)]
+pub async fn init_test_database() -> Result<&'static SqlitePool, String> {
+    static INIT: tokio::sync::OnceCell<()> = tokio::sync::OnceCell::const_new();
+    INIT.get_or_try_init(|| async {
+        let path = std::env::temp_dir().join(format!(
+            "zhaoxi-test-{}-{}.db",
+            std::process::id(),
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        let options = SqliteConnectOptions::new()
+            .filename(&path)
+            .create_if_missing(true);
+        let pool = SqlitePool::connect_with(options)
+            .await
+            .map_err(|e| e.to_string())?;
+        init_tables(&pool).await.map_err(|e| e.to_string())?;
+        migrate_tables(&pool).await.map_err(|e| e.to_string())?;
+        insert_default_data(&pool)
+            .await
+            .map_err(|e| e.to_string())?;
+        DB_POOL
+            .set(pool)
+            .map_err(|_| "Database already initialized".to_string())
+    })
+    .await?;
+    get_db_pool()
+}
+
 pub fn get_db_pool() -> Result<&'static SqlitePool, String> {
     DB_POOL
         .get()
         .ok_or_else(|| "Database not initialized".to_string())
 }
 
+pub fn get_db_path() -> Option<&'static PathBuf> {
+    DB_PATH.get()
+}
+
+pub fn get_db_init_error() -> Option<&'static String> {
+    DB_INIT_ERROR.get()
+}
+
 async fn init_tables(pool: &SqlitePool) -> Result<(), sqlx::Error> {
     // Todos table
     sqlx::query(
@@ -60,6 +155,28 @@ async fn init_tables(pool: &SqlitePool) -> Result<(), sqlx::Error> {
             title TEXT NOT NULL,
             completed INTEGER DEFAULT 0,
             priority TEXT DEFAULT 'normal',
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            completed_at TEXT,
+            project_id TEXT,
+            snoozed_until TEXT,
+            pinned INTEGER NOT NULL DEFAULT 0
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // Recurring todo templates table
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS recurring_todo_templates (
+            id TEXT PRIMARY KEY,
+            title TEXT NOT NULL,
+            priority TEXT NOT NULL DEFAULT 'normal',
+            rrule TEXT NOT NULL,
+            start_date TEXT NOT NULL,
+            last_generated_date TEXT,
+            enabled INTEGER NOT NULL DEFAULT 1,
             created_at TEXT DEFAULT CURRENT_TIMESTAMP
         )
         "#,
@@ -75,7 +192,8 @@ async fn init_tables(pool: &SqlitePool) -> Result<(), sqlx::Error> {
             title TEXT NOT NULL,
             deadline TEXT,
             progress INTEGER DEFAULT 0,
-            status TEXT DEFAULT 'active'
+            status TEXT DEFAULT 'active',
+            pinned INTEGER NOT NULL DEFAULT 0
         )
         "#,
     )
@@ -90,7 +208,8 @@ async fn init_tables(pool: &SqlitePool) -> Result<(), sqlx::Error> {
             title TEXT NOT NULL,
             date TEXT NOT NULL,
             color TEXT DEFAULT 'blue',
-            note TEXT
+            note TEXT,
+            pinned INTEGER NOT NULL DEFAULT 0
         )
         "#,
     )
@@ -106,7 +225,9 @@ async fn init_tables(pool: &SqlitePool) -> Result<(), sqlx::Error> {
             budget REAL,
             date TEXT,
             location TEXT,
-            note TEXT
+            note TEXT,
+            completed INTEGER NOT NULL DEFAULT 0,
+            completed_at TEXT
         )
         "#,
     )
@@ -119,6 +240,7 @@ async fn init_tables(pool: &SqlitePool) -> Result<(), sqlx::Error> {
             id TEXT PRIMARY KEY,
             content TEXT NOT NULL,
             is_archived INTEGER NOT NULL DEFAULT 0,
+            status TEXT NOT NULL DEFAULT 'published',
             created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
             updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
         )
@@ -155,7 +277,12 @@ async fn init_tables(pool: &SqlitePool) -> Result<(), sqlx::Error> {
             enabled INTEGER NOT NULL DEFAULT 1,
             is_preset INTEGER NOT NULL DEFAULT 0,
             created_at TEXT DEFAULT CURRENT_TIMESTAMP,
-            updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+            updated_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            last_fetch_status TEXT,
+            last_fetch_error TEXT,
+            last_fetched_at TEXT,
+            auth_type TEXT NOT NULL DEFAULT 'none',
+            auth_value TEXT
         )
         "#,
     )
@@ -170,6 +297,20 @@ async fn init_tables(pool: &SqlitePool) -> Result<(), sqlx::Error> {
             include_keywords_json TEXT NOT NULL DEFAULT '[]',
             exclude_keywords_json TEXT NOT NULL DEFAULT '[]',
             max_items_per_day INTEGER NOT NULL DEFAULT 20,
+            max_concurrent_fetches INTEGER NOT NULL DEFAULT 6,
+            retention_days INTEGER NOT NULL DEFAULT 1,
+            updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS app_settings (
+            id TEXT PRIMARY KEY,
+            utc_offset TEXT,
             updated_at TEXT DEFAULT CURRENT_TIMESTAMP
         )
         "#,
@@ -213,6 +354,34 @@ async fn init_tables(pool: &SqlitePool) -> Result<(), sqlx::Error> {
     .execute(pool)
     .await?;
 
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS info_item_contents (
+            link TEXT PRIMARY KEY,
+            title TEXT,
+            content TEXT NOT NULL,
+            fetched_at TEXT NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // Every (link, source) pair seen per day, before cross-source dedupe
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS info_link_sightings (
+            link TEXT NOT NULL,
+            source_id TEXT NOT NULL,
+            date TEXT NOT NULL,
+            title TEXT NOT NULL,
+            PRIMARY KEY (link, source_id, date)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
     sqlx::query(
         r#"
         CREATE TABLE IF NOT EXISTS agent_sessions (
@@ -243,6 +412,21 @@ async fn init_tables(pool: &SqlitePool) -> Result<(), sqlx::Error> {
     .execute(pool)
     .await?;
 
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS agent_provider_errors (
+            id TEXT PRIMARY KEY,
+            request_id TEXT NOT NULL,
+            provider TEXT NOT NULL,
+            status INTEGER,
+            body TEXT NOT NULL,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
     sqlx::query(
         r#"
         CREATE TABLE IF NOT EXISTS agent_action_audits (
@@ -265,6 +449,171 @@ async fn init_tables(pool: &SqlitePool) -> Result<(), sqlx::Error> {
     Ok(())
 }
 
+async fn migrate_tables(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    // Todos: completion timestamp (existing rows stay NULL)
+    ensure_column(pool, "todos", "completed_at", "TEXT").await?;
+
+    // Todos: optional link to the project they belong to
+    ensure_column(pool, "todos", "project_id", "TEXT").await?;
+
+    // Todos: hidden from the list until this UTC time
+    ensure_column(pool, "todos", "snoozed_until", "TEXT").await?;
+
+    // Todos, projects, events: pinned items sort first
+    for table in ["todos", "projects", "events"] {
+        ensure_column(pool, table, "pinned", "INTEGER NOT NULL DEFAULT 0").await?;
+    }
+
+    // Inspirations: draft / published / archived, superseding is_archived
+    ensure_column(
+        pool,
+        "inspirations",
+        "status",
+        "TEXT NOT NULL DEFAULT 'published'",
+    )
+    .await?;
+    sqlx::query(
+        r#"
+        UPDATE inspirations SET status = 'archived'
+        WHERE is_archived = 1 AND status <> 'archived'
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    sqlx::query(
+        r#"
+        CREATE INDEX IF NOT EXISTS idx_inspirations_status_created_at
+        ON inspirations(status, created_at DESC)
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // Info sources: outcome of the latest fetch (ok / empty / error)
+    ensure_column(pool, "info_sources", "last_fetch_status", "TEXT").await?;
+    ensure_column(pool, "info_sources", "last_fetch_error", "TEXT").await?;
+    ensure_column(pool, "info_sources", "last_fetched_at", "TEXT").await?;
+
+    // Info sources: optional credentials for private feeds (none / basic / bearer)
+    ensure_column(
+        pool,
+        "info_sources",
+        "auth_type",
+        "TEXT NOT NULL DEFAULT 'none'",
+    )
+    .await?;
+    ensure_column(pool, "info_sources", "auth_value", "TEXT").await?;
+
+    // Info sources: resolved site icon, cleared when the URL changes
+    ensure_column(pool, "info_sources", "icon_url", "TEXT").await?;
+
+    // Info sources: HTTP validators for conditional feed requests
+    ensure_column(pool, "info_sources", "etag", "TEXT").await?;
+    ensure_column(pool, "info_sources", "last_modified", "TEXT").await?;
+
+    // Info sources: top link and scored items of the last parse, to skip unchanged feeds
+    ensure_column(pool, "info_sources", "last_top_link", "TEXT").await?;
+    ensure_column(pool, "info_sources", "feed_items_json", "TEXT").await?;
+
+    // Info settings: parallel feed fetch limit
+    ensure_column(
+        pool,
+        "info_settings",
+        "max_concurrent_fetches",
+        "INTEGER NOT NULL DEFAULT 6",
+    )
+    .await?;
+
+    // Info settings: how many days of fetched items to keep
+    ensure_column(
+        pool,
+        "info_settings",
+        "retention_days",
+        "INTEGER NOT NULL DEFAULT 1",
+    )
+    .await?;
+
+    // Info settings: window in which scheduled refreshes are held back
+    ensure_column(pool, "info_settings", "quiet_hours_start", "TEXT").await?;
+    ensure_column(pool, "info_settings", "quiet_hours_end", "TEXT").await?;
+
+    // Info settings: one-shot scheduled refresh on top of the daily push time
+    ensure_column(pool, "info_settings", "next_refresh_at", "TEXT").await?;
+
+    // Personal tasks: completion state, mirroring todos
+    ensure_column(
+        pool,
+        "personal_tasks",
+        "completed",
+        "INTEGER NOT NULL DEFAULT 0",
+    )
+    .await?;
+    ensure_column(pool, "personal_tasks", "completed_at", "TEXT").await?;
+
+    // Todos: fold unknown priorities back to the default
+    sqlx::query(
+        r#"
+        UPDATE todos SET priority = 'normal'
+        WHERE priority IS NULL OR priority NOT IN ('low', 'normal', 'high', 'urgent')
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // Events: rewrite non zero-padded dates (e.g. 2025-1-5) to YYYY-MM-DD
+    let malformed_dates: Vec<(String, String)> = sqlx::query_as(
+        "SELECT id, date FROM events WHERE date NOT GLOB '[0-9][0-9][0-9][0-9]-[0-9][0-9]-[0-9][0-9]'",
+    )
+    .fetch_all(pool)
+    .await?;
+    for (id, date) in malformed_dates {
+        if let Ok(normalized) = crate::commands::normalize_event_date(&date) {
+            sqlx::query("UPDATE events SET date = ?1 WHERE id = ?2")
+                .bind(normalized)
+                .bind(id)
+                .execute(pool)
+                .await?;
+        }
+    }
+
+    // App settings: explicit proxy for outbound HTTP (`off` disables it)
+    ensure_column(pool, "app_settings", "proxy", "TEXT").await?;
+
+    // Agent audits: the batch's stopOnError, replayed by agent_retry_batch
+    ensure_column(
+        pool,
+        "agent_action_audits",
+        "stop_on_error",
+        "INTEGER NOT NULL DEFAULT 1",
+    )
+    .await?;
+
+    Ok(())
+}
+
+async fn ensure_column(
+    pool: &SqlitePool,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> Result<(), sqlx::Error> {
+    let columns: Vec<String> =
+        sqlx::query_scalar(&format!("SELECT name FROM pragma_table_info('{}')", table))
+            .fetch_all(pool)
+            .await?;
+    if columns.iter().any(|name| name == column) {
+        return Ok(());
+    }
+
+    sqlx::query(&format!(
+        "ALTER TABLE {} ADD COLUMN {} {}",
+        table, column, definition
+    ))
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
 async fn insert_default_data(pool: &SqlitePool) -> Result<(), sqlx::Error> {
     // Check and insert default todos
     let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM todos")
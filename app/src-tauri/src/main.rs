@@ -16,72 +16,156 @@ fn main() {
             let app_handle = app.handle();
             if let Err(e) = init_database(&app_handle) {
                 eprintln!("Failed to initialize database: {}", e);
+            } else {
+                match tauri::async_runtime::block_on(commands::ensure_info_settings()) {
+                    Ok(settings) => {
+                        if let Some(due) = settings.next_refresh_at {
+                            commands::arm_one_shot_refresh(app_handle.clone(), due);
+                        }
+                    }
+                    Err(e) => eprintln!("Failed to ensure info settings: {}", e),
+                }
+                if let Err(e) = tauri::async_runtime::block_on(commands::load_app_settings()) {
+                    eprintln!("Failed to load app settings: {}", e);
+                }
             }
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             // Todo commands
             commands::get_todos,
+            commands::get_todo,
             commands::create_todo,
+            commands::import_todos_text,
             commands::update_todo,
             commands::delete_todo,
+            commands::get_recurring_todo_templates,
+            commands::create_recurring_todo_template,
+            commands::delete_recurring_todo_template,
+            commands::generate_due_recurring_todos,
+            commands::snooze_todo,
+            commands::cycle_todo_priority,
             // Project commands
             commands::get_projects,
+            commands::get_project,
             commands::create_project,
+            commands::create_project_with_todos,
             commands::update_project,
             commands::delete_project,
             // Event commands
             commands::get_events,
+            commands::get_event,
             commands::get_events_by_date,
+            commands::get_event_color_palette,
+            commands::get_event_color_usage,
             commands::create_event,
+            commands::import_events_text,
             commands::update_event,
             commands::delete_event,
+            commands::set_pinned,
             // Personal task commands
             commands::get_personal_tasks,
+            commands::get_personal_task,
             commands::create_personal_task,
             commands::update_personal_task,
             commands::delete_personal_task,
+            commands::personal_task_to_event,
+            commands::get_month_digest,
+            commands::get_workbench_stats,
+            commands::get_upcoming,
             // Inspiration commands
             commands::get_inspirations,
             commands::create_inspiration,
             commands::toggle_inspiration_archived,
+            commands::set_inspiration_status,
             commands::delete_inspiration,
             // Daily info center commands
             commands::get_info_sources,
             commands::upsert_info_source,
+            commands::detect_info_source_type,
             commands::delete_info_source,
+            commands::merge_info_sources,
+            commands::dedupe_info_sources,
+            commands::get_source_icon,
+            commands::get_duplicate_info_links,
             commands::get_info_settings,
+            commands::ensure_info_settings,
             commands::update_info_settings,
             commands::get_today_info_items,
+            commands::get_today_info_items_sorted,
+            commands::reprocess_today_info,
+            commands::clear_today_info,
+            commands::export_info_digest,
+            commands::get_info_item_counts_by_source,
+            commands::format_info_item_citation,
             commands::refresh_info_now,
+            commands::refresh_info_scheduled,
+            commands::schedule_info_refresh_in,
+            commands::refresh_info_incremental,
             commands::get_info_refresh_status,
+            commands::export_refresh_logs_csv,
+            commands::validate_all_info_sources,
             commands::open_external_link,
+            commands::fetch_info_item_content,
             // Weather commands
             commands::geocode_city,
             commands::get_current_weather,
             // Backup commands
             commands::validate_backup,
             commands::export_backup,
+            commands::preview_import,
             commands::import_backup,
+            commands::list_backups,
+            commands::inspect_backup,
+            commands::diff_backups,
+            commands::db_health_check,
+            commands::get_startup_status,
+            commands::get_app_settings,
+            commands::update_app_settings,
+            commands::vacuum_database,
+            commands::get_storage_report,
+            commands::archive_agent_audits,
             // Agent commands
             commands::agent_chat,
             commands::agent_execute_action,
             commands::agent_execute_actions_atomic,
+            commands::export_agent_session,
+            commands::get_audit_records,
+            commands::replay_agent_events,
+            commands::agent_retry_batch,
             commands::agent_list_capabilities,
+            commands::agent_get_action_schemas,
+            commands::agent_parse_test,
             commands::agent_reload_skills,
             commands::agent_list_mcp_servers,
             commands::agent_get_tooling_config,
             commands::agent_reload_tooling,
             commands::agent_upsert_mcp_server,
             commands::agent_delete_mcp_server,
+            commands::agent_call_mcp_tool,
             commands::agent_import_skill,
+            commands::agent_validate_skills,
             commands::agent_toggle_skill,
             commands::agent_delete_skill,
             commands::agent_list_commands,
             commands::agent_upsert_command,
             commands::agent_import_command_markdown,
+            commands::agent_preview_command_markdown,
             commands::agent_delete_command,
+            commands::export_agent_bundle,
+            commands::import_agent_bundle,
             commands::agent_codex_health,
+            commands::agent_preview_prompt,
+            commands::agent_estimate_tokens,
+            commands::summarize_today_info,
+            commands::get_recent_provider_errors,
+            commands::get_agent_settings,
+            commands::save_agent_settings,
+            commands::set_provider_key_in_keychain,
+            commands::list_agent_profiles,
+            commands::save_agent_profile,
+            commands::export_agent_settings_template,
+            commands::import_agent_settings_template,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
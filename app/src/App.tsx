@@ -12,22 +12,51 @@ import { AppsPage } from '@/pages/AppsPage';
 import { AgentPage } from '@/pages/AgentPage';
 import { AppContainer } from '@/pages/AppContainer';
 import { useAppStore } from '@/stores/appStore';
+import { getStartupStatus, type StartupStatus } from '@/lib/api';
 
 function App() {
   const [isReady, setIsReady] = useState(false);
+  const [startupStatus, setStartupStatus] = useState<StartupStatus | null>(null);
   const currentPage = useAppStore((state) => state.currentPage) ?? 'dashboard';
   const backgroundImage = useAppStore((state) => state.backgroundImage);
   const initializeData = useAppStore((state) => state.initializeData);
   const sidebarItems = useAppStore((state) => state.sidebarItems);
 
   useEffect(() => {
-    // Initialize data from database on app start
-    initializeData();
+    // Check the database came up before loading anything from it
+    getStartupStatus()
+      .then((status) => {
+        setStartupStatus(status);
+        if (status.warning) {
+          console.warn(status.warning);
+        }
+        if (status.databaseReady) {
+          initializeData();
+        }
+      })
+      .catch((error) => {
+        console.error('Failed to get startup status:', error);
+        initializeData();
+      });
     // Give the store a moment to rehydrate
     const timer = setTimeout(() => setIsReady(true), 100);
     return () => clearTimeout(timer);
   }, [initializeData]);
 
+  if (startupStatus && !startupStatus.databaseReady) {
+    return (
+      <div className="flex h-screen items-center justify-center bg-gradient-to-br from-[#1a3a5c] to-[#0d2847] p-8">
+        <div className="max-w-lg rounded-xl bg-white/10 p-6 text-white">
+          <div className="text-xl font-semibold mb-3">无法启动：数据库不可用</div>
+          <div className="text-sm text-white/80 break-all">{startupStatus.error}</div>
+          {startupStatus.dataDir && (
+            <div className="mt-3 text-xs text-white/60 break-all">数据目录：{startupStatus.dataDir}</div>
+          )}
+        </div>
+      </div>
+    );
+  }
+
   // Show loading state until store is ready
   if (!isReady || !Array.isArray(sidebarItems)) {
     return (
@@ -1,4 +1,4 @@
-import { useEffect, useMemo, useState } from 'react';
+import { useEffect, useMemo, useRef, useState } from 'react';
 import {
   Bell,
   ExternalLink,
@@ -7,6 +7,7 @@ import {
   Settings2,
   Trash2,
 } from 'lucide-react';
+import { listen } from '@tauri-apps/api/event';
 import { toast } from 'sonner';
 import { GlassCard } from '../layout/GlassCard';
 import { Button } from '@/components/ui/button';
@@ -16,24 +17,34 @@ import { Switch } from '@/components/ui/switch';
 import { Textarea } from '@/components/ui/textarea';
 import {
   deleteInfoSource,
+  getAppSettings,
   getInfoRefreshStatus,
   getInfoSettings,
   getInfoSources,
+  getSourceIcon,
   getTodayInfoItems,
   refreshInfoNow,
+  refreshInfoScheduled,
   openExternalLink,
+  scheduleInfoRefreshIn,
+  updateAppSettings,
   updateInfoSettings,
   upsertInfoSource,
 } from '@/lib/api';
-import type { InfoItem, InfoSettings, InfoSource } from '@/types';
+import type { InfoItem, InfoRefreshResponse, InfoSettings, InfoSource } from '@/types';
 
 const DEFAULT_SETTINGS: InfoSettings = {
   pushTime: '09:00',
   includeKeywords: [],
   excludeKeywords: [],
   maxItemsPerDay: 20,
+  maxConcurrentFetches: 6,
+  retentionDays: 1,
 };
 
+/** 与后端 MAX_INFO_RETENTION_DAYS 保持一致 */
+const MAX_RETENTION_DAYS = 90;
+
 function toKeywords(value: string): string[] {
   return value
     .split(/[,\n，]/)
@@ -53,11 +64,19 @@ export function HotList() {
   const [isRefreshing, setIsRefreshing] = useState(false);
   const [settingsOpen, setSettingsOpen] = useState(false);
   const [lastRefreshAt, setLastRefreshAt] = useState<string | null>(null);
+  const [sourceIcons, setSourceIcons] = useState<Record<string, string | null>>({});
 
   const [newSourceName, setNewSourceName] = useState('');
   const [newSourceUrl, setNewSourceUrl] = useState('');
   const [includeKeywordsText, setIncludeKeywordsText] = useState('');
   const [excludeKeywordsText, setExcludeKeywordsText] = useState('');
+  const [utcOffsetText, setUtcOffsetText] = useState('');
+  const [proxyText, setProxyText] = useState('');
+  const [refreshInMinutes, setRefreshInMinutes] = useState(60);
+  // Kept in refs so the scheduler effect can re-run on isRefreshing without losing them
+  const lastRunDateRef = useRef('');
+  // Set when quiet hours held back the push-time refresh; retried until it runs
+  const deferredRef = useRef(false);
 
   const sourceNameMap = useMemo(() => {
     const map = new Map<string, string>();
@@ -70,11 +89,12 @@ export function HotList() {
   const loadAll = async () => {
     setIsLoading(true);
     try {
-      const [nextSources, nextItems, nextSettings, status] = await Promise.all([
+      const [nextSources, nextItems, nextSettings, status, appSettings] = await Promise.all([
         getInfoSources(),
         getTodayInfoItems(),
         getInfoSettings(),
         getInfoRefreshStatus(),
+        getAppSettings(),
       ]);
       setSources(nextSources);
       setItems(nextItems);
@@ -82,6 +102,8 @@ export function HotList() {
       setIncludeKeywordsText(toText(nextSettings.includeKeywords));
       setExcludeKeywordsText(toText(nextSettings.excludeKeywords));
       setLastRefreshAt(status.lastRefreshAt ?? null);
+      setUtcOffsetText(appSettings.utcOffset ?? '');
+      setProxyText(appSettings.proxy ?? '');
     } catch (error) {
       console.error('Failed to load info center data:', error);
       toast.error('加载每日信息中心失败');
@@ -95,16 +117,36 @@ export function HotList() {
   }, []);
 
   useEffect(() => {
-    let lastRunDate = '';
+    if (!settingsOpen) {
+      return;
+    }
+    for (const source of sources) {
+      if (source.id in sourceIcons) {
+        continue;
+      }
+      void getSourceIcon(source.id)
+        .catch(() => null)
+        .then((icon) => setSourceIcons((prev) => ({ ...prev, [source.id]: icon })));
+    }
+  }, [settingsOpen, sources]);
+
+  useEffect(() => {
     const intervalId = window.setInterval(() => {
       const now = new Date();
       const hh = `${now.getHours()}`.padStart(2, '0');
       const mm = `${now.getMinutes()}`.padStart(2, '0');
       const currentTime = `${hh}:${mm}`;
       const currentDate = `${now.getFullYear()}-${`${now.getMonth() + 1}`.padStart(2, '0')}-${`${now.getDate()}`.padStart(2, '0')}`;
-      if (currentTime === settings.pushTime && lastRunDate !== currentDate && !isRefreshing) {
-        lastRunDate = currentDate;
-        void handleRefresh();
+      const pushTimeDue =
+        currentTime === settings.pushTime && lastRunDateRef.current !== currentDate;
+      if ((deferredRef.current || pushTimeDue) && !isRefreshing) {
+        if (pushTimeDue) {
+          lastRunDateRef.current = currentDate;
+        }
+        deferredRef.current = false;
+        void handleRefresh(true).then((ran) => {
+          deferredRef.current = !ran;
+        });
       }
     }, 15000);
     return () => {
@@ -112,10 +154,29 @@ export function HotList() {
     };
   }, [settings.pushTime, isRefreshing]);
 
-  const handleRefresh = async () => {
+  // One-shot refreshes run on a backend timer, whoever scheduled them
+  useEffect(() => {
+    let unlisten: (() => void) | undefined;
+    void listen<InfoRefreshResponse>('info_refreshed', (event) => {
+      setSettings((prev) => ({ ...prev, nextRefreshAt: undefined }));
+      setLastRefreshAt(event.payload.refreshedAt);
+      void getTodayInfoItems().then(setItems);
+    }).then((cleanup) => {
+      unlisten = cleanup;
+    });
+
+    return () => {
+      if (unlisten) unlisten();
+    };
+  }, []);
+
+  const handleRefresh = async (scheduled = false): Promise<boolean> => {
     setIsRefreshing(true);
     try {
-      const result = await refreshInfoNow();
+      const result = scheduled ? await refreshInfoScheduled() : await refreshInfoNow();
+      if (!result || (scheduled && result.skipped)) {
+        return false;
+      }
       const [nextItems, status] = await Promise.all([getTodayInfoItems(), getInfoRefreshStatus()]);
       setItems(nextItems);
       setLastRefreshAt(status.lastRefreshAt ?? result.refreshedAt);
@@ -124,9 +185,11 @@ export function HotList() {
       } else {
         toast.error(result.message);
       }
+      return true;
     } catch (error) {
       console.error('Failed to refresh info center:', error);
       toast.error('刷新失败，请稍后重试');
+      return true;
     } finally {
       setIsRefreshing(false);
     }
@@ -197,10 +260,18 @@ export function HotList() {
         includeKeywords: toKeywords(includeKeywordsText),
         excludeKeywords: toKeywords(excludeKeywordsText),
         maxItemsPerDay: settings.maxItemsPerDay,
+        maxConcurrentFetches: settings.maxConcurrentFetches,
+        retentionDays: settings.retentionDays,
       });
       setSettings(nextSettings);
       setIncludeKeywordsText(toText(nextSettings.includeKeywords));
       setExcludeKeywordsText(toText(nextSettings.excludeKeywords));
+      const appSettings = await updateAppSettings({
+        utcOffset: utcOffsetText.trim(),
+        proxy: proxyText.trim(),
+      });
+      setUtcOffsetText(appSettings.utcOffset ?? '');
+      setProxyText(appSettings.proxy ?? '');
       toast.success('每日信息设置已保存');
       setSettingsOpen(false);
     } catch (error) {
@@ -209,6 +280,17 @@ export function HotList() {
     }
   };
 
+  const handleScheduleRefresh = async () => {
+    try {
+      const nextRefreshAt = await scheduleInfoRefreshIn(refreshInMinutes);
+      setSettings((prev) => ({ ...prev, nextRefreshAt }));
+      toast.success(`将在 ${new Date(nextRefreshAt).toLocaleString()} 刷新一次`);
+    } catch (error) {
+      console.error('Failed to schedule info refresh:', error);
+      toast.error('安排刷新失败');
+    }
+  };
+
   const handleOpenLink = async (link: string) => {
     try {
       await openExternalLink(link);
@@ -229,6 +311,9 @@ export function HotList() {
           <div className="text-xs text-white/50 mt-1">
             推送时间 {settings.pushTime}
             {lastRefreshAt ? ` · 最近更新 ${new Date(lastRefreshAt).toLocaleString()}` : ''}
+            {settings.nextRefreshAt
+              ? ` · 下次刷新 ${new Date(settings.nextRefreshAt).toLocaleString()}`
+              : ''}
           </div>
         </div>
         <div className="flex items-center gap-2">
@@ -309,6 +394,43 @@ export function HotList() {
               />
             </div>
 
+            <div>
+              <div className="text-sm font-medium mb-2">时区（UTC 偏移，对整个应用生效）</div>
+              <Input
+                value={utcOffsetText}
+                onChange={(event) => setUtcOffsetText(event.target.value)}
+                placeholder="留空跟随系统，如 +08:00"
+              />
+            </div>
+
+            <div>
+              <div className="text-sm font-medium mb-2">稍后刷新一次（分钟后）</div>
+              <div className="flex gap-2">
+                <Input
+                  type="number"
+                  min={1}
+                  value={refreshInMinutes}
+                  onChange={(event) => {
+                    const value = Number(event.target.value);
+                    if (!Number.isFinite(value)) return;
+                    setRefreshInMinutes(Math.max(1, Math.round(value)));
+                  }}
+                />
+                <Button variant="outline" onClick={() => void handleScheduleRefresh()}>
+                  安排
+                </Button>
+              </div>
+            </div>
+
+            <div>
+              <div className="text-sm font-medium mb-2">网络代理（天气、信息源与模型请求）</div>
+              <Input
+                value={proxyText}
+                onChange={(event) => setProxyText(event.target.value)}
+                placeholder="留空使用系统代理变量，off 关闭代理，如 http://127.0.0.1:7890"
+              />
+            </div>
+
             <div className="grid grid-cols-2 gap-3">
               <div>
                 <div className="text-sm font-medium mb-2">包含关键词（逗号或换行分隔）</div>
@@ -348,6 +470,46 @@ export function HotList() {
               />
             </div>
 
+            <div>
+              <div className="text-sm font-medium mb-2">同时抓取的信息源数</div>
+              <Input
+                type="number"
+                min={1}
+                max={20}
+                value={settings.maxConcurrentFetches}
+                onChange={(event) => {
+                  const value = Number(event.target.value);
+                  if (!Number.isFinite(value)) return;
+                  setSettings((prev) => ({
+                    ...prev,
+                    maxConcurrentFetches: Math.max(1, Math.min(20, Math.round(value))),
+                  }));
+                }}
+              />
+              <p className="text-xs text-white/50 mt-1">
+                遇到限流或网络较慢时可调低
+              </p>
+            </div>
+
+            <div>
+              <div className="text-sm font-medium mb-2">保留天数</div>
+              <Input
+                type="number"
+                min={1}
+                max={MAX_RETENTION_DAYS}
+                value={settings.retentionDays}
+                onChange={(event) => {
+                  const value = Number(event.target.value);
+                  if (!Number.isFinite(value)) return;
+                  setSettings((prev) => ({
+                    ...prev,
+                    retentionDays: Math.max(1, Math.min(MAX_RETENTION_DAYS, Math.round(value))),
+                  }));
+                }}
+              />
+              <p className="text-xs text-white/50 mt-1">1 表示只保留当天的信息</p>
+            </div>
+
             <div className="space-y-3">
               <div className="text-sm font-medium">信息源</div>
               <div className="space-y-2">
@@ -360,6 +522,9 @@ export function HotList() {
                       checked={source.enabled}
                       onCheckedChange={(checked) => void handleSourceToggle(source, checked)}
                     />
+                    {sourceIcons[source.id] && (
+                      <img src={sourceIcons[source.id] ?? undefined} alt="" className="w-4 h-4 rounded-sm" />
+                    )}
                     <div className="flex-1 min-w-0">
                       <div className="text-sm text-white truncate">{source.name}</div>
                       <div className="text-xs text-white/50 truncate">{source.url}</div>
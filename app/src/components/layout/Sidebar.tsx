@@ -20,8 +20,10 @@ import { cn } from '@/lib/utils';
 import { DynamicIcon } from '@/components/features/DynamicIcon';
 import { SidebarSettingsDialog } from '@/components/features/SidebarSettingsDialog';
 import { open as openDialog, save as saveDialog } from '@tauri-apps/plugin-dialog';
+import { listen } from '@tauri-apps/api/event';
 import { toast } from 'sonner';
-import { exportBackup, importBackup, validateBackup } from '@/lib/api';
+import { exportBackup, importBackup, validateBackup, vacuumDatabase } from '@/lib/api';
+import type { BackupProgressEvent } from '@/lib/api';
 import {
   Dialog,
   DialogContent,
@@ -29,6 +31,7 @@ import {
   DialogTitle,
 } from '@/components/ui/dialog';
 import { Button } from '@/components/ui/button';
+import { Progress } from '@/components/ui/progress';
 import type { SidebarItem } from '@/types';
 
 // 内置页面定义
@@ -51,6 +54,12 @@ const BUILTIN_PAGES: BuiltinPageDef[] = [
   { id: 'agent', label: '工作台 Agent', icon: Bot },
 ];
 
+const BACKUP_PHASE_LABELS: Record<BackupProgressEvent['phase'], string> = {
+  export: '导出',
+  rollback: '创建回滚备份',
+  import: '导入',
+};
+
 interface SidebarProps {
   className?: string;
 }
@@ -69,6 +78,8 @@ export function Sidebar({ className }: SidebarProps) {
   const [sidebarSettingsOpen, setSidebarSettingsOpen] = useState(false);
   const [isExportingBackup, setIsExportingBackup] = useState(false);
   const [isImportingBackup, setIsImportingBackup] = useState(false);
+  const [isVacuuming, setIsVacuuming] = useState(false);
+  const [backupProgress, setBackupProgress] = useState<BackupProgressEvent | null>(null);
   const fileInputRef = useRef<HTMLInputElement>(null);
 
   const handleFileUpload = (e: React.ChangeEvent<HTMLInputElement>) => {
@@ -86,8 +97,20 @@ export function Sidebar({ className }: SidebarProps) {
     setBackgroundImage(null);
   };
 
+  // 导出/导入期间订阅后端逐表进度，返回的函数用于结束时取消订阅
+  const watchBackupProgress = async () => {
+    const unlisten = await listen<BackupProgressEvent>('backup_progress', (event) => {
+      setBackupProgress(event.payload);
+    });
+    return () => {
+      unlisten();
+      setBackupProgress(null);
+    };
+  };
+
   const handleExportBackup = async () => {
     if (isExportingBackup || isImportingBackup) return;
+    let stopProgress: (() => void) | undefined;
 
     try {
       setIsExportingBackup(true);
@@ -132,6 +155,7 @@ export function Sidebar({ className }: SidebarProps) {
         workbenchStorage: rawWorkbenchStorage ? JSON.parse(rawWorkbenchStorage) : {},
         workbenchAgentStorage: rawAgentStorage ? JSON.parse(rawAgentStorage) : {},
       };
+      stopProgress = await watchBackupProgress();
       const result = await exportBackup({
         path: filePath,
         includeSecrets: false,
@@ -146,10 +170,27 @@ export function Sidebar({ className }: SidebarProps) {
       console.error('Failed to export backup:', error);
       toast.error(error instanceof Error ? error.message : '导出失败');
     } finally {
+      stopProgress?.();
       setIsExportingBackup(false);
     }
   };
 
+  const handleVacuumDatabase = async () => {
+    if (isExportingBackup || isImportingBackup || isVacuuming) return;
+
+    try {
+      setIsVacuuming(true);
+      const result = await vacuumDatabase();
+      const reclaimedKb = Math.round(result.reclaimedBytes / 1024);
+      toast.success(`已释放 ${reclaimedKb} KB 空间`);
+    } catch (error) {
+      console.error('Failed to vacuum database:', error);
+      toast.error(error instanceof Error ? error.message : String(error));
+    } finally {
+      setIsVacuuming(false);
+    }
+  };
+
   const handleImportBackup = async () => {
     if (isExportingBackup || isImportingBackup) return;
     let selected: string | null = null;
@@ -173,6 +214,7 @@ export function Sidebar({ className }: SidebarProps) {
       return;
     }
     if (!selected) return;
+    let stopProgress: (() => void) | undefined;
 
     try {
       setIsImportingBackup(true);
@@ -189,6 +231,7 @@ export function Sidebar({ className }: SidebarProps) {
         return;
       }
 
+      stopProgress = await watchBackupProgress();
       const result = await importBackup({
         path: selected,
         mode: 'replace',
@@ -210,6 +253,7 @@ export function Sidebar({ className }: SidebarProps) {
       console.error('Failed to import backup:', error);
       toast.error(error instanceof Error ? error.message : '导入失败');
     } finally {
+      stopProgress?.();
       setIsImportingBackup(false);
     }
   };
@@ -418,7 +462,36 @@ export function Sidebar({ className }: SidebarProps) {
                 >
                   导入数据
                 </Button>
+                <Button
+                  variant="outline"
+                  disabled={isExportingBackup || isImportingBackup || isVacuuming}
+                  onClick={() => void handleVacuumDatabase()}
+                  className="col-span-2 bg-white/5 border-white/10 text-white hover:bg-white/10 disabled:opacity-50"
+                >
+                  {isVacuuming ? '正在整理...' : '释放空间'}
+                </Button>
               </div>
+              {backupProgress && (
+                <div className="mt-3 space-y-1">
+                  <div className="flex justify-between text-xs text-white/60">
+                    <span>
+                      {BACKUP_PHASE_LABELS[backupProgress.phase]}：{backupProgress.table}
+                    </span>
+                    <span>
+                      {backupProgress.tableIndex + 1}/{backupProgress.tableCount} 表 ·{' '}
+                      {backupProgress.rowsDone}/{backupProgress.rowsTotal} 行
+                    </span>
+                  </div>
+                  <Progress
+                    value={
+                      backupProgress.rowsTotal > 0
+                        ? (backupProgress.rowsDone / backupProgress.rowsTotal) * 100
+                        : 100
+                    }
+                    className="h-1.5 bg-white/10"
+                  />
+                </div>
+              )}
               <p className="text-xs text-white/40 mt-2">
                 导入会覆盖当前数据并自动重载。默认备份不包含敏感密钥。
               </p>
@@ -1,6 +1,8 @@
 import { invoke } from '@tauri-apps/api/core';
 import type {
+  AgentActionSchema,
   AgentCommand,
+  AgentBundleSummary,
   AgentCapabilities,
   AgentChatRequest,
   AgentChatResponse,
@@ -9,15 +11,31 @@ import type {
   AgentExecuteActionsResponse,
   AgentExecuteRequest,
   AgentExecuteResponse,
+  AgentExecutionAuditRecord,
+  AgentMessage,
+  AgentProfile,
+  AgentSettings,
+  AgentStreamEvent,
+  AgentTokenEstimate,
   AgentToolingConfig,
+  CommandMarkdownPreview,
   McpServerConfig,
+  McpToolCallResult,
   SkillConfig,
+  SkillValidationResult,
 } from '@/types/agent';
 
 export async function agentChat(request: AgentChatRequest): Promise<AgentChatResponse> {
   return invoke('agent_chat', { request });
 }
 
+export async function agentEstimateTokens(
+  messages: AgentMessage[],
+  settings?: AgentSettings
+): Promise<AgentTokenEstimate> {
+  return invoke('agent_estimate_tokens', { settings, messages });
+}
+
 export async function agentExecuteAction(request: AgentExecuteRequest): Promise<AgentExecuteResponse> {
   return invoke('agent_execute_action', { request });
 }
@@ -28,10 +46,41 @@ export async function agentExecuteActionsAtomic(
   return invoke('agent_execute_actions_atomic', { request });
 }
 
+export async function exportAgentSession(requestId: string, path: string): Promise<string> {
+  return invoke('export_agent_session', { requestId, path });
+}
+
+export async function getAuditRecords(
+  batchId?: string,
+  since?: string,
+  limit?: number
+): Promise<AgentExecutionAuditRecord[]> {
+  return invoke('get_audit_records', { batchId, since, limit });
+}
+
+export async function replayAgentEvents(
+  requestId: string,
+  limit?: number
+): Promise<AgentStreamEvent[]> {
+  return invoke('replay_agent_events', { requestId, limit });
+}
+
+export async function agentRetryBatch(batchId: string): Promise<AgentExecuteActionsResponse> {
+  return invoke('agent_retry_batch', { batchId });
+}
+
 export async function agentListCapabilities(): Promise<AgentCapabilities> {
   return invoke('agent_list_capabilities');
 }
 
+export async function agentGetActionSchemas(): Promise<AgentActionSchema[]> {
+  return invoke('agent_get_action_schemas');
+}
+
+export async function agentParseTest(content: string): Promise<AgentChatResponse> {
+  return invoke('agent_parse_test', { content });
+}
+
 export async function agentReloadSkills(): Promise<{ reloaded: number }> {
   return invoke('agent_reload_skills');
 }
@@ -56,10 +105,22 @@ export async function agentDeleteMcpServer(name: string): Promise<void> {
   return invoke('agent_delete_mcp_server', { request: { name } });
 }
 
+export async function agentCallMcpTool(
+  server: string,
+  tool: string,
+  args?: Record<string, unknown>
+): Promise<McpToolCallResult> {
+  return invoke('agent_call_mcp_tool', { request: { server, tool, arguments: args } });
+}
+
 export async function agentImportSkill(path: string): Promise<SkillConfig> {
   return invoke('agent_import_skill', { request: { path } });
 }
 
+export async function agentValidateSkills(): Promise<SkillValidationResult[]> {
+  return invoke('agent_validate_skills');
+}
+
 export async function agentToggleSkill(id: string, enabled: boolean): Promise<void> {
   return invoke('agent_toggle_skill', { request: { id, enabled } });
 }
@@ -80,6 +141,10 @@ export async function agentImportCommandMarkdown(path: string): Promise<AgentCom
   return invoke('agent_import_command_markdown', { request: { path } });
 }
 
+export async function agentPreviewCommandMarkdown(path: string): Promise<CommandMarkdownPreview> {
+  return invoke('agent_preview_command_markdown', { request: { path } });
+}
+
 export async function agentDeleteCommand(slug: string): Promise<void> {
   return invoke('agent_delete_command', { request: { slug } });
 }
@@ -87,3 +152,49 @@ export async function agentDeleteCommand(slug: string): Promise<void> {
 export async function agentCodexHealth(request: AgentChatRequest): Promise<AgentCodexHealth> {
   return invoke('agent_codex_health', { request });
 }
+
+export async function getAgentSettings(): Promise<AgentSettings | null> {
+  return invoke('get_agent_settings');
+}
+
+export async function saveAgentSettings(settings: AgentSettings): Promise<AgentSettings> {
+  return invoke('save_agent_settings', { settings });
+}
+
+export async function setProviderKeyInKeychain(
+  provider: 'openai' | 'anthropic' | 'minimax',
+  key: string
+): Promise<string> {
+  return invoke('set_provider_key_in_keychain', { provider, key });
+}
+
+export async function listAgentProfiles(): Promise<AgentProfile[]> {
+  return invoke('list_agent_profiles');
+}
+
+export async function saveAgentProfile(
+  name: string,
+  settings: AgentSettings
+): Promise<AgentSettings> {
+  return invoke('save_agent_profile', { name, settings });
+}
+
+export async function exportAgentSettingsTemplate(path: string): Promise<string> {
+  return invoke('export_agent_settings_template', { path });
+}
+
+export async function importAgentSettingsTemplate(path: string): Promise<AgentSettings> {
+  return invoke('import_agent_settings_template', { path });
+}
+
+export async function summarizeTodayInfo(settings?: AgentSettings): Promise<string> {
+  return invoke('summarize_today_info', { settings });
+}
+
+export async function exportAgentBundle(path: string, includeSecrets = false): Promise<AgentBundleSummary> {
+  return invoke('export_agent_bundle', { request: { path, includeSecrets } });
+}
+
+export async function importAgentBundle(path: string): Promise<AgentBundleSummary> {
+  return invoke('import_agent_bundle', { request: { path } });
+}
@@ -1,28 +1,60 @@
 import { invoke } from '@tauri-apps/api/core';
 import type {
+  AppSettings,
   Todo,
+  RecurringTodoTemplate,
   Project,
   CalendarEvent,
   PersonalTask,
+  MonthDigest,
   Inspiration,
+  InspirationStatus,
   InfoSource,
   InfoSettings,
   InfoItem,
+  InfoItemContent,
   InfoRefreshResponse,
   InfoRefreshStatus,
+  InfoSourceValidation,
   WeatherData,
+  WorkbenchStats,
+  UpcomingItem,
 } from '@/types';
 
 // ============= Todo API =============
 
-export async function getTodos(): Promise<Todo[]> {
-  return invoke('get_todos');
+export async function getTodos(includeSnoozed?: boolean): Promise<Todo[]> {
+  return invoke('get_todos', { includeSnoozed });
 }
 
-export async function createTodo(title: string, priority: 'normal' | 'urgent'): Promise<Todo> {
+export async function getTodo(id: string): Promise<Todo | null> {
+  return invoke('get_todo', { id });
+}
+
+export async function createTodo(title: string, priority: Todo['priority']): Promise<Todo> {
   return invoke('create_todo', { request: { title, priority } });
 }
 
+export async function snoozeTodo(id: string, until?: string): Promise<Todo> {
+  return invoke('snooze_todo', { id, until });
+}
+
+export async function cycleTodoPriority(id: string): Promise<Todo> {
+  return invoke('cycle_todo_priority', { id });
+}
+
+export interface ImportTodosTextResponse {
+  created: number;
+  ids: string[];
+}
+
+export async function importTodosText(
+  text: string,
+  defaultPriority?: string
+): Promise<ImportTodosTextResponse> {
+  return invoke('import_todos_text', { text, defaultPriority });
+}
+
 export interface UpdateTodoRequest extends Record<string, unknown> {
   id: string;
   title?: string;
@@ -38,16 +70,63 @@ export async function deleteTodo(id: string): Promise<void> {
   return invoke('delete_todo', { id });
 }
 
+export interface CreateRecurringTodoTemplateRequest {
+  title: string;
+  priority?: Todo['priority'];
+  rrule: string;
+  startDate?: string;
+}
+
+export interface GenerateRecurringTodosResponse {
+  created: number;
+  ids: string[];
+}
+
+export async function getRecurringTodoTemplates(): Promise<RecurringTodoTemplate[]> {
+  return invoke('get_recurring_todo_templates');
+}
+
+export async function createRecurringTodoTemplate(
+  request: CreateRecurringTodoTemplateRequest
+): Promise<RecurringTodoTemplate> {
+  return invoke('create_recurring_todo_template', { request });
+}
+
+export async function deleteRecurringTodoTemplate(id: string): Promise<void> {
+  return invoke('delete_recurring_todo_template', { id });
+}
+
+export async function generateDueRecurringTodos(): Promise<GenerateRecurringTodosResponse> {
+  return invoke('generate_due_recurring_todos');
+}
+
 // ============= Project API =============
 
 export async function getProjects(): Promise<Project[]> {
   return invoke('get_projects');
 }
 
+export async function getProject(id: string): Promise<Project | null> {
+  return invoke('get_project', { id });
+}
+
 export async function createProject(title: string, deadline: string): Promise<Project> {
   return invoke('create_project', { request: { title, deadline } });
 }
 
+export interface CreateProjectWithTodosResponse {
+  project: Project;
+  todoIds: string[];
+}
+
+export async function createProjectWithTodos(
+  title: string,
+  deadline: string,
+  todos: string[]
+): Promise<CreateProjectWithTodosResponse> {
+  return invoke('create_project_with_todos', { title, deadline, todos });
+}
+
 export interface UpdateProjectRequest extends Record<string, unknown> {
   id: string;
   title?: string;
@@ -70,10 +149,33 @@ export async function getEvents(): Promise<CalendarEvent[]> {
   return invoke('get_events');
 }
 
+export async function getEvent(id: string): Promise<CalendarEvent | null> {
+  return invoke('get_event', { id });
+}
+
 export async function getEventsByDate(date: string): Promise<CalendarEvent[]> {
   return invoke('get_events_by_date', { date });
 }
 
+export interface EventColor {
+  name: CalendarEvent['color'];
+  hex: string;
+}
+
+export async function getEventColorPalette(): Promise<EventColor[]> {
+  return invoke('get_event_color_palette');
+}
+
+export interface EventColorUsage {
+  color: string;
+  count: number;
+  inPalette: boolean;
+}
+
+export async function getEventColorUsage(): Promise<EventColorUsage[]> {
+  return invoke('get_event_color_usage');
+}
+
 export async function createEvent(
   title: string,
   date: string,
@@ -83,6 +185,20 @@ export async function createEvent(
   return invoke('create_event', { request: { title, date, color, note } });
 }
 
+export interface ImportEventsTextResponse {
+  created: number;
+  ids: string[];
+  skipped: string[];
+  warning?: string | null;
+}
+
+export async function importEventsText(
+  text: string,
+  defaultColor?: CalendarEvent['color']
+): Promise<ImportEventsTextResponse> {
+  return invoke('import_events_text', { text, defaultColor });
+}
+
 export interface UpdateEventRequest extends Record<string, unknown> {
   id: string;
   title?: string;
@@ -99,12 +215,24 @@ export async function deleteEvent(id: string): Promise<void> {
   return invoke('delete_event', { id });
 }
 
+export async function setPinned(
+  entityType: 'todo' | 'project' | 'event',
+  id: string,
+  pinned: boolean
+): Promise<void> {
+  return invoke('set_pinned', { entityType, id, pinned });
+}
+
 // ============= Personal Task API =============
 
 export async function getPersonalTasks(): Promise<PersonalTask[]> {
   return invoke('get_personal_tasks');
 }
 
+export async function getPersonalTask(id: string): Promise<PersonalTask | null> {
+  return invoke('get_personal_task', { id });
+}
+
 export async function createPersonalTask(
   title: string,
   budget?: number,
@@ -122,6 +250,7 @@ export interface UpdatePersonalTaskRequest extends Record<string, unknown> {
   date?: string;
   location?: string;
   note?: string;
+  completed?: boolean;
 }
 
 export async function updatePersonalTask(request: UpdatePersonalTaskRequest): Promise<PersonalTask> {
@@ -132,21 +261,54 @@ export async function deletePersonalTask(id: string): Promise<void> {
   return invoke('delete_personal_task', { id });
 }
 
+export async function personalTaskToEvent(
+  id: string,
+  markCompleted: boolean = false
+): Promise<CalendarEvent> {
+  return invoke('personal_task_to_event', { id, markCompleted });
+}
+
+export async function getMonthDigest(yearMonth: string): Promise<MonthDigest> {
+  return invoke('get_month_digest', { yearMonth });
+}
+
+export async function getWorkbenchStats(): Promise<WorkbenchStats> {
+  return invoke('get_workbench_stats');
+}
+
+export async function getUpcoming(days: number): Promise<UpcomingItem[]> {
+  return invoke('get_upcoming', { days });
+}
+
 // ============= Inspiration API =============
 
-export async function getInspirations(includeArchived: boolean = true): Promise<Inspiration[]> {
-  return invoke('get_inspirations', { includeArchived });
+export async function getInspirations(
+  includeArchived: boolean = true,
+  status?: InspirationStatus
+): Promise<Inspiration[]> {
+  return invoke('get_inspirations', { includeArchived, status });
 }
 
 export interface CreateInspirationRequest {
   content: string;
+  status?: Exclude<InspirationStatus, 'archived'>;
 }
 
-export async function createInspiration(content: string): Promise<Inspiration> {
-  const request: CreateInspirationRequest = { content };
+export async function createInspiration(
+  content: string,
+  status?: CreateInspirationRequest['status']
+): Promise<Inspiration> {
+  const request: CreateInspirationRequest = { content, status };
   return invoke('create_inspiration', { request });
 }
 
+export async function setInspirationStatus(
+  id: string,
+  status: InspirationStatus
+): Promise<Inspiration> {
+  return invoke('set_inspiration_status', { id, status });
+}
+
 export interface ToggleInspirationArchivedRequest {
   id: string;
   isArchived: boolean;
@@ -168,9 +330,12 @@ export interface UpsertInfoSourceRequest {
   id?: string;
   name: string;
   url: string;
-  type: 'rss';
+  type: InfoSource['type'];
   enabled: boolean;
   isPreset?: boolean;
+  authType?: InfoSource['authType'];
+  /** `user:password` for basic, the token for bearer; omit to keep the stored value. */
+  authValue?: string;
 }
 
 export interface UpdateInfoSettingsRequest {
@@ -178,6 +343,10 @@ export interface UpdateInfoSettingsRequest {
   includeKeywords: string[];
   excludeKeywords: string[];
   maxItemsPerDay: number;
+  maxConcurrentFetches?: number;
+  retentionDays?: number;
+  quietHoursStart?: string;
+  quietHoursEnd?: string;
 }
 
 export async function getInfoSources(): Promise<InfoSource[]> {
@@ -188,34 +357,132 @@ export async function upsertInfoSource(request: UpsertInfoSourceRequest): Promis
   return invoke('upsert_info_source', { request });
 }
 
+export async function detectInfoSourceType(url: string): Promise<InfoSource['type']> {
+  return invoke('detect_info_source_type', { url });
+}
+
 export async function deleteInfoSource(id: string): Promise<void> {
   return invoke('delete_info_source', { id });
 }
 
+export async function mergeInfoSources(keepId: string, removeId: string): Promise<InfoSource> {
+  return invoke('merge_info_sources', { keepId, removeId });
+}
+
+export async function dedupeInfoSources(): Promise<number> {
+  return invoke('dedupe_info_sources');
+}
+
+export async function getSourceIcon(sourceId: string): Promise<string | null> {
+  return invoke('get_source_icon', { sourceId });
+}
+
+export interface DuplicateInfoLink {
+  link: string;
+  title: string;
+  sourceIds: string[];
+}
+
+export async function getDuplicateInfoLinks(days?: number): Promise<DuplicateInfoLink[]> {
+  return invoke('get_duplicate_info_links', { days });
+}
+
 export async function getInfoSettings(): Promise<InfoSettings> {
   return invoke('get_info_settings');
 }
 
+export async function ensureInfoSettings(): Promise<InfoSettings> {
+  return invoke('ensure_info_settings');
+}
+
 export async function updateInfoSettings(request: UpdateInfoSettingsRequest): Promise<InfoSettings> {
   return invoke('update_info_settings', { request });
 }
 
-export async function getTodayInfoItems(): Promise<InfoItem[]> {
-  return invoke('get_today_info_items');
+export async function getTodayInfoItems(date?: string): Promise<InfoItem[]> {
+  return invoke('get_today_info_items', { date });
+}
+
+export async function getTodayInfoItemsSorted(
+  by: 'score' | 'recency',
+  date?: string
+): Promise<InfoItem[]> {
+  return invoke('get_today_info_items_sorted', { by, date });
+}
+
+export async function reprocessTodayInfo(): Promise<InfoItem[]> {
+  return invoke('reprocess_today_info');
+}
+
+export async function clearTodayInfo(): Promise<number> {
+  return invoke('clear_today_info');
+}
+
+export interface ExportInfoDigestResponse {
+  path: string;
+  count: number;
+}
+
+export async function exportInfoDigest(
+  format: 'markdown' | 'html',
+  path: string,
+  date?: string
+): Promise<ExportInfoDigestResponse> {
+  return invoke('export_info_digest', { date, format, path });
+}
+
+export async function getInfoItemCountsBySource(date?: string): Promise<Record<string, number>> {
+  return invoke('get_info_item_counts_by_source', { date });
+}
+
+export async function formatInfoItemCitation(id: string): Promise<string> {
+  return invoke('format_info_item_citation', { id });
 }
 
 export async function refreshInfoNow(): Promise<InfoRefreshResponse> {
   return invoke('refresh_info_now');
 }
 
+export async function refreshInfoScheduled(): Promise<InfoRefreshResponse | null> {
+  return invoke('refresh_info_scheduled');
+}
+
+export async function refreshInfoIncremental(): Promise<InfoRefreshResponse> {
+  return invoke('refresh_info_incremental');
+}
+
+export async function scheduleInfoRefreshIn(minutes: number): Promise<string> {
+  return invoke('schedule_info_refresh_in', { minutes });
+}
+
 export async function getInfoRefreshStatus(): Promise<InfoRefreshStatus> {
   return invoke('get_info_refresh_status');
 }
 
+export interface ExportRefreshLogsResponse {
+  path: string;
+  count: number;
+}
+
+export async function exportRefreshLogsCsv(
+  path: string,
+  since?: string
+): Promise<ExportRefreshLogsResponse> {
+  return invoke('export_refresh_logs_csv', { path, since });
+}
+
+export async function validateAllInfoSources(): Promise<InfoSourceValidation[]> {
+  return invoke('validate_all_info_sources');
+}
+
 export async function openExternalLink(url: string): Promise<void> {
   return invoke('open_external_link', { url });
 }
 
+export async function fetchInfoItemContent(link: string): Promise<InfoItemContent> {
+  return invoke('fetch_info_item_content', { link });
+}
+
 // ============= Weather API =============
 
 export interface GeocodeCityRequest {
@@ -256,6 +523,8 @@ export interface ExportBackupRequest {
   path: string;
   includeSecrets?: boolean;
   localState?: BackupLocalState;
+  since?: string;
+  excludeTables?: string[];
 }
 
 export interface ExportBackupResponse {
@@ -266,6 +535,16 @@ export interface ExportBackupResponse {
   warnings: string[];
 }
 
+/** Payload of the `backup_progress` event emitted during export and import. */
+export interface BackupProgressEvent {
+  phase: 'export' | 'rollback' | 'import';
+  table: string;
+  tableIndex: number;
+  tableCount: number;
+  rowsDone: number;
+  rowsTotal: number;
+}
+
 export interface ValidateBackupRequest {
   path: string;
 }
@@ -289,6 +568,71 @@ export interface ImportBackupResponse {
   localState: BackupLocalState;
 }
 
+export interface ImportTablePreview {
+  table: string;
+  currentRows: number;
+  incomingRows: number;
+  excluded: boolean;
+}
+
+export interface PreviewImportResponse {
+  mode: ImportBackupRequest['mode'];
+  delta: boolean;
+  sanitized: boolean;
+  tables: ImportTablePreview[];
+}
+
+export interface BackupMeta {
+  app: string;
+  exportedAt: string;
+  platform: string;
+  includeSecrets: boolean;
+  deltaSince?: string;
+  excludedTables?: string[];
+}
+
+export interface BackupFileEntry {
+  path: string;
+  fileName: string;
+  kind: 'rollback' | 'auto' | 'manual';
+  createdAt?: string;
+  sizeBytes: number;
+  meta?: BackupMeta;
+}
+
+export interface InspectBackupResponse {
+  schemaVersion: string;
+  meta: BackupMeta;
+  tableCounts: Record<string, number>;
+  mcpServers: number;
+  commands: number;
+  skills: number;
+}
+
+export interface BackupTableDiff {
+  table: string;
+  added: number;
+  removed: number;
+  changed: number;
+}
+
+export interface BackupNameDiff {
+  onlyInA: string[];
+  onlyInB: string[];
+}
+
+export interface DiffBackupsResponse {
+  tables: BackupTableDiff[];
+  mcpServers: BackupNameDiff;
+  commands: BackupNameDiff;
+  skills: BackupNameDiff;
+}
+
+export interface ListBackupsResponse {
+  backups: BackupFileEntry[];
+  warnings: string[];
+}
+
 export async function validateBackup(request: ValidateBackupRequest): Promise<ValidateBackupResponse> {
   return invoke('validate_backup', { request });
 }
@@ -297,6 +641,86 @@ export async function exportBackup(request: ExportBackupRequest): Promise<Export
   return invoke('export_backup', { request });
 }
 
+export async function previewImport(
+  path: string,
+  mode: ImportBackupRequest['mode']
+): Promise<PreviewImportResponse> {
+  return invoke('preview_import', { path, mode });
+}
+
 export async function importBackup(request: ImportBackupRequest): Promise<ImportBackupResponse> {
   return invoke('import_backup', { request });
 }
+
+export async function listBackups(): Promise<ListBackupsResponse> {
+  return invoke('list_backups');
+}
+
+export interface DbHealthReport {
+  initialized: boolean;
+  writable: boolean;
+  path?: string;
+  sizeBytes?: number;
+  message: string;
+}
+
+export async function dbHealthCheck(): Promise<DbHealthReport> {
+  return invoke('db_health_check');
+}
+
+export interface StartupStatus {
+  databaseReady: boolean;
+  dataDir?: string;
+  error?: string;
+  warning?: string;
+}
+
+export async function getStartupStatus(): Promise<StartupStatus> {
+  return invoke('get_startup_status');
+}
+
+export interface UpdateAppSettingsRequest {
+  /** e.g. `+08:00`; an empty string follows the system timezone, omit to keep the stored value. */
+  utcOffset?: string;
+  /** Proxy URL or `off`; an empty string clears it, omit to keep the stored value. */
+  proxy?: string;
+}
+
+export async function getAppSettings(): Promise<AppSettings> {
+  return invoke('get_app_settings');
+}
+
+export async function updateAppSettings(request: UpdateAppSettingsRequest): Promise<AppSettings> {
+  return invoke('update_app_settings', { request });
+}
+
+export interface VacuumResult {
+  beforeBytes: number;
+  afterBytes: number;
+  reclaimedBytes: number;
+}
+
+export async function vacuumDatabase(): Promise<VacuumResult> {
+  return invoke('vacuum_database');
+}
+
+export interface StorageReport {
+  dataDir: string;
+  databaseBytes: number;
+  walBytes: number;
+  backupsBytes: number;
+  agentBytes: number;
+  totalBytes: number;
+}
+
+export async function getStorageReport(): Promise<StorageReport> {
+  return invoke('get_storage_report');
+}
+
+export async function inspectBackup(path: string): Promise<InspectBackupResponse> {
+  return invoke('inspect_backup', { path });
+}
+
+export async function diffBackups(pathA: string, pathB: string): Promise<DiffBackupsResponse> {
+  return invoke('diff_backups', { pathA, pathB });
+}
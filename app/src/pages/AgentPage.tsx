@@ -41,6 +41,7 @@ export function AgentPage() {
     auditLog,
     settings,
     commands,
+    toolingWarnings,
     isSending,
     isExecuting,
     sendMessage,
@@ -214,6 +215,14 @@ export function AgentPage() {
             </Button>
           </div>
 
+          {toolingWarnings.length > 0 && (
+            <div className="mb-3 rounded-lg border border-amber-400/30 bg-amber-500/10 px-3 py-2 text-xs text-amber-100 space-y-1">
+              {toolingWarnings.map((warning) => (
+                <p key={warning}>{warning}</p>
+              ))}
+            </div>
+          )}
+
           <div className="flex-1 overflow-auto rounded-lg bg-black/20 border border-white/10 p-3 space-y-3">
             {messages.map((message) => (
               <div
@@ -23,6 +23,7 @@ interface AgentState {
   settings: AgentSettings;
   capabilities: AgentCapabilities | null;
   commands: AgentCommand[];
+  toolingWarnings: string[];
   toolingLoading: boolean;
   isSending: boolean;
   isExecuting: boolean;
@@ -56,6 +57,7 @@ const DEFAULT_SETTINGS: AgentSettings = {
     mcpArgs: ['mcp-server'],
     requestTimeoutMs: 120000,
   },
+  autoExecute: true,
 };
 
 function createMessage(role: AgentMessage['role'], content: string): AgentMessage {
@@ -92,11 +94,13 @@ function getErrorMessage(error: unknown): string {
 }
 
 const STAGE_PERCENT: Record<AgentStreamEvent['stage'], number> = {
+  queued: 5,
   runtime_detect: 10,
   mcp_connect: 20,
   exec_fallback: 20,
   planning: 60,
   executing: 70,
+  awaiting_approval: 90,
   fallback: 90,
   completed: 100,
   error: 0,
@@ -137,6 +141,7 @@ export const useAgentStore = create<AgentState>()(
       settings: DEFAULT_SETTINGS,
       capabilities: null,
       commands: [],
+      toolingWarnings: [],
       toolingLoading: false,
       isSending: false,
       isExecuting: false,
@@ -144,7 +149,7 @@ export const useAgentStore = create<AgentState>()(
       sendMessage: async (content) => {
         const trimmed = content.trim();
         if (!trimmed || get().isSending) return;
-        const requestId = `req-${Date.now()}`;
+        const requestId = `req-${Date.now()}-${Math.random().toString(36).slice(2, 8)}`;
 
         const userMessage = createMessage('user', trimmed);
         set((state) => ({
@@ -372,6 +377,7 @@ export const useAgentStore = create<AgentState>()(
           const tooling = await agentApi.agentGetToolingConfig();
           set({
             commands: tooling.commands,
+            toolingWarnings: tooling.warnings,
             toolingLoading: false,
           });
         } catch (error) {
@@ -73,7 +73,7 @@ interface AppState {
   todos: Todo[];
   isLoadingTodos: boolean;
   fetchTodos: () => Promise<void>;
-  addTodo: (title: string, priority: 'normal' | 'urgent') => Promise<void>;
+  addTodo: (title: string, priority: Todo['priority']) => Promise<void>;
   toggleTodo: (id: string) => Promise<void>;
   deleteTodo: (id: string) => Promise<void>;
   updateTodoTitle: (id: string, title: string) => Promise<void>;
@@ -183,6 +183,10 @@ export const useAppStore = create<AppState>()(
       fetchTodos: async () => {
         set({ isLoadingTodos: true });
         try {
+          // Materialize today's recurring todos first; a failure must not block the list
+          await api.generateDueRecurringTodos().catch((error) => {
+            console.error('Failed to generate recurring todos:', error);
+          });
           const todos = await api.getTodos();
           set({ todos });
         } catch (error) {
@@ -309,6 +313,9 @@ export const useAppStore = create<AppState>()(
           );
           set((state) => ({ events: [...state.events, newEvent] }));
           toast.success('事件添加成功');
+          if (newEvent.warning) {
+            toast.info(newEvent.warning);
+          }
         } catch (error) {
           console.error('Failed to add event:', error);
           toast.error('添加事件失败: ' + (error instanceof Error ? error.message : '未知错误'));
@@ -6,6 +6,8 @@ export interface LlmProviderConfig {
   apiKey: string;
   model: string;
   apiVersion?: string;
+  requestsPerMinute?: number;
+  requestTimeoutMs?: number;
 }
 
 export interface AgentSettings {
@@ -16,6 +18,12 @@ export interface AgentSettings {
   slashMode: SlashMode;
   provider: LlmProvider;
   codex: AgentCodexConfig;
+  autoExecute: boolean;
+}
+
+export interface AgentProfile {
+  name: string;
+  settings: AgentSettings;
 }
 
 export interface AgentCodexConfig {
@@ -35,6 +43,10 @@ export interface McpServerConfig {
   env: Record<string, string>;
   cwd?: string;
   enabled: boolean;
+  /** 单次工具调用的超时秒数，默认 60 */
+  toolTimeoutSecs?: number;
+  /** 无法解密、需要重新填写的环境变量名 */
+  lockedEnv?: string[];
 }
 
 export interface SkillConfig {
@@ -47,6 +59,14 @@ export interface SkillConfig {
   source: 'builtin' | 'user';
 }
 
+export interface SkillValidationResult {
+  path: string;
+  source: SkillConfig['source'];
+  id?: string;
+  ok: boolean;
+  error?: string;
+}
+
 export interface AgentCommand {
   slug: string;
   title: string;
@@ -59,10 +79,25 @@ export interface AgentCommand {
   source: 'builtin' | 'user';
 }
 
+export interface AgentBundleSummary {
+  path: string;
+  mcpServers: number;
+  commands: number;
+  skills: number;
+  warnings: string[];
+}
+
+export interface CommandMarkdownPreview {
+  valid: boolean;
+  command?: AgentCommand;
+  error?: string;
+}
+
 export interface AgentToolingConfig {
   mcpServers: McpServerConfig[];
   skills: SkillConfig[];
   commands: AgentCommand[];
+  warnings: string[];
 }
 
 export interface AgentMessage {
@@ -74,6 +109,7 @@ export interface AgentMessage {
 
 export type AgentActionType =
   | 'todo.create'
+  | 'todo.bulk_create'
   | 'todo.update'
   | 'todo.delete'
   | 'project.create'
@@ -85,7 +121,12 @@ export type AgentActionType =
   | 'personal.create'
   | 'personal.update'
   | 'personal.delete'
-  | 'query.snapshot';
+  | 'personal.to_event'
+  | 'info.update_settings'
+  | 'query.snapshot'
+  | 'todo.list'
+  | 'project.list'
+  | 'event.list_by_date';
 
 export interface AgentActionProposal {
   id: string;
@@ -99,12 +140,29 @@ export interface AgentActionProposal {
 export interface AgentChatRequest {
   requestId?: string;
   messages: AgentMessage[];
-  settings: AgentSettings;
+  settings?: AgentSettings;
+  providerOverride?: string;
+  modelOverride?: string;
+  profile?: string;
+}
+
+export interface AgentPlanStep {
+  step: number;
+  description: string;
+  actionRef?: string;
 }
 
 export interface AgentChatResponse {
   reply: string;
   actions: AgentActionProposal[];
+  plan: AgentPlanStep[];
+}
+
+export interface AgentTokenEstimate {
+  provider: string;
+  method: 'tiktoken' | 'heuristic';
+  tokens: number;
+  chars: number;
 }
 
 export interface AgentExecuteRequest {
@@ -119,6 +177,7 @@ export interface AgentExecuteResponse {
 export interface AgentExecuteActionsRequest {
   requestId?: string;
   actions: AgentActionProposal[];
+  stopOnError?: boolean;
 }
 
 export interface AgentExecutionAuditRecord {
@@ -131,6 +190,7 @@ export interface AgentExecutionAuditRecord {
   afterState?: Record<string, unknown>;
   success: boolean;
   error?: string;
+  stopOnError: boolean;
   createdAt: string;
 }
 
@@ -144,11 +204,13 @@ export interface AgentExecuteActionsResponse {
 export interface AgentStreamEvent {
   requestId: string;
   stage:
+    | 'queued'
     | 'runtime_detect'
     | 'mcp_connect'
     | 'exec_fallback'
     | 'planning'
     | 'executing'
+    | 'awaiting_approval'
     | 'fallback'
     | 'completed'
     | 'error';
@@ -193,6 +255,31 @@ export interface AgentCapabilities {
   builtinTools: string[];
   skills: string[];
   mcpServers: string[];
+  mcpTools: McpToolInfo[];
+}
+
+export interface McpToolInfo {
+  server: string;
+  tool: string;
+  description?: string;
+}
+
+export interface McpToolCallResult {
+  content: unknown[];
+  text: string;
+  isError: boolean;
+}
+
+export interface AgentActionFieldSchema {
+  name: string;
+  type: 'string' | 'boolean' | 'integer' | 'number' | 'array';
+  required: boolean;
+}
+
+export interface AgentActionSchema {
+  type: AgentActionType;
+  readOnly: boolean;
+  fields: AgentActionFieldSchema[];
 }
 
 export interface ExecutionAuditRecord {
@@ -3,8 +3,23 @@ export interface Todo {
   id: string;
   title: string;
   completed: boolean;
-  priority: 'normal' | 'urgent';
+  priority: 'low' | 'normal' | 'high' | 'urgent';
   createdAt: string;
+  completedAt?: string;
+  projectId?: string;
+  snoozedUntil?: string;
+  pinned: boolean;
+}
+
+// 周期待办模板
+export interface RecurringTodoTemplate {
+  id: string;
+  title: string;
+  priority: Todo['priority'];
+  rrule: string;
+  startDate: string;
+  lastGeneratedDate?: string;
+  enabled: boolean;
 }
 
 // 长期项目
@@ -14,6 +29,7 @@ export interface Project {
   deadline: string;
   progress: number;
   status: 'active' | 'completed';
+  pinned: boolean;
 }
 
 // 日程事件
@@ -23,6 +39,9 @@ export interface CalendarEvent {
   date: string;
   color: 'blue' | 'orange' | 'green' | 'teal' | 'red';
   note?: string;
+  pinned: boolean;
+  /** 创建/更新时颜色不在调色板内、已改用 blue 的提示 */
+  warning?: string;
 }
 
 // 个人事务
@@ -33,11 +52,50 @@ export interface PersonalTask {
   date?: string;
   location?: string;
   note?: string;
+  completed: boolean;
+  completedAt?: string;
+}
+
+export interface MonthDigest {
+  yearMonth: string;
+  startDate: string;
+  endDate: string;
+  events: CalendarEvent[];
+  projects: Project[];
+  personalTasks: PersonalTask[];
+  totalBudget: number;
+}
+
+export interface WorkbenchStats {
+  todosTotal: number;
+  todosCompleted: number;
+  projectsActive: number;
+  projectsCompleted: number;
+  weekStart: string;
+  weekEnd: string;
+  eventsThisWeek: number;
+  personalTasksTotal: number;
+  personalTasksCompleted: number;
+  personalBudgetTotal: number;
+  personalBudgetPending: number;
+  inspirationsTotal: number;
+  infoItemsToday: number;
 }
 
+export interface UpcomingItem {
+  kind: 'event';
+  id: string;
+  title: string;
+  date: string;
+  color?: CalendarEvent['color'];
+}
+
+export type InspirationStatus = 'draft' | 'published' | 'archived';
+
 export interface Inspiration {
   id: string;
   content: string;
+  status: InspirationStatus;
   isArchived: boolean;
   createdAt: string;
   updatedAt: string;
@@ -53,12 +111,23 @@ export interface HotItem {
 export interface InfoSource {
   id: string;
   name: string;
-  type: 'rss';
+  type: 'rss' | 'atom' | 'json';
   url: string;
   enabled: boolean;
   isPreset: boolean;
   createdAt?: string;
   updatedAt?: string;
+  lastFetchStatus?: 'ok' | 'empty' | 'error';
+  lastFetchError?: string;
+  lastFetchedAt?: string;
+  authType: 'none' | 'basic' | 'bearer';
+}
+
+export interface AppSettings {
+  /** Fixed UTC offset such as `+08:00` that decides "today"; unset follows the system timezone. */
+  utcOffset?: string;
+  /** Proxy URL for all outbound requests, or `off`; unset uses the proxy env vars. */
+  proxy?: string;
 }
 
 export interface InfoSettings {
@@ -66,6 +135,11 @@ export interface InfoSettings {
   includeKeywords: string[];
   excludeKeywords: string[];
   maxItemsPerDay: number;
+  maxConcurrentFetches: number;
+  retentionDays: number;
+  quietHoursStart?: string;
+  quietHoursEnd?: string;
+  nextRefreshAt?: string;
 }
 
 export interface InfoItem {
@@ -80,12 +154,31 @@ export interface InfoItem {
   fetchedAt: string;
 }
 
+export interface InfoItemContent {
+  link: string;
+  title?: string;
+  content: string;
+  fetchedAt: string;
+}
+
 export interface InfoRefreshResponse {
   success: boolean;
+  /** Another refresh was already running, so this one did nothing. */
+  skipped: boolean;
   fetchedCount: number;
   keptCount: number;
   message: string;
   refreshedAt: string;
+  triggerType: string;
+}
+
+export interface InfoSourceValidation {
+  sourceId: string;
+  name: string;
+  status: 'ok' | 'empty' | 'error';
+  title?: string;
+  itemCount: number;
+  error?: string;
 }
 
 export interface InfoRefreshStatus {